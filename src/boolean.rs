@@ -0,0 +1,285 @@
+use crate::hull::lexicographic;
+use crate::multipolygon::MultiPolygon;
+use crate::point::Point;
+use crate::polygon::{Location, Polygon, simplify, split_loops};
+use crate::scalar::{Scalar, Field};
+use crate::segment::Segment;
+use crate::sweep::subdivide;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+
+//Which parts of the two shapes to keep
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+	Union,
+	Intersection,
+	//the first shape minus the second
+	Difference,
+	//in exactly one of the shapes
+	Xor,
+}
+
+impl BooleanOp {
+	//whether a point inside the first shape or not, and the second, is in the result
+	pub fn keeps(self, a: bool, b: bool) -> bool {
+		match self {
+			BooleanOp::Union => a || b,
+			BooleanOp::Intersection => a && b,
+			BooleanOp::Difference => a && !b,
+			BooleanOp::Xor => a != b,
+		}
+	}
+}
+
+//A piece of boundary that no other piece crosses, p before q lexicographically.
+//Index 0 is about the first shape, 1 about the second
+struct Edge<T> {
+	p: Point<T>,
+	q: Point<T>,
+	//crossing the edge enters or leaves the shape
+	toggles: [bool; 2],
+	//the region just below the edge, or right of it if vertical, is inside the shape
+	below: [bool; 2],
+}
+
+impl<T> Edge<T> {
+	fn above(&self) -> [bool; 2] {
+		[self.below[0] != self.toggles[0], self.below[1] != self.toggles[1]]
+	}
+}
+
+// Union, intersection, difference or xor of two sets of polygons, in the
+// style of Martinez-Rueda. Every ring is cut at every crossing, pieces shared
+// by both shapes are merged, and a sweep finds which shapes lie on either side
+// of each piece. The pieces with the result on one side only are then linked
+// into rings, cut apart where they touch at a vertex.
+// Polygons in a set should not overlap each other. Rings are read even-odd,
+// so holes may run either way. Pieces are split into simple counterclockwise
+// polygons with clockwise holes. The sweep is O((n + k) log n) for n edges
+// and k crossings, with its status in a BTreeSet. Each hole in the result is
+// then tested against each exterior to find the one it belongs to. Every
+// decision is an exact orientation test, but crossing points are computed in
+// T, so the result is only exact for Rational
+pub fn boolean<T: Field>(a: &[Polygon<T>], b: &[Polygon<T>], op: BooleanOp) -> MultiPolygon<T> {
+	let mut segments = Vec::new();
+	let mut owners = Vec::new();
+	for (owner, shape) in [a, b].iter().enumerate() {
+		for ring in shape.iter().flat_map(|poly| poly.rings()) {
+			let n = ring.len();
+			for i in 0..n {
+				let (p, q) = (ring[i].clone(), ring[(i + 1) % n].clone());
+				if !p.equals(&q) {
+					segments.push(Segment { p1: p, p2: q });
+					owners.push(owner);
+				}
+			}
+		}
+	}
+
+	//a piece covered twice by the same shape is no boundary of it
+	let mut index = HashMap::new();
+	let mut edges: Vec<Edge<T>> = Vec::new();
+	for (cut, owner) in subdivide(&segments).into_iter().zip(owners) {
+		for w in cut.windows(2) {
+			let (p, q) = if lexicographic(&w[0], &w[1]) == Ordering::Less {(w[0].clone(), w[1].clone())} else {(w[1].clone(), w[0].clone())};
+			let i = *index.entry((key(&p), key(&q))).or_insert_with(|| {
+				edges.push(Edge { p, q, toggles: [false; 2], below: [false; 2] });
+				edges.len() - 1
+			});
+			edges[i].toggles[owner] = !edges[i].toggles[owner];
+		}
+	}
+	edges.retain(|e| e.toggles != [false; 2]);
+
+	sweep(&mut edges);
+
+	//keep each piece with the result on its left
+	let mut kept = Vec::new();
+	for e in &edges {
+		let below = op.keeps(e.below[0], e.below[1]);
+		let above = e.above();
+		let above = op.keeps(above[0], above[1]);
+		if below != above {
+			kept.push(if above {(e.p.clone(), e.q.clone())} else {(e.q.clone(), e.p.clone())});
+		}
+	}
+
+	//walk each ring taking the sharpest left turn, which keeps to one face
+	//even where rings touch at a vertex
+	let mut outgoing: HashMap<_, Vec<usize>> = HashMap::new();
+	for (i, (p, _)) in kept.iter().enumerate() {
+		outgoing.entry(key(p)).or_default().push(i);
+	}
+	let mut used = vec![false; kept.len()];
+	let mut exteriors = Vec::new();
+	let mut holes = Vec::new();
+	for start in 0..kept.len() {
+		let mut ring = Vec::new();
+		let mut i = start;
+		while !used[i] {
+			used[i] = true;
+			let (u, v) = &kept[i];
+			ring.push(u.clone());
+			//with rounded crossing points a ring may not close, so stop at a dead end
+			match outgoing.get(&key(v)).and_then(|out| out.iter().min_by(|&&x, &&y| clockwise_from(v, u, &kept[x].1, &kept[y].1))) {
+				Some(&next) => i = next,
+				None => break,
+			}
+		}
+		if ring.is_empty() {
+			continue;
+		}
+
+		//a ring can still pass through a point twice where a hole touches its exterior
+		for piece in split_loops(ring) {
+			let poly = Polygon { points: simplify(&piece), orientation: false, holes: Vec::new() };
+			if poly.points.len() < 3 {
+				continue;
+			}
+			match poly.signed_double_area().partial_cmp(&T::zero()) {
+				Some(Ordering::Greater) => exteriors.push(poly),
+				Some(Ordering::Less) => holes.push(poly.points),
+				_ => {}
+			}
+		}
+	}
+
+	//each hole belongs to the smallest exterior around it
+	let mut owners = Vec::new();
+	for hole in &holes {
+		let mut best: Option<usize> = None;
+		for (i, outer) in exteriors.iter().enumerate() {
+			if encloses(outer, hole) && best.is_none_or(|b| outer.double_area() < exteriors[b].double_area()) {
+				best = Some(i);
+			}
+		}
+		owners.push(best);
+	}
+	for (hole, owner) in holes.into_iter().zip(owners) {
+		if let Some(i) = owner {
+			exteriors[i].holes.push(hole);
+		}
+	}
+	MultiPolygon::new(exteriors)
+}
+
+fn key<T: Scalar>(p: &Point<T>) -> (T::Key, T::Key) {
+	(p.x.key(), p.y.key())
+}
+
+//Fills in below for every edge from the edge under its left end, sweeping
+//left to right. Edges only meet at their ends, so the order of the edges
+//crossing the sweep line never changes while both are in it
+fn sweep<T: Scalar>(edges: &mut [Edge<T>]) {
+	let mut events = Vec::new();
+	for (i, e) in edges.iter().enumerate() {
+		events.push((e.p.clone(), i, true));
+		events.push((e.q.clone(), i, false));
+	}
+	//ends before starts at the same point, and starts from the bottom up
+	events.sort_by(|x, y| {
+		lexicographic(&x.0, &y.0).then(x.2.cmp(&y.2)).then_with(|| {
+			if !x.2 {
+				return Ordering::Equal;
+			}
+			match x.0.orientation(&edges[x.1].q, &edges[y.1].q) {
+				2 => Ordering::Less,
+				1 => Ordering::Greater,
+				_ => Ordering::Equal,
+			}
+		})
+	});
+
+	//the status borrows the edges, so what is below each is filled in afterwards
+	let mut below = vec![[false; 2]; edges.len()];
+	let mut status: BTreeSet<Place<T>> = BTreeSet::new();
+	let place = |i: usize| Place { edges: &*edges, i };
+	for (_, i, starts) in events {
+		if !starts {
+			status.remove(&place(i));
+			continue;
+		}
+		if let Some(s) = status.range(..place(i)).next_back() {
+			let toggles = edges[s.i].toggles;
+			below[i] = [below[s.i][0] != toggles[0], below[s.i][1] != toggles[1]];
+		}
+		status.insert(place(i));
+	}
+	for (e, below) in edges.iter_mut().zip(below) {
+		e.below = below;
+	}
+}
+
+// An edge in the sweep status, ordered bottom to top by under. Edges in the
+// status never cross, so any two compare the same way while both are in it
+struct Place<'a, T> {
+	edges: &'a [Edge<T>],
+	i: usize,
+}
+
+impl<T: Scalar> Ord for Place<'_, T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		let (s, e) = (&self.edges[self.i], &self.edges[other.i]);
+		if self.i == other.i {
+			Ordering::Equal
+		} else if lexicographic(&s.p, &e.p) == Ordering::Greater {
+			other.cmp(self).reverse()
+		} else if under(s, e) {
+			Ordering::Less
+		} else {
+			Ordering::Greater
+		}
+	}
+}
+
+impl<T: Scalar> PartialOrd for Place<'_, T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T: Scalar> PartialEq for Place<'_, T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.i == other.i
+	}
+}
+
+impl<T: Scalar> Eq for Place<'_, T> {}
+
+//s is below e where e starts. A shared start is ordered by direction
+fn under<T: Scalar>(s: &Edge<T>, e: &Edge<T>) -> bool {
+	match s.p.orientation(&s.q, &e.p) {
+		0 => e.p.orientation(&s.q, &e.q) == 2,
+		o => o == 2,
+	}
+}
+
+//Orders the directions from v to a and b by how far clockwise they are from
+//the direction to u, with u itself last
+pub(crate) fn clockwise_from<T: Scalar>(v: &Point<T>, u: &Point<T>, a: &Point<T>, b: &Point<T>) -> Ordering {
+	let group = |p: &Point<T>| match v.orientation(u, p) {
+		1 => 0,
+		2 => 2,
+		_ if lexicographic(v, u) == lexicographic(v, p) => 3,
+		_ => 1,
+	};
+	group(a).cmp(&group(b)).then_with(|| match v.orientation(a, b) {
+		1 => Ordering::Less,
+		2 => Ordering::Greater,
+		_ => Ordering::Equal,
+	})
+}
+
+//ring lies inside the exterior of outer, judged by the first of its vertices
+//and edge midpoints that is off the boundary
+fn encloses<T: Field>(outer: &Polygon<T>, ring: &[Point<T>]) -> bool {
+	let two = T::from_i32(2);
+	let n = ring.len();
+	let mids = (0..n).map(|i| {
+		let (a, b) = (&ring[i], &ring[(i + 1) % n]);
+		Point::new((a.x.clone() + b.x.clone())/two.clone(), (a.y.clone() + b.y.clone())/two.clone())
+	});
+	ring.iter().cloned().chain(mids)
+		.map(|p| outer.contains_point(&p))
+		.find(|&l| l != Location::OnBoundary) == Some(Location::Inside)
+}
@@ -1,85 +1,183 @@
+use crate::delaunay::circumcenter;
+use crate::error::GeometryError;
 use crate::point::Point;
+use crate::random::{Lcg, random_seed};
+use crate::scalar::Float;
+use crate::tolerance::Tolerance;
 
-const EPSILON: f64 = 0.00001;
-const PI: f64 = std::f64::consts::PI;
 //Struct
-pub struct Circle {
-	center: Point,
-	radius: f64,
+pub struct Circle<T = f64> {
+	center: Point<T>,
+	radius: T,
 }
 
 //Constructor
-impl Circle {
-	pub fn new(center: Point, radius: f64) -> Self {
-		if radius > 0.0 { 
-			return Circle {
-				center,
-				radius,
+impl<T: Float> Circle<T> {
+	//radius must be non-negative. NaN compares as None and is rejected too
+	pub fn new(center: Point<T>, radius: T) -> Result<Self, GeometryError> {
+		if radius.partial_cmp(&T::zero()).is_none_or(|o| o == std::cmp::Ordering::Less) {
+			return Err(GeometryError::InvalidRadius);
+		}
+		Ok(Circle {
+			center,
+			radius,
+		})
+	}
+
+	//smallest circle holding every point, Welzl's algorithm in expected O(n)
+	//over a random shuffle. Every point is then at zero distance_from_point
+	pub fn enclosing(points: &[Point<T>]) -> Result<Self, GeometryError> {
+		Circle::enclosing_seeded(points, random_seed())
+	}
+
+	//the seed only picks the shuffle, so the same input and seed always give the same circle
+	pub fn enclosing_seeded(points: &[Point<T>], seed: u64) -> Result<Self, GeometryError> {
+		if points.is_empty() {
+			return Err(GeometryError::TooFewPoints { needed: 1, found: 0 });
+		}
+		let mut order = points.to_vec();
+		Lcg::new(seed).shuffle(&mut order);
+
+		let mut circle = Circle { center: order[0], radius: T::zero() };
+		for i in 1..order.len() {
+			if circle.center.distance(&order[i]) <= circle.radius {
+				continue;
+			}
+			//order[i] is on the smallest circle holding the first i + 1 points
+			circle = Circle { center: order[i], radius: T::zero() };
+			for j in 0..i {
+				if circle.center.distance(&order[j]) <= circle.radius {
+					continue;
+				}
+				//and so is order[j]
+				circle = Circle::diameter(&order[i], &order[j]);
+				for k in 0..j {
+					if circle.center.distance(&order[k]) > circle.radius {
+						circle = Circle::through(&order[i], &order[j], &order[k]);
+					}
+				}
 			}
 		}
-		Circle {
-			center, 
-			radius: 0.0,
+		//rounding can leave a point just outside, so reach the farthest one
+		circle.radius = order.iter().fold(circle.radius, |r, p| r.max(circle.center.distance(p)));
+		Ok(circle)
+	}
+
+	fn diameter(a: &Point<T>, b: &Point<T>) -> Self {
+		let center = Point::new((a.x + b.x)/T::from_i32(2), (a.y + b.y)/T::from_i32(2));
+		Circle { center, radius: center.distance(a).max(center.distance(b)) }
+	}
+
+	//collinear points fall back to the widest pair
+	fn through(a: &Point<T>, b: &Point<T>, c: &Point<T>) -> Self {
+		match circumcenter(a, b, c) {
+			Some(center) => Circle { center, radius: center.distance(a).max(center.distance(b)).max(center.distance(c)) },
+			None => {
+				let mut widest = Circle::diameter(a, b);
+				for (p, q) in [(b, c), (a, c)].iter() {
+					let d = Circle::diameter(p, q);
+					if d.radius > widest.radius {
+						widest = d;
+					}
+				}
+				widest
+			}
 		}
 	}
 }
 
 //Methods
-impl Circle {
-	pub fn equals(&self, other:&Circle) -> bool {
+impl<T: Float> Circle<T> {
+	pub fn center(&self) -> Point<T> {
+		self.center
+	}
+
+	pub fn radius(&self) -> T {
+		self.radius
+	}
+
+	pub fn equals(&self, other:&Circle<T>) -> bool {
 		self.center.equals(&other.center) && self.radius == other.radius
 	} 
 
-	pub fn circumference(&self) -> f64 {
-		2.0*PI*self.radius
+	pub fn circumference(&self) -> T {
+		T::from_i32(2)*T::pi()*self.radius
 	}
 
-	pub fn area(&self) -> f64 {
-		2.0*PI*self.radius*self.radius
+	pub fn area(&self) -> T {
+		T::from_i32(2)*T::pi()*self.radius*self.radius
 	}
 
-	pub fn intersects_circle(&self, other: &Circle) -> bool {
+	pub fn intersects_circle(&self, other: &Circle<T>) -> bool {
 		self.center.distance(&other.center) < self.radius + other.radius 
 	}
 
-	pub fn distance_from_circle(&self, other: &Circle) -> f64 {
+	//circles that are tangent within the tolerance also count
+	pub fn intersects_circle_tol(&self, other: &Circle<T>, tol: &Tolerance) -> bool {
+		let d = self.center.distance(&other.center);
+		d < self.radius + other.radius || tol.approx_eq(d.to_f64(), (self.radius + other.radius).to_f64())
+	}
+
+	pub fn distance_from_circle(&self, other: &Circle<T>) -> T {
 		if self.intersects_circle(other) {
-			return 0.0;
+			return T::zero();
 		}
 		self.center.distance(&other.center) - self.radius - other.radius 
 	}
 
-	pub fn distance_from_point(&self, p:&Point) -> f64 {
-		let d = self.center.distance(&p) - self.radius;
-		if d < 0.0 {
-			return 0.0;
+	pub fn distance_from_circle_tol(&self, other: &Circle<T>, tol: &Tolerance) -> T {
+		if self.intersects_circle_tol(other, tol) {
+			return T::zero();
+		}
+		self.center.distance(&other.center) - self.radius - other.radius
+	}
+
+	pub fn distance_from_point(&self, p:&Point<T>) -> T {
+		let d = self.center.distance(p) - self.radius;
+		if d < T::zero() {
+			return T::zero();
 		}
 		d
 	}
 
-	pub fn contains_circle(&self, other: &Circle) -> bool {
+	//points within tolerance of the boundary are on the circle
+	pub fn distance_from_point_tol(&self, p:&Point<T>, tol: &Tolerance) -> T {
+		let d = self.center.distance(p);
+		if d < self.radius || tol.approx_eq(d.to_f64(), self.radius.to_f64()) {
+			return T::zero();
+		}
+		d - self.radius
+	}
+
+	pub fn contains_circle(&self, other: &Circle<T>) -> bool {
 		self.center.distance(&other.center) + other.radius < self.radius 
 	}
 
+	//internally tangent within the tolerance also counts
+	pub fn contains_circle_tol(&self, other: &Circle<T>, tol: &Tolerance) -> bool {
+		let d = self.center.distance(&other.center) + other.radius;
+		d < self.radius || tol.approx_eq(d.to_f64(), self.radius.to_f64())
+	}
+
 	//p1 and p2 are points that lie on the circle
-	pub fn arc_length(&self, p1: &Point, p2: &Point) ->f64 {
+	pub fn arc_length(&self, p1: &Point<T>, p2: &Point<T>) ->T {
 		let c = self.circumference();
-		let d1 = self.center.distance(&p1);
-		let d2 = self.center.distance(&p2);
-		let d3 = p1.distance(&p2);
+		let d1 = self.center.distance(p1);
+		let d2 = self.center.distance(p2);
+		let d3 = p1.distance(p2);
 
 		let n = (d1*d1) + (d2*d2) - (d3*d3);
-		let d = 2.0*d1*d2;
+		let d = T::from_i32(2)*d1*d2;
 
 		let angle = (n/d).acos();
 		//length essentially is % of the circle between the given points
-		let length = angle/(2.0*PI);
+		let length = angle/(T::from_i32(2)*T::pi());
 
 		c*length
 	}
 
 	//Angle must be in radians
-	pub fn arc_length_angle(&self, angle:f64) -> f64 {
+	pub fn arc_length_angle(&self, angle:T) -> T {
 		self.radius*angle
 	}
 
@@ -0,0 +1,163 @@
+use crate::boolean::clockwise_from;
+use crate::multipolygon::MultiPolygon;
+use crate::point::Point;
+use crate::polygon::{Location, Polygon, simplify, split_loops};
+use crate::scalar::Field;
+use crate::segment::Segment;
+use crate::sweep::subdivide;
+use std::collections::{HashMap, HashSet};
+
+// Sutherland-Hodgman. Clips every ring of subject to a convex window given in
+// either order, one window edge at a time. O(n*m) for n subject and m window
+// vertices with no other work, so it suits clipping many shapes to a viewport.
+// A concave subject that the window cuts into several pieces comes back as one
+// ring with the pieces joined by zero-width edges along the window boundary.
+// None if nothing is left of the exterior
+pub fn sutherland_hodgman<T: Field>(subject: &Polygon<T>, window: &Polygon<T>) -> Option<Polygon<T>> {
+	let points = clip_ring(&subject.points, window)?;
+	let holes = subject.holes.iter().filter_map(|hole| clip_ring(hole, window)).collect();
+	let mut poly = Polygon { points, orientation: false, holes };
+	poly.orientation = poly.signed_double_area() < T::zero();
+	Some(poly)
+}
+
+// ring cut down to the inside of each window edge in turn
+fn clip_ring<T: Field>(ring: &[Point<T>], window: &Polygon<T>) -> Option<Vec<Point<T>>> {
+	let w = &window.points;
+	let n = w.len();
+	//the inside is left of each edge for a counterclockwise window
+	let inside = if window.orientation {1} else {2};
+	let mut input = Vec::with_capacity(ring.len());
+	let mut output = ring.to_vec();
+	for i in 0..n {
+		let (c, d) = (&w[i], &w[(i + 1) % n]);
+		if c.equals(d) {
+			continue;
+		}
+		std::mem::swap(&mut input, &mut output);
+		output.clear();
+		let m = input.len();
+		for j in 0..m {
+			let (a, b) = (&input[(j + m - 1) % m], &input[j]);
+			let (sa, sb) = (c.orientation(d, a), c.orientation(d, b));
+			//a point on the edge line counts as inside, so it is kept once as it is
+			if sb == inside || sb == 0 {
+				if sa != inside && sa != 0 && sb != 0 {
+					output.push(crossing(c, d, a, b));
+				}
+				output.push(b.clone());
+			} else if sa == inside {
+				output.push(crossing(c, d, a, b));
+			}
+		}
+		if output.len() < 3 {
+			return None;
+		}
+	}
+	Some(output)
+}
+
+// where the segment from a to b crosses the line through c and d. a and b
+// must be strictly on opposite sides
+pub(crate) fn crossing<T: Field>(c: &Point<T>, d: &Point<T>, a: &Point<T>, b: &Point<T>) -> Point<T> {
+	let (dx, dy) = (d.x.clone() - c.x.clone(), d.y.clone() - c.y.clone());
+	let da = dx.clone()*(a.y.clone() - c.y.clone()) - dy.clone()*(a.x.clone() - c.x.clone());
+	let db = dx*(b.y.clone() - c.y.clone()) - dy*(b.x.clone() - c.x.clone());
+	let t = da.clone()/(da - db);
+	Point::new(a.x.clone() + (b.x.clone() - a.x.clone())*t.clone(), a.y.clone() + (b.y.clone() - a.y.clone())*t)
+}
+
+// Weiler-Atherton. The parts of subject inside clip, for any two simple
+// polygons, concave or not. Both rings are cut where they meet, then the result
+// is traced along the subject while it is inside the clip polygon, switching
+// to the clip ring where the subject leaves it. Which edges are inside is
+// decided by their midpoints, so edges and vertices the two share are handled
+// like any other. Holes are ignored, Polygon::clip hands shapes with holes to
+// boolean instead.
+// O((n + m)*(n + m + k)) for n and m vertices and k crossings
+pub fn weiler_atherton<T: Field>(subject: &Polygon<T>, clip: &Polygon<T>) -> MultiPolygon<T> {
+	//both run counterclockwise, so the inside is always on the left
+	let shapes = [subject, clip].map(|shape| {
+		let mut points = shape.points.clone();
+		if shape.orientation {
+			points.reverse();
+		}
+		Polygon { points, orientation: false, holes: Vec::new() }
+	});
+	let mut segments = Vec::new();
+	let mut counts = [0; 2];
+	for (r, shape) in shapes.iter().enumerate() {
+		let n = shape.points.len();
+		for i in 0..n {
+			let (p, q) = (shape.points[i].clone(), shape.points[(i + 1) % n].clone());
+			if !p.equals(&q) {
+				segments.push(Segment { p1: p, p2: q });
+				counts[r] += 1;
+			}
+		}
+	}
+
+	let mut lists: [Vec<Point<T>>; 2] = [Vec::new(), Vec::new()];
+	for (i, mut cut) in subdivide(&segments).into_iter().enumerate() {
+		cut.pop();
+		lists[if i < counts[0] {0} else {1}].extend(cut);
+	}
+	let edge = |r: usize, j: usize| (lists[r][j].clone(), lists[r][(j + 1) % lists[r].len()].clone());
+	let key = |p: &Point<T>| (p.x.key(), p.y.key());
+
+	//an edge the two rings share is kept once, from the subject, if both run along it the same way
+	let clip_edges: HashSet<_> = (0..lists[1].len()).map(|j| {
+		let (u, v) = edge(1, j);
+		(key(&u), key(&v))
+	}).collect();
+	let two = T::from_i32(2);
+	let keep: Vec<Vec<bool>> = (0..2).map(|r| (0..lists[r].len()).map(|j| {
+		let (u, v) = edge(r, j);
+		let mid = Point::new((u.x.clone() + v.x.clone())/two.clone(), (u.y.clone() + v.y.clone())/two.clone());
+		match shapes[1 - r].contains_point(&mid) {
+			Location::Inside => true,
+			Location::OnBoundary => r == 0 && clip_edges.contains(&(key(&u), key(&v))),
+			Location::Outside => false,
+		}
+	}).collect()).collect();
+
+	let index: Vec<HashMap<_, usize>> = (0..2).map(|r| lists[r].iter().enumerate().map(|(j, p)| (key(p), j)).collect()).collect();
+	let mut used: Vec<Vec<bool>> = (0..2).map(|r| vec![false; lists[r].len()]).collect();
+	let mut polygons = Vec::new();
+	for first in 0..2 {
+		for start in 0..lists[first].len() {
+			if !keep[first][start] || used[first][start] {
+				continue;
+			}
+			let (mut r, mut j) = (first, start);
+			let mut ring = Vec::new();
+			loop {
+				used[r][j] = true;
+				let (u, v) = edge(r, j);
+				ring.push(u.clone());
+
+				//stay on this ring while it is inside the other, or switch where it leaves.
+				//Where both go on, take the sharper left turn to stay on one piece
+				let along = (j + 1) % lists[r].len();
+				let here = Some((r, along)).filter(|&(r, j)| keep[r][j] && !used[r][j]);
+				let there = index[1 - r].get(&key(&v)).map(|&k| (1 - r, k)).filter(|&(r, j)| keep[r][j] && !used[r][j]);
+				(r, j) = match (here, there) {
+					(Some(a), Some(b)) => {
+						let ends = (edge(a.0, a.1).1, edge(b.0, b.1).1);
+						if clockwise_from(&v, &u, &ends.0, &ends.1).is_le() {a} else {b}
+					}
+					(Some(a), None) | (None, Some(a)) => a,
+					(None, None) => break,
+				};
+			}
+
+			for piece in split_loops(ring) {
+				let poly = Polygon { points: simplify(&piece), orientation: false, holes: Vec::new() };
+				if poly.points.len() >= 3 && poly.signed_double_area() > T::zero() {
+					polygons.push(poly);
+				}
+			}
+		}
+	}
+	MultiPolygon::new(polygons)
+}
@@ -0,0 +1,880 @@
+use crate::clip::crossing;
+use crate::error::GeometryError;
+use crate::point::Point;
+use crate::polygon::Polygon;
+use crate::random::{Lcg, SEED};
+use crate::scalar::{Scalar, Field, Float};
+use crate::segment::Segment;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
+
+//TRIANGULATION
+// Triangles are counterclockwise index triples into points. The neighbour in
+// slot k of a triangle is across the edge opposite its vertex k, None on the
+// convex hull, and the same slot of constrained says whether that edge is a
+// constraint. A point repeated in the input is only used at its first index
+#[derive(Clone, Debug)]
+pub struct Triangulation<T = f64> {
+	pub(crate) points: Vec<Point<T>>,
+	pub(crate) triangles: Vec<[usize; 3]>,
+	pub(crate) neighbours: Vec<[Option<usize>; 3]>,
+	pub(crate) constrained: Vec<[bool; 3]>,
+}
+
+//Methods
+impl<T: Scalar> Triangulation<T> {
+	pub fn points(&self) -> &[Point<T>] {
+		&self.points
+	}
+
+	pub fn triangles(&self) -> &[[usize; 3]] {
+		&self.triangles
+	}
+
+	pub fn neighbours(&self) -> &[[Option<usize>; 3]] {
+		&self.neighbours
+	}
+
+	pub fn constrained(&self) -> &[[bool; 3]] {
+		&self.constrained
+	}
+
+	pub fn len(&self) -> usize {
+		self.triangles.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.triangles.is_empty()
+	}
+
+	// Keeps the triangles inside an odd number of loops of constrained edges,
+	// which for the edges of a polygon is its inside less its holes. Counted
+	// from the hull inwards, so constraints should form closed loops
+	pub fn retain_inside(&mut self) {
+		let n = self.triangles.len();
+		let mut inside: Vec<Option<bool>> = vec![None; n];
+		let mut queue = VecDeque::new();
+		for (t, (neighbours, constrained)) in self.neighbours.iter().zip(&self.constrained).enumerate() {
+			if let Some(k) = (0..3).find(|&k| neighbours[k].is_none()) {
+				inside[t] = Some(constrained[k]);
+				queue.push_back(t);
+			}
+		}
+		while let Some(t) = queue.pop_front() {
+			for k in 0..3 {
+				if let Some(m) = self.neighbours[t][k] {
+					if inside[m].is_none() {
+						inside[m] = Some(inside[t] != Some(self.constrained[t][k]));
+						queue.push_back(m);
+					}
+				}
+			}
+		}
+
+		let kept: Vec<usize> = (0..n).filter(|&t| inside[t] == Some(true)).collect();
+		let mut index = vec![None; n];
+		for (i, &t) in kept.iter().enumerate() {
+			index[t] = Some(i);
+		}
+		self.triangles = kept.iter().map(|&t| self.triangles[t]).collect();
+		self.neighbours = kept.iter().map(|&t| self.neighbours[t].map(|m| m.and_then(|m| index[m]))).collect();
+		self.constrained = kept.iter().map(|&t| self.constrained[t]).collect();
+	}
+
+	fn empty(points: &[Point<T>]) -> Self {
+		Triangulation { points: points.to_vec(), triangles: Vec::new(), neighbours: Vec::new(), constrained: Vec::new() }
+	}
+}
+
+impl<T: Float> Triangulation<T> {
+	// The smallest angle of any triangle, in radians. None if there are none
+	pub fn min_angle(&self) -> Option<T> {
+		self.triangles.iter().map(|&[a, b, c]| smallest_angle(&self.points[a], &self.points[b], &self.points[c])).reduce(T::min)
+	}
+}
+
+// Delaunay triangulation by Bowyer-Watson insertion. Each point removes every
+// triangle whose circumcircle holds it strictly and fills the hole with a fan
+// around it. The outside of the hull is covered by ghost triangles sharing a
+// vertex at infinity, so there is no bounding triangle to get wrong and hull
+// points are handled like any other. Points go in a fixed shuffled order and
+// are found by walking from the last new triangle.
+// Every decision is an exact orientation or incircle test. Cocircular points
+// give one of the valid triangulations. Fewer than three points, or all of
+// them in line, give no triangles
+pub fn delaunay<T: Scalar>(points: &[Point<T>]) -> Triangulation<T> {
+	match Mesh::build(points) {
+		Some(mesh) => mesh.finish(),
+		None => Triangulation::empty(points),
+	}
+}
+
+// Delaunay triangulation in which every constraint is a chain of edges. Each
+// constraint is flipped into place after the points are in; a point on it
+// splits it, and where two constraints cross a Steiner point is added. Points
+// are the given ones, then the ends of each constraint, then any Steiner
+// points. Crossing points are computed in T, so are only exact for Rational.
+// See Triangulation::retain_inside to drop the outside of a boundary and the
+// inside of holes
+pub fn constrained_delaunay<T: Field>(points: &[Point<T>], constraints: &[Segment<T>]) -> Triangulation<T> {
+	let mut all = points.to_vec();
+	all.extend(constraints.iter().flat_map(|s| [s.p1.clone(), s.p2.clone()]));
+	match Mesh::build_constrained(&all, points.len()) {
+		Some(mesh) => mesh.finish(),
+		None => Triangulation::empty(&all),
+	}
+}
+
+// Constrained Delaunay triangulation of the inside of a polygon, holes left
+// out. The first points are Polygon::vertices
+pub fn constrained_delaunay_polygon<T: Field>(poly: &Polygon<T>) -> Triangulation<T> {
+	let (points, segments) = boundary(poly);
+	let mut mesh = constrained_delaunay(&points, &segments);
+	mesh.retain_inside();
+	mesh
+}
+
+// Delaunay refinement in the style of Ruppert. Subsegments of the boundary
+// with a vertex inside their diametral circle are split, then each triangle
+// with an angle under min_angle, in radians, or an area over max_area gets a
+// vertex at its circumcenter, unless that would encroach on a subsegment,
+// which is split instead. A subsegment at an input vertex is split on a circle
+// round that vertex with a power of two radius rather than in half, and a
+// triangle is let be when its shortest edge joins two segments meeting at
+// under 60 degrees, so small input angles do not refine forever.
+// Only ends for bounds up to atan(1/sqrt(7)), about 20.7 degrees (0.3613), so
+// a larger min_angle or a max_area that is not positive is an InvalidBound.
+// Rounding can still keep it splitting the same places, so it gives up with
+// RefinementLimit after 1024 new points per polygon vertex plus 8 for each
+// max_area of the polygon's area. Triangulation::min_angle reports the result.
+// The first points are Polygon::vertices
+pub fn refine<T: Float>(poly: &Polygon<T>, min_angle: T, max_area: Option<T>) -> Result<Triangulation<T>, GeometryError> {
+	let bound = T::one().atan2(T::from_i32(7).sqrt());
+	let (angle_ok, area_ok) = (min_angle >= T::zero() && min_angle <= bound, max_area.is_none_or(|max| max > T::zero()));
+	if !angle_ok || !area_ok {
+		return Err(GeometryError::InvalidBound);
+	}
+	let (mut points, segments) = boundary(poly);
+	let inputs = points.len();
+	let per_area = max_area.map_or(0.0, |max| (poly.area()/max).to_f64().ceil());
+	let limit = inputs.saturating_mul(1024).saturating_add((8.0*per_area).min(usize::MAX as f64) as usize);
+	points.extend(segments.iter().flat_map(|s| [s.p1, s.p2]));
+	let Some(mut mesh) = Mesh::build_constrained(&points, inputs) else {
+		return Ok(Triangulation::empty(&points));
+	};
+	let mut refinement = Refinement::new(&mut mesh, inputs);
+	refinement.run(min_angle, max_area, limit)?;
+	let mut result = mesh.finish();
+	result.retain_inside();
+	Ok(result)
+}
+
+//the vertices of a polygon and the edges of its rings
+fn boundary<T: Scalar>(poly: &Polygon<T>) -> (Vec<Point<T>>, Vec<Segment<T>>) {
+	let points = poly.vertices().cloned().collect();
+	let mut segments = Vec::new();
+	for ring in poly.rings() {
+		let n = ring.len();
+		for i in 0..n {
+			if !ring[i].equals(&ring[(i + 1) % n]) {
+				segments.push(Segment { p1: ring[i].clone(), p2: ring[(i + 1) % n].clone() });
+			}
+		}
+	}
+	(points, segments)
+}
+
+// Center of the circle through a, b and c. None if they are in line
+pub(crate) fn circumcenter<T: Field>(a: &Point<T>, b: &Point<T>, c: &Point<T>) -> Option<Point<T>> {
+	let (bx, by, cx, cy) = (b.x.clone() - a.x.clone(), b.y.clone() - a.y.clone(), c.x.clone() - a.x.clone(), c.y.clone() - a.y.clone());
+	let d = T::from_i32(2)*(bx.clone()*cy.clone() - by.clone()*cx.clone());
+	if d == T::zero() {
+		return None;
+	}
+	let (b2, c2) = (bx.clone()*bx.clone() + by.clone()*by.clone(), cx.clone()*cx.clone() + cy.clone()*cy.clone());
+	Some(Point::new(a.x.clone() + (cy*b2.clone() - by*c2.clone())/d.clone(), a.y.clone() + (bx*c2 - cx*b2)/d))
+}
+
+//stands in for the point at infinity in ghost triangles
+pub(crate) const GHOST: usize = usize::MAX;
+
+// Working triangulation. Ghost triangles hold GHOST and a hull edge, with the
+// outside of the hull on the left of that edge. Removed triangles are left in
+// place and their slots reused
+pub(crate) struct Mesh<T> {
+	pub(crate) points: Vec<Point<T>>,
+	pub(crate) triangles: Vec<[usize; 3]>,
+	pub(crate) neighbours: Vec<[usize; 3]>,
+	pub(crate) fixed: Vec<[bool; 3]>,
+	pub(crate) alive: Vec<bool>,
+	free: Vec<usize>,
+	//points already in the mesh
+	pub(crate) inserted: Vec<bool>,
+	//a live real triangle to start walks from
+	last: usize,
+}
+
+impl<T: Scalar> Mesh<T> {
+	// Every distinct point inserted in a fixed shuffled order. None if they
+	// are all in line
+	pub(crate) fn build(points: &[Point<T>]) -> Option<Self> {
+		let mut seen = HashSet::new();
+		let mut order: Vec<usize> = (0..points.len()).filter(|&i| seen.insert((points[i].x.key(), points[i].y.key()))).collect();
+		Lcg::new(SEED).shuffle(&mut order);
+		let mut mesh = Mesh::new(points.to_vec(), &order)?;
+		for &p in &order {
+			mesh.insert(p);
+		}
+		Some(mesh)
+	}
+
+	// First triangle from the first three points of order not in line, with a
+	// ghost on each side. None if there are no such three
+	fn new(points: Vec<Point<T>>, order: &[usize]) -> Option<Self> {
+		let &a = order.first()?;
+		let &b = order.iter().find(|&&b| !points[b].equals(&points[a]))?;
+		let &c = order.iter().find(|&&c| points[a].orientation(&points[b], &points[c]) != 0)?;
+		let (b, c) = if points[a].orientation(&points[b], &points[c]) == 2 {(b, c)} else {(c, b)};
+
+		let mut inserted = vec![false; points.len()];
+		for v in [a, b, c] {
+			inserted[v] = true;
+		}
+		Some(Mesh {
+			points,
+			triangles: vec![[a, b, c], [b, a, GHOST], [c, b, GHOST], [a, c, GHOST]],
+			neighbours: vec![[2, 3, 1], [3, 2, 0], [1, 3, 0], [2, 1, 0]],
+			fixed: vec![[false; 3]; 4],
+			alive: vec![true; 4],
+			free: Vec::new(),
+			inserted,
+			last: 0,
+		})
+	}
+
+	// Adds point p and returns it, or the vertex already at the same place.
+	// The triangles removed are those whose circumcircles hold p that can be
+	// reached from it without crossing a constrained edge, unless p is on that
+	// edge, in which case the edge is split
+	pub(crate) fn insert(&mut self, p: usize) -> usize {
+		if self.inserted[p] {
+			return p;
+		}
+		let point = self.points[p].clone();
+		let start = self.locate(&point);
+		if let Some(&v) = self.triangles[start].iter().find(|&&v| v != GHOST && self.points[v].equals(&point)) {
+			return v;
+		}
+		self.inserted[p] = true;
+
+		//every triangle in conflict, found by spreading out from the one holding p
+		let mut cavity = vec![start];
+		let mut in_cavity = HashMap::new();
+		in_cavity.insert(start, true);
+		let mut split = Vec::new();
+		let mut i = 0;
+		while i < cavity.len() {
+			let t = cavity[i];
+			for k in 0..3 {
+				let (u, w) = self.edge(t, k);
+				let through = !self.fixed[t][k] || self.on_edge(u, w, &point);
+				if let (true, Entry::Vacant(entry)) = (through, in_cavity.entry(self.neighbours[t][k])) {
+					let n = *entry.key();
+					let conflict = self.conflicts(n, &point);
+					entry.insert(conflict);
+					if conflict {
+						cavity.push(n);
+						if self.fixed[t][k] {
+							split.extend([(u, p), (p, u), (w, p), (p, w)]);
+						}
+					}
+				}
+			}
+			i += 1;
+		}
+
+		//a fan from p to each edge on the edge of the cavity
+		let mut fan = Vec::new();
+		for &t in &cavity {
+			for k in 0..3 {
+				if in_cavity.get(&self.neighbours[t][k]) != Some(&true) {
+					let (u, w) = self.edge(t, k);
+					fan.push([u, w, p]);
+				}
+			}
+		}
+		self.fill(&cavity, fan, &split);
+		p
+	}
+
+	// Replaces the triangles of region with others covering the same ground.
+	// Edges on the outside of the region keep their neighbours and flags, and
+	// the new edges listed in fixed are constrained
+	pub(crate) fn fill(&mut self, region: &[usize], triangles: Vec<[usize; 3]>, fixed: &[(usize, usize)]) {
+		let inside: HashSet<usize> = region.iter().copied().collect();
+		let mut outside = HashMap::new();
+		for &t in region {
+			for k in 0..3 {
+				let n = self.neighbours[t][k];
+				if !inside.contains(&n) {
+					outside.insert(self.edge(t, k), (n, self.fixed[t][k]));
+				}
+			}
+		}
+		for &t in region {
+			self.alive[t] = false;
+			self.free.push(t);
+		}
+
+		let made: Vec<usize> = triangles.into_iter().map(|triangle| self.add(triangle)).collect();
+		let mut edges = HashMap::new();
+		for &t in &made {
+			for k in 0..3 {
+				edges.insert(self.edge(t, k), t);
+			}
+			if !self.triangles[t].contains(&GHOST) {
+				self.last = t;
+			}
+		}
+		for &t in &made {
+			for k in 0..3 {
+				let (u, w) = self.edge(t, k);
+				match outside.get(&(u, w)) {
+					Some(&(n, flag)) => {
+						self.neighbours[t][k] = n;
+						self.fixed[t][k] = flag;
+						let back = self.slot(n, w, u);
+						self.neighbours[n][back] = t;
+					}
+					None => {
+						self.neighbours[t][k] = edges[&(w, u)];
+						self.fixed[t][k] = fixed.contains(&(u, w));
+					}
+				}
+			}
+		}
+	}
+
+	fn add(&mut self, triangle: [usize; 3]) -> usize {
+		match self.free.pop() {
+			Some(t) => {
+				self.triangles[t] = triangle;
+				self.alive[t] = true;
+				t
+			}
+			None => {
+				self.triangles.push(triangle);
+				self.neighbours.push([0; 3]);
+				self.fixed.push([false; 3]);
+				self.alive.push(true);
+				self.triangles.len() - 1
+			}
+		}
+	}
+
+	//the edge opposite vertex k of t, counterclockwise
+	pub(crate) fn edge(&self, t: usize, k: usize) -> (usize, usize) {
+		(self.triangles[t][(k + 1) % 3], self.triangles[t][(k + 2) % 3])
+	}
+
+	//the slot of t opposite its edge from u to w
+	pub(crate) fn slot(&self, t: usize, u: usize, w: usize) -> usize {
+		(0..3).find(|&k| self.edge(t, k) == (u, w)).unwrap()
+	}
+
+	//constrains or frees the edge opposite vertex k of t, on both sides
+	pub(crate) fn set_fixed(&mut self, t: usize, k: usize, flag: bool) {
+		let (u, w) = self.edge(t, k);
+		let n = self.neighbours[t][k];
+		let back = self.slot(n, w, u);
+		self.fixed[t][k] = flag;
+		self.fixed[n][back] = flag;
+	}
+
+	//p is on the edge between vertices u and w, ends excluded
+	fn on_edge(&self, u: usize, w: usize, p: &Point<T>) -> bool {
+		if u == GHOST || w == GHOST {
+			return false;
+		}
+		let (a, b) = (&self.points[u], &self.points[w]);
+		a.orientation(b, p) == 0 && (a.x.clone() - p.x.clone())*(b.x.clone() - p.x.clone()) + (a.y.clone() - p.y.clone())*(b.y.clone() - p.y.clone()) < T::zero()
+	}
+
+	// p is strictly inside the circumcircle of t. For a ghost triangle that is
+	// strictly outside its hull edge, or on the edge between its ends
+	fn conflicts(&self, t: usize, p: &Point<T>) -> bool {
+		let [a, b, c] = self.triangles[t];
+		match [a, b, c].iter().position(|&v| v == GHOST) {
+			None => T::incircle_sign(&self.points[a], &self.points[b], &self.points[c], p) == Ordering::Greater,
+			Some(g) => {
+				let (u, w) = self.edge(t, g);
+				self.points[u].orientation(&self.points[w], p) == 2 || self.on_edge(u, w, p)
+			}
+		}
+	}
+
+	// A real triangle holding p, on its boundary included, or a ghost triangle
+	// whose hull edge has p strictly outside. Walks across any edge with p on
+	// the far side, which cannot circle in a Delaunay triangulation but can
+	// with constraints, so a long walk gives way to a search
+	pub(crate) fn locate(&self, p: &Point<T>) -> usize {
+		let beyond = |t: usize, k: usize| {
+			let (u, w) = self.edge(t, k);
+			self.points[u].orientation(&self.points[w], p) == 1
+		};
+		let mut t = self.last;
+		'walk: for _ in 0..self.triangles.len() {
+			for k in 0..3 {
+				if beyond(t, k) {
+					t = self.neighbours[t][k];
+					if self.triangles[t].contains(&GHOST) {
+						return t;
+					}
+					continue 'walk;
+				}
+			}
+			return t;
+		}
+		(0..self.triangles.len()).find(|&t| self.alive[t] && match self.triangles[t].iter().position(|&v| v == GHOST) {
+			None => (0..3).all(|k| !beyond(t, k)),
+			Some(g) => {
+				let (u, w) = self.edge(t, g);
+				self.points[u].orientation(&self.points[w], p) == 2
+			}
+		}).unwrap()
+	}
+
+	// The live real triangles, renumbered
+	pub(crate) fn finish(self) -> Triangulation<T> {
+		let real: Vec<usize> = (0..self.triangles.len()).filter(|&t| self.alive[t] && !self.triangles[t].contains(&GHOST)).collect();
+		let mut index = vec![None; self.triangles.len()];
+		for (i, &t) in real.iter().enumerate() {
+			index[t] = Some(i);
+		}
+		let triangles = real.iter().map(|&t| self.triangles[t]).collect();
+		let neighbours = real.iter().map(|&t| self.neighbours[t].map(|n| index[n])).collect();
+		let constrained = real.iter().map(|&t| self.fixed[t]).collect();
+		Triangulation { points: self.points, triangles, neighbours, constrained }
+	}
+}
+
+impl<T: Field> Mesh<T> {
+	// Every distinct point inserted, then each pair of points from index from on
+	// joined by a constraint
+	pub(crate) fn build_constrained(points: &[Point<T>], from: usize) -> Option<Self> {
+		let mut mesh = Mesh::build(points)?;
+		let mut first = HashMap::new();
+		for (i, p) in points.iter().enumerate() {
+			first.entry((p.x.key(), p.y.key())).or_insert(i);
+		}
+		let key = |p: &Point<T>| first[&(p.x.key(), p.y.key())];
+		for pair in points[from..].chunks(2) {
+			mesh.insert_segment(key(&pair[0]), key(&pair[1]));
+		}
+		Some(mesh)
+	}
+
+	// Makes the segment between vertices a and b a chain of constrained edges.
+	// A vertex on the way splits it, and a constrained edge across it is split
+	// at the crossing by a new point
+	pub(crate) fn insert_segment(&mut self, a: usize, b: usize) {
+		let mut todo = vec![(a, b)];
+		while let Some((a, b)) = todo.pop() {
+			if a == b {
+				continue;
+			}
+			let (pa, pb) = (self.points[a].clone(), self.points[b].clone());
+			let ahead = |v: usize, points: &[Point<T>]| {
+				let pv = &points[v];
+				pa.orientation(pv, &pb) == 0 && (pv.x.clone() - pa.x.clone())*(pb.x.clone() - pa.x.clone()) + (pv.y.clone() - pa.y.clone())*(pb.y.clone() - pa.y.clone()) > T::zero()
+			};
+
+			//turn round a to the edge the segment runs along, or the triangle it leaves a through
+			let mut t = self.locate(&pa);
+			let (start, u, w) = loop {
+				let i = self.triangles[t].iter().position(|&v| v == a).unwrap();
+				let (u, w) = self.edge(t, i);
+				if u != GHOST && ahead(u, &self.points) {
+					self.set_fixed(t, (i + 2) % 3, true);
+					break (None, u, w);
+				}
+				if u != GHOST && w != GHOST && pa.orientation(&self.points[u], &pb) == 2 && pa.orientation(&self.points[w], &pb) == 1 {
+					break (Some(t), u, w);
+				}
+				t = self.neighbours[t][(i + 1) % 3];
+			};
+			let Some(mut t) = start else {
+				todo.push((u, b));
+				continue;
+			};
+
+			//walk the triangles the segment cuts, gathering the edges it crosses
+			let (mut u, mut w) = (u, w);
+			let mut crossed = VecDeque::new();
+			let end = loop {
+				let k = self.slot(t, u, w);
+				if self.fixed[t][k] {
+					break Err(k);
+				}
+				crossed.push_back((u, w));
+				let n = self.neighbours[t][k];
+				let v = self.triangles[n][self.slot(n, w, u)];
+				t = n;
+				if v == b {
+					break Ok(b);
+				}
+				match pa.orientation(&pb, &self.points[v]) {
+					0 => break Ok(v),
+					2 => w = v,
+					_ => u = v,
+				}
+			};
+
+			match end {
+				Ok(e) => {
+					self.flip_in(a, e, crossed);
+					todo.push((e, b));
+				}
+				//split both at the crossing, and lay in the four pieces
+				Err(k) => {
+					let (c, d) = self.edge(t, k);
+					self.set_fixed(t, k, false);
+					self.points.push(crossing(&self.points[c], &self.points[d], &pa, &pb));
+					self.inserted.push(false);
+					let x = self.insert(self.points.len() - 1);
+					todo.extend([(c, x), (x, d), (x, b), (a, x)]);
+				}
+			}
+		}
+	}
+
+	// Sloan's method. Flips each edge crossing a to e whose two triangles make
+	// a convex quadrilateral, coming back to the others later, until none is
+	// left. The new edges are then flipped until they are Delaunay, and a to e
+	// is constrained. Constrained edges are never flipped, even ones reaching
+	// into the triangles cut
+	fn flip_in(&mut self, a: usize, e: usize, mut crossed: VecDeque<(usize, usize)>) {
+		let (pa, pe) = (self.points[a].clone(), self.points[e].clone());
+		let crosses = |x: usize, y: usize, points: &[Point<T>]| {
+			let (px, py) = (&points[x], &points[y]);
+			let (o1, o2) = (pa.orientation(&pe, px), pa.orientation(&pe, py));
+			let (o3, o4) = (px.orientation(py, &pa), px.orientation(py, &pe));
+			o1 != 0 && o2 != 0 && o1 != o2 && o3 != 0 && o4 != 0 && o3 != o4
+		};
+		let mut made = Vec::new();
+		while let Some((u, w)) = crossed.pop_front() {
+			match self.flip(u, w, false) {
+				Some((x, y)) if crosses(x, y, &self.points) => crossed.push_back((x, y)),
+				Some(edge) => made.push(edge),
+				None => crossed.push_back((u, w)),
+			}
+		}
+
+		let mut swapped = true;
+		while swapped {
+			swapped = false;
+			for edge in made.iter_mut() {
+				if *edge == (a, e) || *edge == (e, a) {
+					continue;
+				}
+				if let Some(flipped) = self.flip(edge.0, edge.1, true) {
+					*edge = flipped;
+					swapped = true;
+				}
+			}
+		}
+		let (t, k) = self.find_edge(a, e).unwrap();
+		self.set_fixed(t, k, true);
+	}
+
+	// Swaps the edge from u to w for the other diagonal of its two triangles,
+	// if they make a strictly convex quadrilateral and, when delaunay is set,
+	// the far vertex is strictly inside the circumcircle of the near triangle.
+	// Returns the new edge
+	fn flip(&mut self, u: usize, w: usize, delaunay: bool) -> Option<(usize, usize)> {
+		let (t, k) = self.find_edge(u, w)?;
+		let n = self.neighbours[t][k];
+		let (x, y) = (self.triangles[t][k], self.triangles[n][self.slot(n, w, u)]);
+		let p = |v: usize| &self.points[v];
+		if p(x).orientation(p(u), p(y)) != 2 || p(x).orientation(p(y), p(w)) != 2 {
+			return None;
+		}
+		if delaunay && T::incircle_sign(p(x), p(u), p(w), p(y)) != Ordering::Greater {
+			return None;
+		}
+		self.fill(&[t, n], vec![[x, u, y], [x, y, w]], &[]);
+		Some((x, y))
+	}
+
+	// The triangle with the edge from u to w, and the slot opposite it, found
+	// by turning round u. None if there is no such edge
+	pub(crate) fn find_edge(&self, u: usize, w: usize) -> Option<(usize, usize)> {
+		let first = self.locate(&self.points[u]);
+		let mut t = first;
+		loop {
+			let i = self.triangles[t].iter().position(|&v| v == u)?;
+			if self.triangles[t][(i + 1) % 3] == w {
+				return Some((t, (i + 2) % 3));
+			}
+			t = self.neighbours[t][(i + 1) % 3];
+			if t == first {
+				return None;
+			}
+		}
+	}
+}
+
+// The angle opposite the shortest edge
+fn smallest_angle<T: Float>(a: &Point<T>, b: &Point<T>, c: &Point<T>) -> T {
+	let length = |p: &Point<T>, q: &Point<T>| (q.x - p.x)*(q.x - p.x) + (q.y - p.y)*(q.y - p.y);
+	let (ab, bc, ca) = (length(a, b), length(b, c), length(c, a));
+	let (apex, p, q) = if ab <= bc && ab <= ca {(c, a, b)} else if bc <= ca {(a, b, c)} else {(b, c, a)};
+	let (ux, uy, vx, vy) = (p.x - apex.x, p.y - apex.y, q.x - apex.x, q.y - apex.y);
+	(ux*vy - uy*vx).abs().atan2(ux*vx + uy*vy)
+}
+
+impl<T: Scalar> Mesh<T> {
+	// Whether each triangle is inside the constrained edges, found by crossing
+	// them from the ghosts
+	pub(crate) fn inside(&self) -> Vec<bool> {
+		let mut inside = vec![None; self.triangles.len()];
+		let mut queue: VecDeque<usize> = (0..self.triangles.len()).filter(|&t| self.alive[t] && self.triangles[t].contains(&GHOST)).collect();
+		for &t in &queue {
+			inside[t] = Some(false);
+		}
+		while let Some(t) = queue.pop_front() {
+			for k in 0..3 {
+				let n = self.neighbours[t][k];
+				if inside[n].is_none() {
+					inside[n] = Some(inside[t] != Some(self.fixed[t][k]));
+					queue.push_back(n);
+				}
+			}
+		}
+		inside.into_iter().map(|flag| flag == Some(true)).collect()
+	}
+}
+
+// State for refine. Subsegments are the constrained edges, each with the
+// input segment it is part of, which Steiner points on segments also keep
+struct Refinement<'a, T> {
+	mesh: &'a mut Mesh<T>,
+	//points before this are polygon vertices
+	inputs: usize,
+	segments: Vec<(usize, usize)>,
+	subsegments: HashMap<(usize, usize), usize>,
+	segment_of: Vec<Option<usize>>,
+}
+
+impl<'a, T: Float> Refinement<'a, T> {
+	fn new(mesh: &'a mut Mesh<T>, inputs: usize) -> Self {
+		let mut first = HashMap::new();
+		for (i, p) in mesh.points[..inputs].iter().enumerate() {
+			first.entry((p.x.key(), p.y.key())).or_insert(i);
+		}
+		let key = |p: &Point<T>| first[&(p.x.key(), p.y.key())];
+		let segments: Vec<(usize, usize)> = mesh.points[inputs..].chunks(2).map(|pair| (key(&pair[0]), key(&pair[1]))).collect();
+
+		//an edge on a segment that runs through another vertex is only part of it
+		let mut subsegments = HashMap::new();
+		for t in 0..mesh.triangles.len() {
+			for k in 0..3 {
+				let (u, w) = mesh.edge(t, k);
+				if mesh.alive[t] && mesh.fixed[t][k] && u < w {
+					let on = |v: usize, (a, b): (usize, usize)| {
+						let (pa, pb, pv) = (&mesh.points[a], &mesh.points[b], &mesh.points[v]);
+						v == a || v == b || (pa.orientation(pb, pv) == 0 && (pa.x - pv.x)*(pb.x - pv.x) + (pa.y - pv.y)*(pb.y - pv.y) < T::zero())
+					};
+					if let Some(s) = segments.iter().position(|&segment| on(u, segment) && on(w, segment)) {
+						subsegments.insert((u, w), s);
+					}
+				}
+			}
+		}
+		let segment_of = vec![None; mesh.points.len()];
+		Refinement { mesh, inputs, segments, subsegments, segment_of }
+	}
+
+	// Splits encroached subsegments, then each bad triangle found in a pass,
+	// until a pass changes nothing or more than limit points have been added
+	fn run(&mut self, min_angle: T, max_area: Option<T>, limit: usize) -> Result<(), GeometryError> {
+		let start = self.mesh.points.len();
+		let all: Vec<(usize, usize)> = self.subsegments.keys().copied().collect();
+		self.split_encroached(all);
+		loop {
+			let inside = self.mesh.inside();
+			let bad: Vec<[usize; 3]> = (0..self.mesh.triangles.len()).filter(|&t| self.mesh.alive[t] && inside[t]).map(|t| self.mesh.triangles[t]).filter(|&triangle| self.is_bad(triangle, min_angle, max_area)).collect();
+			let mut changed = false;
+			for [a, b, c] in bad {
+				if self.mesh.points.len() - start > limit {
+					return Err(GeometryError::RefinementLimit { points: limit });
+				}
+				let t = match self.mesh.find_edge(a, b) {
+					Some((t, k)) if self.mesh.triangles[t][k] == c => t,
+					_ => continue,
+				};
+				let p = &self.mesh.points;
+				let Some(center) = circumcenter(&p[a], &p[b], &p[c]) else {
+					continue;
+				};
+				let encroached = self.encroached_by(t, &center);
+				if !encroached.is_empty() {
+					let mut queue = Vec::new();
+					for (u, w) in encroached {
+						changed |= self.split(u, w, &mut queue);
+					}
+					self.split_encroached(queue);
+					continue;
+				}
+				let x = self.add_point(center, None);
+				changed |= self.mesh.insert(x) == x;
+			}
+			if !changed {
+				return Ok(());
+			}
+		}
+	}
+
+	// The subsegments a new point at the circumcenter of t would encroach on.
+	// The triangles whose circumcircles hold it, spreading out from t, are
+	// those it would replace, and only the constrained edges round them can be
+	// seen from it. One it lies behind is taken as encroached too, so the
+	// point never lands outside the mesh
+	fn encroached_by(&self, t: usize, center: &Point<T>) -> Vec<(usize, usize)> {
+		let (mesh, p) = (&*self.mesh, &self.mesh.points);
+		let mut cavity = vec![t];
+		let mut seen = HashSet::from([t]);
+		let mut encroached = Vec::new();
+		let mut i = 0;
+		while i < cavity.len() {
+			let s = cavity[i];
+			for k in 0..3 {
+				let (u, w) = mesh.edge(s, k);
+				let n = mesh.neighbours[s][k];
+				if !mesh.fixed[s][k] {
+					if seen.insert(n) && mesh.conflicts(n, center) {
+						cavity.push(n);
+					}
+				} else if u != GHOST && w != GHOST && (encroaches(&p[u], &p[w], center) || p[u].orientation(&p[w], center) == 1) {
+					encroached.push((u, w));
+				}
+			}
+			i += 1;
+		}
+		encroached
+	}
+
+	// An angle under the bound, unless its shortest edge joins points on two
+	// segments meeting at an input vertex at under 60 degrees, or too big
+	fn is_bad(&self, [a, b, c]: [usize; 3], min_angle: T, max_area: Option<T>) -> bool {
+		let p = &self.mesh.points;
+		let area = ((p[b].x - p[a].x)*(p[c].y - p[a].y) - (p[b].y - p[a].y)*(p[c].x - p[a].x)).abs()/T::from_i32(2);
+		if max_area.is_some_and(|max| area > max) {
+			return true;
+		}
+		if smallest_angle(&p[a], &p[b], &p[c]) >= min_angle {
+			return false;
+		}
+		let length = |(u, w): (usize, usize)| (p[w].x - p[u].x)*(p[w].x - p[u].x) + (p[w].y - p[u].y)*(p[w].y - p[u].y);
+		let (u, w) = [(a, b), (b, c), (c, a)].iter().copied().min_by(|&e, &f| length(e).partial_cmp(&length(f)).unwrap_or(Ordering::Equal)).unwrap();
+		let (Some(s), Some(r)) = (self.segment_of[u], self.segment_of[w]) else {
+			return true;
+		};
+		let ((s1, s2), (r1, r2)) = (self.segments[s], self.segments[r]);
+		let Some(z) = [s1, s2].iter().copied().find(|&z| s != r && (z == r1 || z == r2)) else {
+			return true;
+		};
+		let (e, f) = (if z == s1 {s2} else {s1}, if z == r1 {r2} else {r1});
+		let (ux, uy, vx, vy) = (p[e].x - p[z].x, p[e].y - p[z].y, p[f].x - p[z].x, p[f].y - p[z].y);
+		(ux*vy - uy*vx).abs().atan2(ux*vx + uy*vy) >= T::pi()/T::from_i32(3)
+	}
+
+	// Splits every subsegment in the queue with a vertex across from it in its
+	// diametral circle, and those the new points encroach on, until none are
+	// left
+	fn split_encroached(&mut self, mut queue: Vec<(usize, usize)>) {
+		while let Some((u, w)) = queue.pop() {
+			let Some((t, k)) = self.mesh.find_edge(u, w) else {
+				continue;
+			};
+			let n = self.mesh.neighbours[t][k];
+			let apexes = [self.mesh.triangles[t][k], self.mesh.triangles[n][self.mesh.slot(n, w, u)]];
+			let p = &self.mesh.points;
+			if apexes.iter().any(|&v| v != GHOST && encroaches(&p[u], &p[w], &p[v])) {
+				self.split(u, w, &mut queue);
+			}
+		}
+	}
+
+	// Splits the subsegment from u to w on a circle round an input vertex at
+	// one end, else halfway, and queues the pieces and the subsegments across
+	// from the new point. Returns whether it was split
+	fn split(&mut self, u: usize, w: usize, queue: &mut Vec<(usize, usize)>) -> bool {
+		let (Some(&s), Some((t, k))) = (self.subsegments.get(&(u.min(w), u.max(w))), self.mesh.find_edge(u, w)) else {
+			return false;
+		};
+		let (pu, pw) = (self.mesh.points[u], self.mesh.points[w]);
+		let along = match (u < self.inputs, w < self.inputs) {
+			(true, false) => shell(&pu, &pw),
+			(false, true) => T::one() - shell(&pw, &pu),
+			_ => T::one()/T::from_i32(2),
+		};
+		let point = Point::new(pu.x + (pw.x - pu.x)*along, pu.y + (pw.y - pu.y)*along);
+		if point.equals(&pu) || point.equals(&pw) {
+			return false;
+		}
+		self.mesh.set_fixed(t, k, false);
+		self.subsegments.remove(&(u.min(w), u.max(w)));
+		let x = self.add_point(point, Some(s));
+		let x = self.mesh.insert(x);
+		self.mesh.insert_segment(u, x);
+		self.mesh.insert_segment(x, w);
+		self.subsegments.insert((u.min(x), u.max(x)), s);
+		self.subsegments.insert((x.min(w), x.max(w)), s);
+
+		queue.extend([(u, x), (x, w)]);
+		let mesh = &self.mesh;
+		let start = mesh.locate(&mesh.points[x]);
+		let mut t = start;
+		while let Some(i) = mesh.triangles[t].iter().position(|&v| v == x) {
+			let (a, b) = mesh.edge(t, i);
+			if mesh.fixed[t][i] && a != GHOST && b != GHOST {
+				queue.push((a, b));
+			}
+			t = mesh.neighbours[t][(i + 1) % 3];
+			if t == start {
+				break;
+			}
+		}
+		true
+	}
+
+	fn add_point(&mut self, point: Point<T>, segment: Option<usize>) -> usize {
+		self.mesh.points.push(point);
+		self.mesh.inserted.push(false);
+		self.segment_of.push(segment);
+		self.mesh.points.len() - 1
+	}
+}
+
+//p is strictly inside the circle with diameter from a to b
+fn encroaches<T: Scalar>(a: &Point<T>, b: &Point<T>, p: &Point<T>) -> bool {
+	(a.x.clone() - p.x.clone())*(b.x.clone() - p.x.clone()) + (a.y.clone() - p.y.clone())*(b.y.clone() - p.y.clone()) < T::zero()
+}
+
+// How far along from input vertex a to b to split: the power of two nearest
+// half the length, so splits from both ends of a short edge meet
+fn shell<T: Float>(a: &Point<T>, b: &Point<T>) -> T {
+	let length = ((b.x - a.x)*(b.x - a.x) + (b.y - a.y)*(b.y - a.y)).sqrt();
+	let (two, three) = (T::from_i32(2), T::from_i32(3));
+	let half = length/two;
+	let mut d = T::one();
+	while d*three > half*two*two {
+		d = d/two;
+	}
+	while d*three < half*two {
+		d = d*two;
+	}
+	d/length
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+
+// Errors returned by constructors and operations that have no sensible
+// answer for the given input
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GeometryError {
+	// A line or segment was given the same point twice
+	DegenerateLine,
+	// Not enough points for the shape or algorithm
+	TooFewPoints {
+		needed: usize,
+		found: usize,
+	},
+	// Circle radius was negative or NaN
+	InvalidRadius,
+	// Edge from vertex index to the next one has no length
+	ZeroLengthEdge {
+		index: usize,
+	},
+	// The same point appears at two vertex indices that are not neighbours
+	DuplicateVertex {
+		first: usize,
+		second: usize,
+	},
+	// Two edges cross or touch, each edge named by the index of its first vertex
+	SelfIntersection {
+		first: usize,
+		second: usize,
+	},
+	// Every vertex lies on one line, so the ring encloses no area
+	DegenerateRing,
+	// A hole, by its index in holes, is not inside the exterior ring or overlaps another hole
+	MisplacedHole {
+		hole: usize,
+	},
+	// A quality bound that refinement cannot promise to meet
+	InvalidBound,
+	// Refinement added this many points without meeting its bounds
+	RefinementLimit {
+		points: usize,
+	},
+}
+
+impl fmt::Display for GeometryError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			GeometryError::DegenerateLine => write!(f, "a line needs two distinct points"),
+			GeometryError::TooFewPoints { needed, found } => write!(f, "needed at least {} points but found {}", needed, found),
+			GeometryError::InvalidRadius => write!(f, "radius must be a non-negative number"),
+			GeometryError::ZeroLengthEdge { index } => write!(f, "edge {} has zero length", index),
+			GeometryError::DuplicateVertex { first, second } => write!(f, "vertices {} and {} are the same point", first, second),
+			GeometryError::SelfIntersection { first, second } => write!(f, "edges {} and {} intersect", first, second),
+			GeometryError::DegenerateRing => write!(f, "all vertices are collinear"),
+			GeometryError::MisplacedHole { hole } => write!(f, "hole {} is not inside the exterior ring or overlaps another hole", hole),
+			GeometryError::InvalidBound => write!(f, "minimum angle must be at most about 20.7 degrees and maximum area positive"),
+			GeometryError::RefinementLimit { points } => write!(f, "refinement added {} points without meeting its bounds", points),
+		}
+	}
+}
+
+impl Error for GeometryError {}
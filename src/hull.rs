@@ -0,0 +1,382 @@
+use crate::point::{Point, leftmost_index};
+use crate::scalar::Scalar;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::vec::Vec;
+
+// Every algorithm in this module returns the same hull for the same input:
+//	- vertices are in counterclockwise order
+//	- the first vertex is the one picked by leftmost_index (leftmost, highest y on ties)
+//	- points that lie on the boundary between two hull vertices are NOT included,
+//	  so only the extreme points of the hull are returned
+//	- duplicate points are collapsed into a single vertex
+// Fewer than 3 distinct points (or an input where every point is collinear)
+// gives back the distinct extreme points, which is at most 2 points.
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum HullAlgorithm {
+	// Gift wrapping, O(n*h). Still the quickest choice when the hull is very small
+	Jarvis,
+	// Andrew's monotone chain, O(n log n)
+	#[default]
+	MonotoneChain,
+	// Graham scan around the lowest point, O(n log n)
+	GrahamScan,
+	// QuickHull, O(n log n) expected, O(n^2) worst case
+	QuickHull,
+	// Chan's algorithm, O(n log h). Best when the hull is tiny compared to n
+	Chan,
+}
+
+
+pub fn convex_hull_with<T: Scalar>(points: &[Point<T>], algorithm: HullAlgorithm) -> Vec<Point<T>> {
+	let hull = match algorithm {
+		HullAlgorithm::Jarvis => jarvis_march(points),
+		HullAlgorithm::MonotoneChain => monotone_chain(points),
+		HullAlgorithm::GrahamScan => graham_scan(points),
+		HullAlgorithm::QuickHull => quick_hull(points),
+		HullAlgorithm::Chan => chan(points),
+	};
+	normalize_start(hull)
+}
+
+//counterclockwise turn, using the same convention as Point::orientation
+fn is_ccw<T: Scalar>(o: &Point<T>, a: &Point<T>, b: &Point<T>) -> bool {
+	o.orientation(a, b) == 2
+}
+
+pub(crate) fn lexicographic<T: Scalar>(a: &Point<T>, b: &Point<T>) -> Ordering {
+	a.x.partial_cmp(&b.x).unwrap().then(a.y.partial_cmp(&b.y).unwrap())
+}
+
+// Rotate a counterclockwise hull so it starts at the leftmost_index vertex
+pub(crate) fn normalize_start<T: Scalar>(mut hull: Vec<Point<T>>) -> Vec<Point<T>> {
+	if hull.len() > 1 {
+		let l = leftmost_index(&hull);
+		hull.rotate_left(l);
+	}
+	hull
+}
+
+// Gift wrapping. At each step take the most clockwise candidate, preferring
+// the farthest one when several are collinear so boundary points are skipped
+pub(crate) fn jarvis_march<T: Scalar>(points: &[Point<T>]) -> Vec<Point<T>> {
+	let n = points.len();
+	if n == 0 {
+		return Vec::new();
+	}
+
+	let l = leftmost_index(points);
+	let mut hull = Vec::new();
+	let mut p = l;
+
+	loop {
+		hull.push(points[p].clone());
+
+		//any point that is not a duplicate of p is a valid starting candidate
+		let mut q = p;
+		for (i, pt) in points.iter().enumerate() {
+			if pt.equals(&points[p]) {
+				continue;
+			}
+			if q == p {
+				q = i;
+				continue;
+			}
+			let o = points[p].orientation(pt, &points[q]);
+			if o == 2 || (o == 0 && points[p].distance_squared(pt) > points[p].distance_squared(&points[q])) {
+				q = i;
+			}
+		}
+
+		//every point was a duplicate of the start
+		if q == p {
+			break;
+		}
+
+		p = q;
+		if points[p].equals(&points[l]) || hull.len() > n {
+			break;
+		}
+	}
+	hull
+}
+
+// Andrew's monotone chain: sort by x then y, then build the lower and upper
+// chains, discarding any point that does not make a strict left turn
+pub(crate) fn monotone_chain<T: Scalar>(points: &[Point<T>]) -> Vec<Point<T>> {
+	let mut sorted = points.to_vec();
+	sorted.sort_unstable_by(lexicographic);
+	sorted.dedup_by(|a, b| a.equals(b));
+
+	let n = sorted.len();
+	if n < 3 {
+		return sorted;
+	}
+
+	let mut hull: Vec<Point<T>> = Vec::with_capacity(2*n);
+
+	//lower chain
+	for p in &sorted {
+		while hull.len() >= 2 && !is_ccw(&hull[hull.len()-2], &hull[hull.len()-1], p) {
+			hull.pop();
+		}
+		hull.push(p.clone());
+	}
+
+	//upper chain, the last point of the lower chain is its first point
+	let lower_len = hull.len() + 1;
+	for p in sorted.iter().rev().skip(1) {
+		while hull.len() >= lower_len && !is_ccw(&hull[hull.len()-2], &hull[hull.len()-1], p) {
+			hull.pop();
+		}
+		hull.push(p.clone());
+	}
+
+	//the first point was pushed again to close the upper chain
+	hull.pop();
+	hull
+}
+
+// Graham scan: sort around the lowest point by angle, nearest first on ties,
+// then keep only strict left turns
+pub(crate) fn graham_scan<T: Scalar>(points: &[Point<T>]) -> Vec<Point<T>> {
+	let mut pts = points.to_vec();
+	pts.sort_unstable_by(lexicographic);
+	pts.dedup_by(|a, b| a.equals(b));
+
+	if pts.len() < 3 {
+		return pts;
+	}
+
+	//pivot is the lowest point, leftmost on ties
+	let mut pivot = 0;
+	for i in 1..pts.len() {
+		if pts[i].y < pts[pivot].y || (pts[i].y == pts[pivot].y && pts[i].x < pts[pivot].x) {
+			pivot = i;
+		}
+	}
+	pts.swap(0, pivot);
+	let p0 = pts[0].clone();
+
+	pts[1..].sort_unstable_by(|a, b| {
+		match p0.orientation(a, b) {
+			2 => Ordering::Less,
+			1 => Ordering::Greater,
+			_ => p0.distance_squared(a).partial_cmp(&p0.distance_squared(b)).unwrap(),
+		}
+	});
+
+	let mut hull: Vec<Point<T>> = Vec::with_capacity(pts.len());
+	for p in &pts {
+		while hull.len() >= 2 && !is_ccw(&hull[hull.len()-2], &hull[hull.len()-1], p) {
+			hull.pop();
+		}
+		hull.push(p.clone());
+	}
+
+	//points on the closing edge back to the pivot are boundary points too
+	while hull.len() >= 3 && !is_ccw(&hull[hull.len()-2], &hull[hull.len()-1], &p0) {
+		hull.pop();
+	}
+	hull
+}
+
+// QuickHull. The recursion is kept on an explicit stack so that inputs with
+// very large hulls do not overflow the call stack
+pub(crate) fn quick_hull<T: Scalar>(points: &[Point<T>]) -> Vec<Point<T>> {
+	let mut pts = points.to_vec();
+	pts.sort_unstable_by(lexicographic);
+	pts.dedup_by(|a, b| a.equals(b));
+
+	let n = pts.len();
+	if n < 3 {
+		return pts;
+	}
+
+	let a = pts[0].clone();
+	let b = pts[n-1].clone();
+
+	//points strictly right of a -> b lie below the chord, left of it above
+	let mut below = Vec::new();
+	let mut above = Vec::new();
+	for p in &pts[1..n-1] {
+		match a.orientation(&b, p) {
+			1 => below.push(p.clone()),
+			2 => above.push(p.clone()),
+			_ => {}
+		}
+	}
+
+	//each task is an edge (from, to) and the points strictly right of it.
+	//Popping in order emits the hull vertices counterclockwise
+	let mut hull = Vec::new();
+	let mut stack = vec![(b.clone(), a.clone(), above), (a, b, below)];
+
+	while let Some((from, to, set)) = stack.pop() {
+		if set.is_empty() {
+			hull.push(from);
+			continue;
+		}
+
+		//farthest point from the edge is always a hull vertex. p is farther
+		//than the current best q exactly when (to - from) x (p - q) < 0
+		let mut far = 0;
+		for i in 1..set.len() {
+			if T::cross_sign(&from, &to, &set[far], &set[i]) == Ordering::Less {
+				far = i;
+			}
+		}
+		let c = set[far].clone();
+
+		let mut first = Vec::new();
+		let mut second = Vec::new();
+		for p in &set {
+			if from.orientation(&c, p) == 1 {
+				first.push(p.clone());
+			}
+			else if c.orientation(&to, p) == 1 {
+				second.push(p.clone());
+			}
+		}
+
+		stack.push((c.clone(), to, second));
+		stack.push((from, c, first));
+	}
+	hull
+}
+
+// r is a better wrapping candidate than q when seen from p: either more
+// clockwise, or collinear with q and farther away
+fn wraps_before<T: Scalar>(p: &Point<T>, r: &Point<T>, q: &Point<T>) -> bool {
+	let o = p.orientation(r, q);
+	o == 2 || (o == 0 && p.distance_squared(r) > p.distance_squared(q))
+}
+
+// Index of the vertex of a counterclockwise convex hull that every other
+// vertex lies left of (or on) when seen from p. p must not be inside the hull.
+// Binary search first, then a short walk to settle any degenerate cases
+fn tangent_index<T: Scalar>(p: &Point<T>, hull: &[Point<T>]) -> usize {
+	let n = hull.len();
+
+	//1 for a left turn, -1 for a right turn
+	let turn = |a: &Point<T>, b: &Point<T>| -> i32 {
+		match p.orientation(a, b) {
+			2 => 1,
+			1 => -1,
+			_ => 0,
+		}
+	};
+
+	let mut l = 0;
+	let mut r = n;
+	let mut l_prev = turn(&hull[0], &hull[n-1]);
+	let mut l_next = turn(&hull[0], &hull[1%n]);
+	while l < r {
+		let c = (l + r)/2;
+		let c_prev = turn(&hull[c], &hull[(c+n-1)%n]);
+		let c_next = turn(&hull[c], &hull[(c+1)%n]);
+		let c_side = turn(&hull[l], &hull[c]);
+
+		if c_prev != -1 && c_next != -1 {
+			l = c;
+			break;
+		}
+		else if (c_side == 1 && (l_next == -1 || l_prev == l_next)) || (c_side == -1 && c_prev == -1) {
+			r = c;
+		}
+		else {
+			l = c + 1;
+			l_prev = -c_next;
+			l_next = turn(&hull[l%n], &hull[(l+1)%n]);
+		}
+	}
+
+	//the hull is convex so any local best is the tangent
+	let mut c = l%n;
+	for _ in 0..n {
+		let next = (c+1)%n;
+		let prev = (c+n-1)%n;
+		if wraps_before(p, &hull[next], &hull[c]) {
+			c = next;
+		}
+		else if wraps_before(p, &hull[prev], &hull[c]) {
+			c = prev;
+		}
+		else {
+			break;
+		}
+	}
+	c
+}
+
+// Chan's algorithm. Guess a hull size m, split the points into groups of m,
+// hull every group with the monotone chain, then gift wrap over the group
+// hulls using tangent queries. The guess is squared until m steps are enough
+pub(crate) fn chan<T: Scalar>(points: &[Point<T>]) -> Vec<Point<T>> {
+	//a hash set keeps this O(n) where sorting would not
+	let mut seen = HashSet::new();
+	let pts: Vec<Point<T>> = points.iter()
+		.filter(|p| seen.insert((p.x.key(), p.y.key())))
+		.cloned()
+		.collect();
+
+	let n = pts.len();
+	if n < 3 {
+		return monotone_chain(&pts);
+	}
+
+	let l = leftmost_index(&pts);
+	let mut t = 1;
+
+	loop {
+		let m = if t >= 5 { n } else { usize::min(1 << (1 << t), n) };
+		let groups: Vec<Vec<Point<T>>> = pts.chunks(m).map(monotone_chain).collect();
+
+		//the overall leftmost point is a vertex of its own group's hull
+		let g0 = l/m;
+		let i0 = groups[g0].iter().position(|p| p.equals(&pts[l])).unwrap();
+
+		let mut hull = vec![pts[l].clone()];
+		let mut cur = (g0, i0);
+		let mut closed = false;
+
+		for _ in 0..m {
+			let p = &groups[cur.0][cur.1];
+			let mut best: Option<(usize, usize)> = None;
+
+			for (g, group) in groups.iter().enumerate() {
+				let i = if g == cur.0 {
+					if group.len() < 2 {
+						continue;
+					}
+					(cur.1 + 1)%group.len()
+				}
+				else {
+					tangent_index(p, group)
+				};
+
+				best = match best {
+					Some((bg, bi)) if !wraps_before(p, &group[i], &groups[bg][bi]) => best,
+					_ => Some((g, i)),
+				};
+			}
+
+			let next = match best {
+				Some(b) => b,
+				None => break,
+			};
+			if groups[next.0][next.1].equals(&pts[l]) {
+				closed = true;
+				break;
+			}
+			hull.push(groups[next.0][next.1].clone());
+			cur = next;
+		}
+
+		if closed {
+			return hull;
+		}
+		t += 1;
+	}
+}
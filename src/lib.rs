@@ -1,16 +1,103 @@
 pub mod line;
+pub mod segment;
+pub mod ray;
 pub mod point;
 pub mod circle;
 pub mod polygon;
+pub mod multipolygon;
+pub mod boolean;
+pub mod clip;
+pub mod triangulate;
+pub mod delaunay;
+pub mod voronoi;
+pub mod power;
+pub mod hull;
+pub mod predicates;
+pub mod tolerance;
+pub mod scalar;
+pub mod rational;
+pub mod error;
+pub mod sweep;
+mod random;
 
 
 #[cfg(test)]
 mod tests {
 	use super::point::*;
 	use super::line::*;
+	use super::segment::*;
+	use super::ray::*;
 	use super::circle::*;
 	use super::polygon::*;
-	const EPSILON: f64 = 0.00001;
+	use super::multipolygon::*;
+	use super::boolean::*;
+	use super::triangulate::*;
+	use super::delaunay::*;
+	use super::voronoi::*;
+	use super::power::*;
+	use super::hull::*;
+	use super::predicates::*;
+	use super::tolerance::*;
+	use super::rational::*;
+	use super::error::*;
+	use super::sweep::*;
+	use super::scalar::{Scalar, Field};
+	use std::cmp::Ordering;
+	const TOL: Tolerance = Tolerance::DEFAULT;
+	const OCTANTS: [(i32, i32); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+	//repeatable pseudo random numbers below m for the random tests
+	fn lcg(mut seed: u64) -> impl FnMut(u64) -> u64 {
+		move |m| {
+			seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+			(seed >> 33) % m
+		}
+	}
+
+	//n points at whole steps below size, divided by unit and moved by low
+	fn random_points(next: &mut impl FnMut(u64) -> u64, n: usize, size: u64, unit: f64, low: f64) -> Vec<Point> {
+		(0..n).map(|_| Point::new(low + next(size) as f64/unit, low + next(size) as f64/unit)).collect()
+	}
+
+	//n segments between the ends given, skipping any of zero length
+	fn random_segments<T: Scalar>(n: usize, mut ends: impl FnMut() -> (Point<T>, Point<T>)) -> Vec<Segment<T>> {
+		let mut segments = Vec::new();
+		while segments.len() < n {
+			let (p, q) = ends();
+			if let Ok(s) = Segment::new(p, q) {
+				segments.push(s);
+			}
+		}
+		segments
+	}
+
+	//star shaped around centre, so simple but often concave: a vertex along
+	//each direction, min to min + 2 steps of scale out
+	fn star<T: Scalar>(next: &mut impl FnMut(u64) -> u64, centre: (i32, i32), directions: &[(i32, i32)], min: i32, scale: i32) -> Vec<Point<T>> {
+		directions.iter().map(|&(dx, dy)| {
+			let r = scale*(min + next(3) as i32);
+			Point::new(T::from_i32(centre.0 + r*dx), T::from_i32(centre.1 + r*dy))
+		}).collect()
+	}
+
+	//points half a unit apart between from and to, off every grid line, to
+	//compare what a result covers with what it should
+	fn probes(from: i64, to: i64) -> Vec<Point<Rational>> {
+		(2*from..2*to).flat_map(|x| (2*from..2*to).map(move |y| Point::new(Rational::new(4*x + 1, 8), Rational::new(4*y + 3, 8)))).collect()
+	}
+
+	//every pair of segments that touch or cross, tested one pair at a time
+	fn brute_pairs<T: Scalar>(segments: &[Segment<T>]) -> std::collections::BTreeSet<(usize, usize)> {
+		let mut pairs = std::collections::BTreeSet::new();
+		for a in 0..segments.len() {
+			for b in a+1..segments.len() {
+				if segments[a].intersects(&segments[b]) {
+					pairs.insert((a, b));
+				}
+			}
+		}
+		pairs
+	}
 
 	//POINT TEST START
 	#[test]
@@ -21,7 +108,7 @@ mod tests {
 		let d = p1.distance(&p2);
 		let d2 = p1.distance(&p3);
 
-		assert!(d - 9.055385 < EPSILON);
+		assert!(TOL.approx_eq(d, 9.055385));
 		assert_eq!(d2, 0.0);
 	}
 
@@ -35,7 +122,7 @@ mod tests {
 
 		assert!(p1.collinear(&p2, & p3));
 		assert!(p1.collinear(&p3, & p2));
-		assert_eq!(p1.collinear(&p2, &p4), false);
+		assert!(!p1.collinear(&p2, &p4));
 	}
 
 	#[test]
@@ -70,16 +157,65 @@ mod tests {
 		assert!(res.len() == 5);
 	}
 
+	#[test]
+	fn point_convex_hull_algorithms_test() {
+		let algorithms = [HullAlgorithm::Jarvis, HullAlgorithm::MonotoneChain, HullAlgorithm::GrahamScan, HullAlgorithm::QuickHull, HullAlgorithm::Chan];
+
+		//square with collinear points on every edge, a duplicate corner and interior points
+		let xs: [f64; 12] = [0.0, 2.0, 4.0, 4.0, 4.0, 2.0, 0.0, 0.0, 1.0, 3.0, 4.0, 2.0];
+		let ys: [f64; 12] = [0.0, 0.0, 0.0, 2.0, 4.0, 4.0, 4.0, 2.0, 1.0, 2.0, 4.0, 2.0];
+		let points: Vec<Point> = xs.iter().zip(ys.iter()).map(|(x, y)| Point::new(*x, *y)).collect();
+
+		let expected = vec![Point::new(0.0, 4.0), Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(4.0, 4.0)];
+		for a in &algorithms {
+			assert_eq!(convex_hull_with(&points, *a), expected);
+		}
+
+		//pseudo random cloud, every algorithm must agree with Jarvis
+		let cloud = random_points(&mut lcg(12345), 500, 1000, 1.0, 0.0);
+		let reference = convex_hull_with(&cloud, HullAlgorithm::Jarvis);
+		for a in &algorithms {
+			assert_eq!(convex_hull_with(&cloud, *a), reference);
+		}
+
+		//degenerate inputs
+		let line = vec![Point::new(0.0, 0.0), Point::new(2.0, 2.0), Point::new(1.0, 1.0), Point::new(2.0, 2.0)];
+		for a in &algorithms {
+			assert_eq!(convex_hull_with(&line, *a), vec![Point::new(0.0, 0.0), Point::new(2.0, 2.0)]);
+			assert!(convex_hull_with::<f64>(&[], *a).is_empty());
+		}
+	}
+
+	#[test]
+	fn point_chan_hull_test() {
+		//many points inside a small hull, the case Chan's algorithm is meant for
+		let mut points = random_points(&mut lcg(987654321), 20000, 10000, 100.0, 0.0);
+		let corners = [Point::new(-5.0, -5.0), Point::new(150.0, -5.0), Point::new(150.0, 150.0), Point::new(-5.0, 150.0)];
+		points.extend_from_slice(&corners);
+		points.extend_from_slice(&corners);
+
+		let res = convex_hull_with(&points, HullAlgorithm::Chan);
+		assert_eq!(res, vec![Point::new(-5.0, 150.0), Point::new(-5.0, -5.0), Point::new(150.0, -5.0), Point::new(150.0, 150.0)]);
+		assert_eq!(res, convex_hull(&points));
+
+		//a large hull forces several rounds of guessing
+		let mut circle = Vec::new();
+		for i in 0..300 {
+			let a = i as f64 * 2.0 * std::f64::consts::PI / 300.0;
+			circle.push(Point::new(1000.0*a.cos(), 1000.0*a.sin()));
+		}
+		circle.push(Point::new(0.0, 0.0));
+		assert_eq!(convex_hull_with(&circle, HullAlgorithm::Chan), convex_hull(&circle));
+		assert_eq!(convex_hull(&circle).len(), 300);
+	}
+
 	#[test]
 	fn point_brute_closest_test() {
 		let p1 = Point::new(1.0,1.0);
 		let p2 = Point::new(4.0,4.0);
 		let p3 = Point::new(4.0,5.0);
 
-		let mut points = Vec::new();
-		points.push(p1);
-		points.push(p2);
-		points.push(p3);
+		let points = vec![p1, p2, p3];
 
 		closest_brute_force(points, 3);
 
@@ -89,14 +225,14 @@ mod tests {
 	fn point_closest_pair_test() {
 		//assert!(false);
 		//swap last x/y with (0.0, 5.5) to test when shortest is on the left and 
-		let mut xs1: [f64; 10] = [0.0, 0.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 4.0, 4.5];
-		let mut ys1: [f64; 10] = [0.0, 6.0, 7.0, 5.0, 2.0, 1.0, 0.0, 4.0, 1.0, 1.0];
+		let xs1: [f64; 10] = [0.0, 0.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 4.0, 4.5];
+		let ys1: [f64; 10] = [0.0, 6.0, 7.0, 5.0, 2.0, 1.0, 0.0, 4.0, 1.0, 1.0];
 
-		let mut xs2: [f64; 10] = [0.0, 0.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 4.0, 0.0];
-		let mut ys2: [f64; 10] = [0.0, 6.0, 7.0, 5.0, 2.0, 1.0, 0.0, 4.0, 1.0, 5.5];
+		let xs2: [f64; 10] = [0.0, 0.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 4.0, 0.0];
+		let ys2: [f64; 10] = [0.0, 6.0, 7.0, 5.0, 2.0, 1.0, 0.0, 4.0, 1.0, 5.5];
 
-		let mut xs3: [f64; 10] = [0.0, 0.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 4.0, 2.5];
-		let mut ys3: [f64; 10] = [0.0, 6.0, 7.0, 5.0, 2.0, 1.0, 0.0, 4.0, 1.0, 0.0];
+		let xs3: [f64; 10] = [0.0, 0.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 4.0, 2.5];
+		let ys3: [f64; 10] = [0.0, 6.0, 7.0, 5.0, 2.0, 1.0, 0.0, 4.0, 1.0, 0.0];
 
 
 		let mut points: Vec<Point> = Vec::new();
@@ -121,23 +257,309 @@ mod tests {
 		}
 
 		//pair on left side
-		let (p1,p2,d) = closest_pair(&points);
+		let (_p1,_p2,d) = closest_pair(&points).unwrap();
 		assert_eq!(d, 0.5);
 
 		//pair on right side
-		let (p1,p2,d) = closest_pair(&points2);
+		let (_p1,_p2,d) = closest_pair(&points2).unwrap();
 		assert_eq!(d, 0.5);
 
 		//pair in middle
-		let (p1,p2,d) = closest_pair(&points3);
+		let (_p1,_p2,d) = closest_pair(&points3).unwrap();
 		assert_eq!(d, 0.5);
 
 	
 
+	}
+	#[test]
+	fn point_closest_pair_random_test() {
+		let points = random_points(&mut lcg(42), 300, 100000, 1000.0, 0.0);
+		let (_, _, d) = closest_pair(&points).unwrap();
+		let (_, _, brute) = closest_brute_force(points.clone(), points.len());
+		assert_eq!(d, brute.sqrt());
 	}
 	//POINT TEST END
 
 
+	//PREDICATES TEST START
+	#[test]
+	fn predicates_orient2d_test() {
+		//near the line y = x the naive determinant gets the sign wrong,
+		//the exact sign is the sign of y - x
+		let q = Point::new(12.0, 12.0);
+		let r = Point::new(24.0, 24.0);
+		let ulp = f64::EPSILON/2.0;
+		for i in 0..32 {
+			for j in 0..32 {
+				let p = Point::new(0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+				let o = orient2d(&q, &r, &p);
+				if j > i {
+					assert!(o > 0.0);
+				}
+				else if j < i {
+					assert!(o < 0.0);
+				}
+				else {
+					assert_eq!(o, 0.0);
+				}
+			}
+		}
+
+		//collinear at a large scale
+		let a = Point::new(1e15, 1e15);
+		let b = Point::new(3e15, 3e15 + 2.0);
+		let c = Point::new(2e15, 2e15 + 1.0);
+		assert!(a.collinear(&b, &c));
+		assert_eq!(a.orientation(&b, &Point::new(2e15, 2e15 + 2.0)), 2);
+
+		//parallel lines at a tiny scale
+		let l1 = Line::from_coordinates(0.0, 0.0, 1e-9, 1e-9).unwrap();
+		let l2 = Line::from_coordinates(0.0, 1e-12, 1e-9, 1.000001e-9).unwrap();
+		assert!(!l1.is_parallel(&l2));
+		assert!(l1.intersects(&l2));
+	}
+
+	#[test]
+	fn predicates_incircle_test() {
+		let a = Point::new(1.0, 0.0);
+		let b = Point::new(0.0, 1.0);
+		let c = Point::new(-1.0, 0.0);
+
+		assert_eq!(incircle(&a, &b, &c, &Point::new(0.0, -1.0)), 0.0);
+		assert!(incircle(&a, &b, &c, &Point::new(0.0, -1.0 + f64::EPSILON)) > 0.0);
+		assert!(incircle(&a, &b, &c, &Point::new(0.0, -1.0 - f64::EPSILON)) < 0.0);
+		assert!(incircle(&a, &b, &c, &Point::new(0.0, 0.0)) > 0.0);
+
+		//clockwise order flips the sign
+		assert!(incircle(&c, &b, &a, &Point::new(0.0, 0.0)) < 0.0);
+
+		//cocircular far from the origin
+		let o = 1e6;
+		let a = Point::new(o + 3.0, o + 4.0);
+		let b = Point::new(o - 4.0, o + 3.0);
+		let c = Point::new(o - 3.0, o - 4.0);
+		assert_eq!(incircle(&a, &b, &c, &Point::new(o + 5.0, o)), 0.0);
+		assert!(incircle(&a, &b, &c, &Point::new(o + 5.0 - 1e-9, o)) > 0.0);
+	}
+	//PREDICATES TEST END
+
+
+	//TOLERANCE TEST START
+	#[test]
+	fn tolerance_scale_test() {
+		let rel = Tolerance::relative(1e-9);
+
+		assert!(TOL.approx_eq(1.0, 1.000001));
+		assert!(!TOL.approx_eq(1.0, 1.0001));
+		assert!(rel.approx_eq(1e9, 1e9 + 0.5));
+		assert!(!rel.approx_eq(1e-3, 1.1e-3));
+
+		//the same slightly bent path in millimetres and in kilometres
+		for &scale in [1e-3, 1.0, 1e3, 1e6].iter() {
+			let p1 = Point::new(0.0, 0.0);
+			let p2 = Point::new(1000.0*scale, 0.0);
+			let p3 = Point::new(2000.0*scale, 1e-7*scale);
+			assert!(p1.collinear_tol(&p2, &p3, &Tolerance::relative(1e-9)));
+			assert!(!p1.collinear_tol(&p2, &p3, &Tolerance::relative(1e-12)));
+			assert!(!p1.collinear(&p2, &p3));
+
+			let l1 = Line::new(p1, p2).unwrap();
+			let l2 = Line::new(Point::new(0.0, scale), Point::new(2000.0*scale, scale + 1e-7*scale)).unwrap();
+			assert!(l1.is_parallel_tol(&l2, &Tolerance::relative(1e-9)));
+			assert!(!l1.is_parallel(&l2));
+
+			assert!(p3.on_line_tol(&l1, &Tolerance::relative(1e-9)));
+			assert!(p3.equals_tol(&Point::new(2000.0*scale, 0.0), &Tolerance::new(1e-6*scale, 0.0)));
+		}
+
+		//the absolute default only suits unit scale coordinates
+		let p1 = Point::new(0.0, 0.0);
+		let p2 = Point::new(1e6, 0.0);
+		let p3 = Point::new(2e6, 1.0);
+		assert!(!p1.collinear_tol(&p2, &p3, &TOL));
+
+		let c1 = Circle::new(Point::new(0.0, 0.0), 1.0).unwrap();
+		let c2 = Circle::new(Point::new(2.0 + 1e-7, 0.0), 1.0).unwrap();
+		assert!(!c1.intersects_circle(&c2));
+		assert!(c1.intersects_circle_tol(&c2, &TOL));
+		assert_eq!(c1.distance_from_circle_tol(&c2, &TOL), 0.0);
+		assert_eq!(c1.distance_from_point_tol(&Point::new(1.0 + 1e-7, 0.0), &TOL), 0.0);
+		assert!(c1.contains_circle_tol(&Circle::new(Point::new(0.5, 0.0), 0.5).unwrap(), &TOL));
+	}
+	//TOLERANCE TEST END
+
+
+	//SCALAR TEST START
+	#[test]
+	fn scalar_integer_test() {
+		//orientation is exact even where the cross product overflows an i128
+		let big = i64::MAX;
+		let a: Point<i64> = Point::new(-big, -big);
+		let b = Point::new(big, big - 1);
+		let c = Point::new(big - 1, big - 2);
+		assert_eq!(a.orientation(&b, &c), 1);
+		assert_eq!(a.orientation(&c, &b), 2);
+		assert!(a.collinear(&Point::new(0, 0), &Point::new(big, big)));
+
+		let grid: Vec<Point<i64>> = (0..10).flat_map(|x| (0..10).map(move |y| Point::new(x, y))).collect();
+		let hull = convex_hull(&grid);
+		assert_eq!(hull, vec![Point::new(0, 9), Point::new(0, 0), Point::new(9, 0), Point::new(9, 9)]);
+		assert_eq!(convex_hull_with(&grid, HullAlgorithm::Chan), hull);
+
+		//twice the area of a triangle with half integer area
+		let tri: Polygon<i32> = Polygon::from_vec(vec![Point::new(0, 0), Point::new(3, 0), Point::new(0, 1)]).unwrap();
+		assert_eq!(tri.double_area(), 3);
+
+		let l1: Segment<i32> = Segment::from_coordinates(0, 0, 4, 4).unwrap();
+		let l2 = Segment::from_coordinates(0, 4, 4, 0).unwrap();
+		assert!(l1.intersects(&l2));
+		assert!(!l1.is_parallel(&l2));
+	}
+
+	#[test]
+	fn scalar_f32_test() {
+		let p1: Point<f32> = Point::new(0.0, 0.0);
+		let p2 = Point::new(3.0, 4.0);
+		assert_eq!(p1.distance(&p2), 5.0);
+
+		let l1: Segment<f32> = Segment::from_coordinates(0.0, 0.0, 4.0, 4.0).unwrap();
+		let l2 = Segment::from_coordinates(0.0, 4.0, 4.0, 0.0).unwrap();
+		assert_eq!(l1.intersection(&l2), Intersection::Point(Point::new(2.0, 2.0)));
+		assert_eq!(l1.distance_to_point(&Point::new(0.0, 2.0)), f32::sqrt(2.0));
+
+		let c: Circle<f32> = Circle::new(p1, 2.0).unwrap();
+		assert!(c.contains_circle(&Circle::new(Point::new(0.5, 0.0), 1.0).unwrap()));
+		assert_eq!(c.distance_from_point(&p2), 3.0);
+
+		let square: Polygon<f32> = Polygon::from_vec(vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(2.0, 2.0), Point::new(0.0, 2.0)]).unwrap();
+		assert_eq!(square.area(), 4.0);
+	}
+	//SCALAR TEST END
+
+
+	//RATIONAL TEST START
+	#[test]
+	fn rational_arithmetic_test() {
+		let third = Rational::new(1, 3);
+		let sixth = Rational::new(-1, -6);
+		assert_eq!(third.clone() + sixth.clone(), Rational::new(1, 2));
+		assert_eq!(third.clone() - sixth.clone(), sixth);
+		assert_eq!(third.clone()*sixth.clone(), Rational::new(1, 18));
+		assert_eq!(third.clone()/sixth.clone(), Rational::from_integer(2));
+		assert_eq!(-third.clone(), Rational::new(2, -6));
+		assert!(Rational::new(-1, 2) < third && sixth < third);
+		assert_eq!(format!("{}", Rational::new(10, -4)), "-5/2");
+
+		//0.1 is not one tenth, and the conversion keeps the difference
+		let tenth = Rational::from_f64(0.1);
+		assert!(tenth != Rational::new(1, 10));
+		assert_eq!(format!("{}", tenth), "3602879701896397/36028797018963968");
+		assert_eq!(tenth.to_f64(), 0.1);
+
+		//big values round trip through multiplication and division
+		let mut next = lcg(7);
+		let mut big = Rational::from_integer(1);
+		for _ in 0..40 {
+			let v = Rational::from_f64(next(1 << 31) as f64 * 1e-7 + 0.5);
+			let before = big.clone();
+			big = big*v.clone() + Rational::new(1, 7);
+			assert_eq!((big.clone() - Rational::new(1, 7))/v, before);
+		}
+		assert_eq!(format!("{}", Rational::from_f64(2f64.powi(100))), "1267650600228229401496703205376");
+
+		for v in [1e300, -3.5e-300, 123456.789, f64::MIN_POSITIVE, 5e-324].iter() {
+			assert_eq!(Rational::from_f64(*v).to_f64(), *v);
+		}
+	}
+
+	#[test]
+	fn rational_exact_mode_test() {
+		//lines through coordinates that are not exact in binary
+		let l1: Line<Rational> = Line::new(Point::new(0.1.into(), 0.2.into()), Point::new(0.7.into(), 0.3.into())).unwrap();
+		let l2: Line<Rational> = Line::new(Point::new(0.3.into(), 0.9.into()), Point::new(0.4.into(), (-0.6).into())).unwrap();
+		let Intersection::Point(p) = l1.intersection(&l2) else {panic!()};
+		assert!(p.on_line(&l1) && p.on_line(&l2));
+		assert!(!p.x.is_integer());
+
+		//the same intersection in f64 is only close
+		let f1 = Line::from_coordinates(0.1, 0.2, 0.7, 0.3).unwrap();
+		let f2 = Line::from_coordinates(0.3, 0.9, 0.4, -0.6).unwrap();
+		let Intersection::Point(q) = f1.intersection(&f2) else {panic!()};
+		assert!(TOL.approx_eq(q.x, p.x.to_f64()) && TOL.approx_eq(q.y, p.y.to_f64()));
+
+		//exact area with no rounding at all
+		let square: Polygon<Rational> = Polygon::from_vec(to_exact(&[Point::new(0.1, 0.1), Point::new(0.3, 0.1), Point::new(0.3, 0.3), Point::new(0.1, 0.3)])).unwrap();
+		let side = Rational::from_f64(0.3) - Rational::from_f64(0.1);
+		assert_eq!(square.area(), side.clone()*side);
+
+		//any generic algorithm runs in exact mode
+		let points = vec![Point::new(0.0, 0.0), Point::new(1.0, 0.1), Point::new(2.0, 0.2), Point::new(1.0, 3.0), Point::new(0.5, 0.5)];
+		let hull = exact(&points, |p| from_exact(&convex_hull(p)));
+		assert_eq!(hull, vec![Point::new(0.0, 0.0), Point::new(2.0, 0.2), Point::new(1.0, 3.0)]);
+	}
+	#[test]
+	fn rational_extreme_test() {
+		//subnormal inputs have denominators of 2^1074, and the incircle
+		//determinant multiplies four of them together
+		let tiny = |x: f64, y: f64| Point::new(Rational::from_f64(x*5e-324), Rational::from_f64(y*5e-324));
+		let (a, b, c) = (tiny(1.0, 0.0), tiny(0.0, 1.0), tiny(-1.0, 0.0));
+		assert_eq!(Rational::incircle_sign(&a, &b, &c, &tiny(0.0, -1.0)), Ordering::Equal);
+		assert_eq!(Rational::incircle_sign(&a, &b, &c, &tiny(0.0, 0.0)), Ordering::Greater);
+		assert_eq!(Rational::incircle_sign(&a, &b, &c, &tiny(0.0, 2.0)), Ordering::Less);
+
+		//and mixed with huge values
+		let a = Point::new(Rational::from_f64(1e300), Rational::from_f64(f64::MIN_POSITIVE));
+		let b = Point::new(Rational::from_f64(5e-324), Rational::from_f64(1e300));
+		let c = Point::new(Rational::from_f64(-1e300), Rational::from_f64(5e-324));
+		assert_eq!(Rational::incircle_sign(&a, &b, &c, &tiny(3.0, -7.0)), Ordering::Greater);
+		assert_eq!(Rational::incircle_sign(&a, &b, &c, &Point::new(Rational::from_f64(0.0), Rational::from_f64(-1e301))), Ordering::Less);
+		assert_eq!(Rational::cross_sign(&a, &b, &a, &tiny(1.0, 1.0)), Ordering::Greater);
+
+		//converting back from far outside the f64 range
+		let (small, big) = (Rational::from_f64(5e-324), Rational::from_f64(1e300));
+		let square = small.clone()*small.clone();
+		assert_eq!(square.to_f64(), 0.0);
+		assert_eq!((square/small.clone()).to_f64(), 5e-324);
+		assert_eq!((big.clone()*big.clone()).to_f64(), f64::INFINITY);
+		assert_eq!((big.clone()*big.clone()*small.clone()/big.clone()).to_f64(), 1e300*5e-324);
+		assert_eq!((small.clone()/big.clone()/big).to_f64(), 0.0);
+
+		//a whole triangulation with subnormal coordinates
+		let points: Vec<Point<f64>> = [(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (0.0, 3.0), (1.0, 1.0)].iter().map(|&(x, y)| Point::new(x*5e-324, y*5e-324)).collect();
+		assert_eq!(exact(&points, |p| delaunay(p).triangles().len()), 4);
+	}
+	//RATIONAL TEST END
+
+
+	//ERROR TEST START
+	#[test]
+	fn error_invalid_input_test() {
+		let p = Point::new(1.0, 2.0);
+		assert_eq!(Segment::new(p, p).err(), Some(GeometryError::DegenerateLine));
+		assert_eq!(Line::new(p, p).err(), Some(GeometryError::DegenerateLine));
+		assert!(Circle::new(p, -1.0).is_err());
+		assert!(Circle::new(p, f64::NAN).is_err());
+		assert!(Circle::new(p, 0.0).is_ok());
+
+		let found = Polygon::from_vec(vec![p, Point::new(3.0, 4.0)]).err();
+		assert_eq!(found, Some(GeometryError::TooFewPoints { needed: 3, found: 2 }));
+		assert_eq!(closest_pair(&[p]).err(), Some(GeometryError::TooFewPoints { needed: 2, found: 1 }));
+	}
+
+	#[test]
+	fn error_no_sentinel_test() {
+		let l1 = Line::from_coordinates(0.0, 0.0, 4.0, 4.0).unwrap();
+		let l2 = Line::from_coordinates(0.0, 1.0, 4.0, 5.0).unwrap();
+		assert_eq!(l1.intersection(&l2), Intersection::None);
+
+		let vertical = Segment::from_coordinates(2.0, 0.0, 2.0, 1.0).unwrap();
+		assert_eq!(vertical.to_line().get_equation(), None);
+		assert_eq!(vertical.length(), 1.0);
+		assert_eq!(l1.get_equation(), Some((1.0, 0.0)));
+	}
+	//ERROR TEST END
+
+
 	//LINE TEST START
 	#[test]
 	fn line_pt_dist_test() {
@@ -150,27 +572,27 @@ mod tests {
 		let p3 = Point::new(9.0, 0.0);
 		let p0 = Point::new(3.0,5.0);
 
-		let l1 = Line::new(p2, p3, false);
+		let l1 = Segment::new(p2, p3).unwrap();
 		assert_eq!(l1.distance_to_point(&p1), p2.distance(&p1));
 		assert_eq!(l1.distance_to_point(&p4), p3.distance(&p4));
-		assert!(l1.distance_to_point(&p5) - 1.1523319192613102 < EPSILON);
-		assert_eq!(l1.distance_to_point(&p0) < EPSILON, true);
+		assert!(TOL.approx_eq(l1.distance_to_point(&p5), 1.1523319192613102));
+		assert!(TOL.is_zero(l1.distance_to_point(&p0), 1.0));
 
 
 		// Now test an infinite line
 		let p6 = Point::new(2.0, 5.0);
 		let p7 = Point::new(10.0, 6.0);
-		let l2 = Line::new(p6, p7, true);
+		let l2 = Line::new(p6, p7).unwrap();
 
 		let p8 = Point::new(1.0, 5.0);
 		let p9 = Point::new(2.0, 6.0);
 		let p10 = Point::new(13.0, 7.0);
 		let p11 = Point::new(6.0, 4.0);
 
-		assert!(l2.distance_to_point(&p8) - 0.124034734 < EPSILON);
-		assert!(l2.distance_to_point(&p9)-  0.99227787 < EPSILON);
-		assert!(l2.distance_to_point(&p10)- 0.6201736729< EPSILON);
-		assert!(l2.distance_to_point(&p11)- 1.48841681507 < EPSILON);
+		assert!(TOL.approx_eq(l2.distance_to_point(&p8), 0.124034734));
+		assert!(TOL.approx_eq(l2.distance_to_point(&p9), 0.99227787));
+		assert!(TOL.approx_eq(l2.distance_to_point(&p10), 0.6201736729));
+		assert!(TOL.approx_eq(l2.distance_to_point(&p11), 1.48841681507));
 
 	}	
 
@@ -179,19 +601,19 @@ mod tests {
 
 		let p1 = Point::new(1.0, 1.0);
 		let p2 = Point::new(5.0, 5.0);
-		let l1 = Line::new(p1,p2,false);
+		let l1 = Segment::new(p1,p2).unwrap();
 
 		let p3 = Point::new(2.0, 1.0);
 		let p4 = Point::new(2.0, -5.0);	//vertical line segment
-		let l2 = Line::new(p3,p4,false);
+		let l2 = Segment::new(p3,p4).unwrap();
 
 		let p5 = Point::new(4.0, 12.0);
 		let p6 = Point::new(8.0, 5.0);
-		let l3 = Line::new(p5,p6,true);
+		let l3 = Line::new(p5,p6).unwrap();
 
 		let p7 = Point::new(5.0, 0.0);
 		let p8 = Point::new(6.0, 0.0);	//infinite horizontal line
-		let l4 = Line::new(p7,p8,true);
+		let l4 = Line::new(p7,p8).unwrap();
 
 		assert_eq!(l1.distance_to_line(&l2), l1.distance_to_point(&p3));
 		assert_eq!(l1.distance_to_line(&l3), l3.distance_to_point(&p2));
@@ -207,27 +629,27 @@ mod tests {
 	fn line_parallel_test() {
 		let p1 = Point::new(1.0,1.0);
 		let p2 = Point::new(5.0,5.0);
-		let l1 = Line::new(p1,p2, true);
+		let l1 = Line::new(p1,p2).unwrap();
 
 		let p3 = Point::new(2.0,2.0);
 		let p4 = Point::new(6.0,6.0);
-		let l2 = Line::new(p3,p4, true);
+		let l2 = Line::new(p3,p4).unwrap();
 
 		let p5 = Point::new(0.0, 5.0);
 		let p6 = Point::new(0.0,10.0);
-		let l3 = Line::new(p5,p6, false);
+		let l3 = Segment::new(p5,p6).unwrap();
 
 		let p7 = Point::new(1.0, 5.0);
 		let p8 = Point::new(1.0, 10.0);
-		let l4 = Line::new(p7,p8, false);
+		let l4 = Segment::new(p7,p8).unwrap();
 
 		let p9 = Point::new(1.0, 0.0);
 		let p10 = Point::new(5.0, 0.0);
-		let l5 = Line::new(p9,p10, true);
+		let l5 = Line::new(p9,p10).unwrap();
 
 		let p11 = Point::new(1.0,1.0);
 		let p12 = Point::new(5.0,1.0);
-		let l6 = Line::new(p11,p12, false);
+		let l6 = Segment::new(p11,p12).unwrap();
 
 		assert!(l1.is_parallel(&l2));
 		assert!(!l1.is_parallel(&l3));
@@ -278,19 +700,19 @@ mod tests {
 	fn line_intersects_test() {
 		let p1 = Point::new(1.0, 1.0);
 		let p2 = Point::new(5.0, 5.0);
-		let l1 = Line::new(p1,p2,true);
+		let l1 = Line::new(p1,p2).unwrap();
 
 		let p3 = Point::new(2.0, 1.0);
 		let p4 = Point::new(2.0, -5.0);	//vertical line segment
-		let l2 = Line::new(p3,p4,false);
+		let l2 = Segment::new(p3,p4).unwrap();
 
 		let p5 = Point::new(4.0, 12.0);
 		let p6 = Point::new(8.0, 5.0);
-		let l3 = Line::new(p5,p6,true);
+		let l3 = Line::new(p5,p6).unwrap();
 
 		let p7 = Point::new(5.0, 0.0);
 		let p8 = Point::new(6.0, 0.0);	//infinite horizontal line
-		let l4 = Line::new(p7,p8,true);
+		let l4 = Line::new(p7,p8).unwrap();
 
 		assert!(!l1.intersects(&l2));
 		assert!(l1.intersects(&l3));
@@ -311,47 +733,341 @@ mod tests {
 	}
 
 	#[test]
-	//Not tested yet
 	fn line_intersection_test() {
-		assert!(false);
+		let l1 = Segment::from_coordinates(0.0, 0.0, 4.0, 4.0).unwrap();
+		let l2 = Segment::from_coordinates(0.0, 4.0, 4.0, 0.0).unwrap();
+		let Intersection::Point(p) = l1.intersection(&l2) else {panic!()};
+		assert!(TOL.approx_eq(p.x, 2.0));
+		assert!(TOL.approx_eq(p.y, 2.0));
+
+		//vertical and horizontal lines
+		let l3 = Line::from_coordinates(1.0, -3.0, 1.0, 8.0).unwrap();
+		let l4 = Line::from_coordinates(-2.0, 5.0, 7.0, 5.0).unwrap();
+		let Intersection::Point(p) = l3.intersection(&l4) else {panic!()};
+		assert!(TOL.approx_eq(p.x, 1.0));
+		assert!(TOL.approx_eq(p.y, 5.0));
+	}
+
+	#[test]
+	fn line_intersection_overlap_test() {
+		//collinear segments share a piece, a point or nothing
+		let l1 = Segment::from_coordinates(0.0, 0.0, 4.0, 4.0).unwrap();
+		let l2 = Segment::from_coordinates(6.0, 6.0, 2.0, 2.0).unwrap();
+		let l3 = Segment::from_coordinates(4.0, 4.0, 5.0, 5.0).unwrap();
+		let l4 = Segment::from_coordinates(5.0, 5.0, 6.0, 6.0).unwrap();
+		assert_eq!(l1.intersection(&l2), Intersection::Segment(Point::new(2.0, 2.0), Point::new(4.0, 4.0)));
+		assert_eq!(l1.intersection(&l3), Intersection::Point(Point::new(4.0, 4.0)));
+		assert_eq!(l1.intersection(&l4), Intersection::None);
+
+		//segments whose lines cross outside of them
+		let l5 = Segment::from_coordinates(0.0, 1.0, 1.0, 0.0).unwrap();
+		let l6 = Segment::from_coordinates(3.0, 0.0, 4.0, 1.0).unwrap();
+		assert_eq!(l5.intersection(&l6), Intersection::None);
+
+		//segment on an infinite line and coincident infinite lines
+		let l7 = Line::from_coordinates(-1.0, -1.0, 9.0, 9.0).unwrap();
+		let l8 = Line::from_coordinates(7.0, 7.0, 8.0, 8.0).unwrap();
+		assert_eq!(l7.intersection(&l4), Intersection::Segment(Point::new(5.0, 5.0), Point::new(6.0, 6.0)));
+		assert_eq!(l7.intersection(&l8), Intersection::Line);
+	}
+
+	#[test]
+	fn line_intersection_vertical_test() {
+		//vertical segment touching the end of a horizontal one
+		let l1 = Segment::from_coordinates(2.0, -1.0, 2.0, 3.0).unwrap();
+		let l2 = Segment::from_coordinates(0.0, 3.0, 2.0, 3.0).unwrap();
+		let l3 = Segment::from_coordinates(2.0, 5.0, 2.0, 1.0).unwrap();
+		let l4 = Line::from_coordinates(0.0, 0.0, 1.0, 0.0).unwrap();
+		assert_eq!(l1.intersection(&l2), Intersection::Point(Point::new(2.0, 3.0)));
+		assert_eq!(l1.intersection(&l3), Intersection::Segment(Point::new(2.0, 1.0), Point::new(2.0, 3.0)));
+		assert_eq!(l1.intersection(&l4), Intersection::Point(Point::new(2.0, 0.0)));
+		assert_eq!(l2.intersection(&l4), Intersection::None);
+	}
+
+	#[test]
+	fn line_ray_intersection_test() {
+		let r1 = Ray::from_coordinates(0.0, 0.0, 1.0, 0.0).unwrap();
+		assert_eq!(r1.intersection(&Segment::from_coordinates(2.0, -1.0, 2.0, 1.0).unwrap()), Intersection::Point(Point::new(2.0, 0.0)));
+		assert_eq!(r1.intersection(&Segment::from_coordinates(-2.0, -1.0, -2.0, 1.0).unwrap()), Intersection::None);
+		assert_eq!(r1.intersection(&Line::from_coordinates(4.0, 0.0, 4.0, 1.0).unwrap()), Intersection::Point(Point::new(4.0, 0.0)));
+		assert_eq!(r1.intersection(&Line::from_coordinates(-1.0, 0.0, -1.0, 1.0).unwrap()), Intersection::None);
+
+		//collinear rays pointing the same way, away from and towards each other
+		assert_eq!(r1.intersection(&Ray::from_coordinates(5.0, 0.0, 6.0, 0.0).unwrap()), Intersection::Ray(Point::new(5.0, 0.0), Point::new(6.0, 0.0)));
+		assert_eq!(r1.intersection(&Ray::from_coordinates(-1.0, 0.0, -2.0, 0.0).unwrap()), Intersection::None);
+		assert_eq!(r1.intersection(&Ray::from_coordinates(0.0, 0.0, -1.0, 0.0).unwrap()), Intersection::Point(Point::new(0.0, 0.0)));
+		assert_eq!(r1.intersection(&Ray::from_coordinates(3.0, 0.0, 2.0, 0.0).unwrap()), Intersection::Segment(Point::new(0.0, 0.0), Point::new(3.0, 0.0)));
+		assert_eq!(r1.intersection(&Line::from_coordinates(7.0, 0.0, 8.0, 0.0).unwrap()), Intersection::Ray(Point::new(0.0, 0.0), Point::new(1.0, 0.0)));
+
+		//vertical rays
+		let r2 = Ray::from_coordinates(1.0, 5.0, 1.0, 4.0).unwrap();
+		assert_eq!(r2.intersection(&r1), Intersection::Point(Point::new(1.0, 0.0)));
+		assert_eq!(r2.intersection(&Ray::from_coordinates(1.0, -1.0, 1.0, 0.0).unwrap()), Intersection::Segment(Point::new(1.0, -1.0), Point::new(1.0, 5.0)));
+		assert!(!r2.intersects(&Ray::from_coordinates(1.0, 6.0, 1.0, 7.0).unwrap()));
+		assert!(r2.intersects(&r1) && r1.intersects(&r2));
+	}
+
+	#[test]
+	fn line_ray_distance_test() {
+		let r1 = Ray::from_coordinates(0.0, 0.0, 1.0, 0.0).unwrap();
+		assert!(TOL.approx_eq(r1.distance_to_point(&Point::new(-3.0, 4.0)), 5.0));
+		assert!(TOL.approx_eq(r1.distance_to_point(&Point::new(2.0, 4.0)), 4.0));
+		assert!(TOL.approx_eq(r1.distance_to_line(&Segment::from_coordinates(-2.0, -1.0, -2.0, 1.0).unwrap()), 2.0));
+		assert!(TOL.approx_eq(r1.distance_to_line(&Ray::from_coordinates(-1.0, 1.0, -2.0, 1.0).unwrap()), f64::sqrt(2.0)));
+
+		//parallel shapes with no endpoints to measure from
+		let l1 = Line::from_coordinates(-5.0, 3.0, 5.0, 3.0).unwrap();
+		assert!(TOL.approx_eq(r1.distance_to_line(&l1), 3.0));
+		assert!(TOL.approx_eq(l1.distance_to_line(&r1.to_line()), 3.0));
+
+		assert_eq!(l1.projection(&Point::new(3.0, 7.0)), Point::new(3.0, 3.0));
+		assert_eq!(r1.projection(&Point::new(-3.0, 7.0)), Point::new(0.0, 0.0));
+	}
+
+	#[test]
+	fn line_conversion_test() {
+		let s1 = Segment::from_coordinates(0.0, 0.0, 2.0, 1.0).unwrap();
+		let far = Point::new(-4.0, -2.0);
+		assert!(!far.on_line(&s1) && !far.on_line(&s1.to_ray()));
+		assert!(far.on_line(&s1.to_line()));
+		assert!(Point::new(6.0, 3.0).on_line(&s1.to_ray()));
+
+		assert!(s1.to_ray().to_segment().equals(&s1));
+		assert!(s1.to_line().to_segment().equals(&s1));
+		assert!(s1.to_line().to_ray().equals(&s1.to_ray()));
+		assert_eq!(Ray::new(far, far).err(), Some(GeometryError::DegenerateLine));
+	}
+
+	#[test]
+	fn line_general_form_test() {
+		//y = 2x + 3 and the vertical line x = 4
+		let l1 = Line::from_general(2.0, -1.0, 3.0).unwrap();
+		let l2 = Line::from_general(1.0, 0.0, -4.0).unwrap();
+		assert_eq!(l1.get_equation(), Some((2.0, 3.0)));
+		assert_eq!(l2.get_equation(), None);
+		assert!(Point::new(1.0, 5.0).on_line(&l1) && Point::new(4.0, 100.0).on_line(&l2));
+		assert_eq!(l2.x_at(7.0), Some(4.0));
+		assert_eq!(l2.y_at(7.0), None);
+		assert_eq!(l1.intersection(&l2), Intersection::Point(Point::new(4.0, 11.0)));
+		assert_eq!(Line::from_general(0.0, 0.0, 1.0).err(), Some(GeometryError::DegenerateLine));
+
+		//slope form round trip, and the exact general form on integers
+		let l3 = Line::from_equation(2.0, 3.0);
+		assert_eq!(l3.general_form(), (-2.0, 1.0, -3.0));
+		assert_eq!(l3.get_equation(), Some((2.0, 3.0)));
+		assert_eq!(Line::<i32>::vertical(3).general_form(), (-1, 0, 3));
+		assert_eq!(Line::horizontal(3.0).get_equation(), Some((0.0, 3.0)));
+		assert_eq!(Line::horizontal(3.0).x_at(1.0), None);
+		assert_eq!(Line::vertical(2.0).intersection(&Line::from_equation(1.0, 0.0)), Intersection::Point(Point::new(2.0, 2.0)));
+	}
+
+	#[test]
+	fn line_direction_constructors_test() {
+		let p = Point::new(1.0, 1.0);
+		let l1 = Line::from_point_normal(p, 0.0, 1.0).unwrap();
+		assert!(Point::new(5.0, 1.0).on_line(&l1));
+		assert_eq!(l1.normal(), (0.0, 1.0));
+
+		let l2 = Line::from_point_direction(p, 2.0, 2.0).unwrap();
+		assert!(Point::new(-3.0, -3.0).on_line(&l2));
+		assert!(TOL.approx_eq(l2.angle(), std::f64::consts::FRAC_PI_4));
+		assert_eq!(Line::from_point_direction(p, 0.0, 0.0).err(), Some(GeometryError::DegenerateLine));
+
+		let l3 = Line::from_angle(Point::new(0.0, 0.0), std::f64::consts::FRAC_PI_2).unwrap();
+		assert!(TOL.approx_eq(l3.angle(), std::f64::consts::FRAC_PI_2));
+		let Intersection::Point(q) = l3.intersection(&Line::horizontal(2.0)) else {panic!()};
+		assert!(q.equals_tol(&Point::new(0.0, 2.0), &TOL));
+	}
+
+	#[test]
+	fn line_intersects_vertical_test() {
+		//a vertical infinite line used to divide by zero
+		let l1 = Line::from_coordinates(2.0, 0.0, 2.0, 1.0).unwrap();
+		let l2 = Segment::from_coordinates(0.0, 5.0, 4.0, 7.0).unwrap();
+		let l3 = Segment::from_coordinates(3.0, 5.0, 4.0, 7.0).unwrap();
+		let l4 = Segment::from_coordinates(2.0, 9.0, 5.0, 9.0).unwrap();
+		assert!(l1.intersects(&l2));
+		assert!(!l1.intersects(&l3));
+		assert!(l1.intersects(&l4));
+
+		//collinear segments that overlap and that do not
+		let l5 = Segment::from_coordinates(0.0, 0.0, 2.0, 2.0).unwrap();
+		let l6 = Segment::from_coordinates(1.0, 1.0, 3.0, 3.0).unwrap();
+		let l7 = Segment::from_coordinates(2.5, 2.5, 3.0, 3.0).unwrap();
+		assert!(l5.intersects(&l6));
+		assert!(!l5.intersects(&l7));
+
+		//coincident infinite lines
+		let l8 = Line::from_coordinates(5.0, 5.0, 6.0, 6.0).unwrap();
+		assert!(Line::from_coordinates(0.0, 0.0, 1.0, 1.0).unwrap().intersects(&l8));
 	}
 	//LINE TEST END
 
 
+	//SWEEP TEST START
+	#[test]
+	fn sweep_degenerate_test() {
+		let segments = vec![
+			//diagonals, a vertical and a horizontal all through (2, 2)
+			Segment::from_coordinates(0.0, 0.0, 4.0, 4.0).unwrap(),
+			Segment::from_coordinates(0.0, 4.0, 4.0, 0.0).unwrap(),
+			Segment::from_coordinates(2.0, 5.0, 2.0, -1.0).unwrap(),
+			Segment::from_coordinates(0.0, 2.0, 4.0, 2.0).unwrap(),
+			//collinear overlap, then a segment touching the end of it
+			Segment::from_coordinates(5.0, 5.0, 8.0, 8.0).unwrap(),
+			Segment::from_coordinates(9.0, 9.0, 6.0, 6.0).unwrap(),
+			Segment::from_coordinates(9.0, 9.0, 10.0, 0.0).unwrap(),
+		];
+		let found = all_intersections(&segments);
+		let expected = vec![
+			(Point::new(2.0, 2.0), vec![0, 1, 2, 3]),
+			(Point::new(6.0, 6.0), vec![4, 5]),
+			(Point::new(8.0, 8.0), vec![4, 5]),
+			(Point::new(9.0, 9.0), vec![5, 6]),
+		];
+		assert_eq!(found.len(), expected.len());
+		for (f, (p, ids)) in found.iter().zip(expected) {
+			assert_eq!(f.point, p);
+			assert_eq!(f.segments, ids);
+		}
+		assert!(all_intersections::<f64>(&[]).is_empty());
+	}
+
+	#[test]
+	fn sweep_any_intersection_test() {
+		//a staircase of disjoint segments, including vertical ones
+		let mut segments = vec![
+			Segment::from_coordinates(0.0, 0.0, 2.0, 0.0).unwrap(),
+			Segment::from_coordinates(2.0, 1.0, 2.0, 3.0).unwrap(),
+			Segment::from_coordinates(3.0, 3.0, 5.0, 3.0).unwrap(),
+			Segment::from_coordinates(5.0, 4.0, 5.0, 6.0).unwrap(),
+			Segment::from_coordinates(0.0, 5.0, 4.0, 9.0).unwrap(),
+		];
+		assert_eq!(any_intersection(&segments), None);
+
+		//touching the middle of a vertical segment, then crossing two others
+		segments.push(Segment::from_coordinates(5.0, 5.0, 7.0, 5.0).unwrap());
+		assert_eq!(any_intersection(&segments), Some((3, 5)));
+		segments[5] = Segment::from_coordinates(-1.0, 2.0, 6.0, 2.0).unwrap();
+		assert_eq!(any_intersection(&segments), Some((1, 5)));
+
+		//collinear overlap on integers
+		let grid: Vec<Segment<i64>> = vec![Segment::from_coordinates(0, 0, 4, 2).unwrap(), Segment::from_coordinates(6, 3, 2, 1).unwrap()];
+		assert_eq!(any_intersection(&grid), Some((0, 1)));
+		assert_eq!(any_intersection(&grid[..1]), None);
+	}
+
+	//pairs the sweep finds, checked against exact tests of every pair
+	fn check_sweep(segments: &[Segment]) {
+		let mut pairs = std::collections::BTreeSet::new();
+		for f in all_intersections(segments) {
+			for (i, &a) in f.segments.iter().enumerate() {
+				for &b in &f.segments[i+1..] {
+					pairs.insert((a, b));
+				}
+			}
+		}
+		assert_eq!(pairs, brute_pairs(segments));
+	}
+
+	#[test]
+	fn sweep_rounding_test() {
+		let s = |x1: f64, y1: f64, x2: f64, y2: f64| Segment::from_coordinates(x1, y1, x2, y2).unwrap();
+		//crossings of vertical segments whose x rounds past the vertical one
+		check_sweep(&[s(4.0, 8.857142857142858, 2.7142857142857144, 7.2857142857142865), s(3.7142857142857144, 10.0, 3.7142857142857144, 7.142857142857143)]);
+		check_sweep(&[s(7.0, 0.2857142857142857, 7.0, -6.285714285714286), s(1.8571428571428572, 0.14285714285714285, 7.428571428571429, -2.142857142857143)]);
+		check_sweep(&[s(4.571428571428571, 7.285714285714286, 9.857142857142858, 0.7142857142857144), s(7.0, 7.714285714285714, 7.0, 0.8571428571428577)]);
+		//and of nearly vertical ones, which used to leave the status out of order
+		check_sweep(&[
+			s(2.857142857142857, 4.571428571428571, 2.8571428581428573, 9.857142857142858),
+			s(7.142857142857143, 10.857142857142858, 7.142857142857143, 6.2857142857142865),
+			s(7.285714285714286, 10.857142857142858, 5.428571428571429, 5.857142857142858),
+			s(1.8571428571428572, 5.142857142857143, 1.8571428581428573, 6.571428571428572),
+		]);
+		//segments that only came close to an endpoint used to be reported through it
+		check_sweep(&[
+			s(4.285714285714286, 13.0, 6.428571428571428, 14.714285714285714),
+			s(5.428571428571429, 1.4285714285714286, 5.428571429571429, 4.714285714285715),
+			s(5.428571428571429, 1.7142857142857142, 5.428571429571429, 4.714285714285714),
+		]);
+		check_sweep(&[
+			s(11.0, 1.8571428571428572, 4.714285714285714, 3.1428571428571437),
+			s(2.7142857142857144, 7.285714285714286, -1.428571428571428, 5.571428571428571),
+			s(5.857142857142857, 1.0, 7.142857142857143, 2.428571428571429),
+			s(6.857142857142857, 4.285714285714286, 6.857142857142857, -0.4285714285714288),
+		]);
+	}
+
+	#[test]
+	fn sweep_random_test() {
+		let mut next = lcg(7);
+		//small grid, so there are plenty of shared endpoints and collinear pieces
+		let segments = random_segments(200, || {
+			let ends = random_points(&mut next, 2, 30, 1.0, 0.0);
+			(ends[0], ends[1])
+		});
+		check_sweep(&segments);
+
+		//short segments, so some sets have no intersection at all
+		for _ in 0..50 {
+			let segments = random_segments(40, || {
+				let ends = random_points(&mut next, 2, 1000, 1.0, 0.0);
+				(ends[0], Point::new(ends[0].x + next(1000) as f64/20.0 - 25.0, ends[0].y + next(1000) as f64/20.0 - 25.0))
+			});
+			match any_intersection(&segments) {
+				Some((a, b)) => assert!(a < b && segments[a].intersects(&segments[b])),
+				None => assert!(brute_pairs(&segments).is_empty()),
+			}
+		}
+
+		//coordinates in sevenths, which round, half of the segments nearly vertical
+		for _ in 0..2000 {
+			let segments = random_segments(6, || {
+				let start = random_points(&mut next, 1, 100, 7.0, 0.0)[0];
+				let dx = if next(2) == 0 {next(3) as f64*1e-9} else {next(100) as f64/7.0 - 7.0};
+				let dy = next(100) as f64/7.0 - 7.0;
+				(start, Point::new(start.x + dx, start.y + dy))
+			});
+			check_sweep(&segments);
+		}
+	}
+	//SWEEP TEST END
+
+
 	//CIRCLE TEST START
 	#[test]
 	fn circle_circumference_test() {
 		let p1 = Point::new(0.0,0.0);
 		let r:f64 = 6.0;
-		let c = Circle::new(p1, r);
-		assert!(c.circumference() - 37.69911 < EPSILON);
+		let c = Circle::new(p1, r).unwrap();
+		assert!(TOL.approx_eq(c.circumference(), 37.69911));
 	}
 
 	#[test]
 	fn circle_area_test() {
 		let p1 = Point::new(0.0,0.0);
 		let r:f64 = 6.0;
-		let c = Circle::new(p1, r);
-		assert!(c.area() - 226.19467105 < EPSILON);
+		let c = Circle::new(p1, r).unwrap();
+		assert!(TOL.approx_eq(c.area(), 226.19467105));
 	}
 
 	#[test]
 	fn circle_intersects_circle() {
 		let p1 = Point::new(0.0,0.0);
 		let r1:f64 = 6.0;
-		let c1 = Circle::new(p1, r1);
+		let c1 = Circle::new(p1, r1).unwrap();
 
 		let p2 = Point::new(0.0,0.0);
 		let r2:f64 = 6.0;
-		let c2 = Circle::new(p2, r2);
+		let c2 = Circle::new(p2, r2).unwrap();
 
 		let p3 = Point::new(10.0,10.0);
 		let r3:f64 = 3.0;
-		let c3 = Circle::new(p3, r3);
+		let c3 = Circle::new(p3, r3).unwrap();
 
 		let p4 = Point::new(4.0,4.0);
 		let r4:f64 = 7.0;
-		let c4 = Circle::new(p4, r4);
+		let c4 = Circle::new(p4, r4).unwrap();
 
 		assert!(c1.intersects_circle(&c2));
 		assert!(!c1.intersects_circle(&c3));
@@ -362,15 +1078,15 @@ mod tests {
 	fn circle_distance_from_circle_test() {
 		let p1 = Point::new(0.0,0.0);
 		let r1:f64 = 6.0;
-		let c1 = Circle::new(p1, r1);
+		let c1 = Circle::new(p1, r1).unwrap();
 
 		let p2 = Point::new(10.0,10.0);
 		let r2:f64 = 9.0;
-		let c2 = Circle::new(p2, r2);
+		let c2 = Circle::new(p2, r2).unwrap();
 
 		let p3 = Point::new(-10.0,-10.0);
 		let r3:f64 = 3.0;
-		let c3 = Circle::new(p3, r3);
+		let c3 = Circle::new(p3, r3).unwrap();
 
 		assert_eq!(c1.distance_from_circle(&c2), 0.0);
 		assert_eq!(c1.distance_from_circle(&c3), p1.distance(&p3) - r1 - r3);
@@ -381,7 +1097,7 @@ mod tests {
 	fn circle_distance_from_point() {
 		let p1 = Point::new(0.0,0.0);
 		let r1:f64 = 6.0;
-		let c1 = Circle::new(p1, r1);
+		let c1 = Circle::new(p1, r1).unwrap();
 
 		let p2 = Point::new(2.0,1.0);
 
@@ -396,19 +1112,19 @@ mod tests {
 	fn circle_contains_circle_test() {
 		let p1 = Point::new(0.0,0.0);
 		let r1:f64 = 6.0;
-		let c1 = Circle::new(p1, r1);
+		let c1 = Circle::new(p1, r1).unwrap();
 
 		let p2 = Point::new(1.0,1.0);
 		let r2:f64 = 2.0;
-		let c2 = Circle::new(p2, r2);
+		let c2 = Circle::new(p2, r2).unwrap();
 
 		let p3 = Point::new(6.0,0.0);
 		let r3:f64 = 3.0;
-		let c3 = Circle::new(p3, r3);
+		let c3 = Circle::new(p3, r3).unwrap();
 
 		let p4= Point::new(10.0,10.0);
 		let r4:f64 = 2.0;
-		let c4 = Circle::new(p4, r4);
+		let c4 = Circle::new(p4, r4).unwrap();
 
 		assert!(c1.contains_circle(&c2));
 		assert!(!c1.contains_circle(&c3));
@@ -419,7 +1135,7 @@ mod tests {
 	fn circle_arc_length_test() {
 		let p1 = Point::new(0.0,0.0);
 		let r1:f64 = 6.0;
-		let c1 = Circle::new(p1, r1);
+		let c1 = Circle::new(p1, r1).unwrap();
 
 		let p2 = Point::new(-6.0, 0.0);
 		let p3 = Point::new(6.0, 0.0);
@@ -430,11 +1146,64 @@ mod tests {
 		let c2 = c1.circumference()/2.0;
 		let c4 = c2/2.0;
 
-		assert!(c1.arc_length(&p3, &p2) - c2 < EPSILON);
-		assert!(c1.arc_length(&p5, &p4) - c2 < EPSILON);
+		assert!(TOL.approx_eq(c1.arc_length(&p3, &p2), c2));
+		assert!(TOL.approx_eq(c1.arc_length(&p5, &p4), c2));
 
-		assert!(c1.arc_length(&p5, &p3) - c4 < EPSILON);
-		assert!(c1.arc_length(&p3, &p4) - c4 < EPSILON);
+		assert!(TOL.approx_eq(c1.arc_length(&p5, &p3), c4));
+		assert!(TOL.approx_eq(c1.arc_length(&p3, &p4), c4));
+	}
+
+	#[test]
+	fn circle_enclosing_test() {
+		let square = [Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(1.0, 1.5), Point::new(2.0, 2.0), Point::new(0.0, 2.0)];
+		let c = Circle::enclosing(&square).unwrap();
+		assert!(TOL.approx_eq(c.center().x, 1.0) && TOL.approx_eq(c.center().y, 1.0));
+		assert!(TOL.approx_eq(c.radius(), 2.0f64.sqrt()));
+
+		//an obtuse triangle only needs its longest side
+		let c = Circle::enclosing(&[Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(4.0, 0.0)]).unwrap();
+		assert!(c.center().equals(&Point::new(2.0, 0.0)) && c.radius() == 2.0);
+
+		let c = Circle::enclosing(&[Point::new(1.0, 0.0), Point::new(0.0, 0.0), Point::new(3.0, 0.0), Point::new(1.0, 0.0)]).unwrap();
+		assert!(c.center().equals(&Point::new(1.5, 0.0)) && c.radius() == 1.5);
+
+		let c = Circle::enclosing(&[Point::new(3.0, 4.0)]).unwrap();
+		assert!(c.center().equals(&Point::new(3.0, 4.0)) && c.radius() == 0.0);
+		assert_eq!(Circle::<f64>::enclosing(&[]).err(), Some(GeometryError::TooFewPoints { needed: 1, found: 0 }));
+	}
+
+	#[test]
+	fn circle_enclosing_random_test() {
+		let mut next = lcg(25);
+		for round in 0..20 {
+			let points = random_points(&mut next, 30, 2000, 100.0, 0.0);
+			let c = Circle::enclosing(&points).unwrap();
+			for p in &points {
+				assert_eq!(c.distance_from_point(p), 0.0);
+				assert!(c.contains_circle_tol(&Circle::new(*p, 0.0).unwrap(), &TOL));
+			}
+			//same circle for any shuffle
+			let other = Circle::enclosing_seeded(&points, round).unwrap();
+			assert!(TOL.approx_eq(other.radius(), c.radius()) && TOL.approx_eq(other.center().distance(&c.center()), 0.0));
+			//and exactly the same one again for the same seed
+			assert!(Circle::enclosing_seeded(&points, round).unwrap().equals(&other));
+
+			//and none of the circles on two or three of the points holding them all is smaller
+			let holds = |o: &Point, r: f64| points.iter().all(|p| o.distance(p) <= r + 1e-9);
+			let n = points.len();
+			for i in 0..n {
+				for j in i + 1..n {
+					let o = Point::new((points[i].x + points[j].x)/2.0, (points[i].y + points[j].y)/2.0);
+					let r = o.distance(&points[i]);
+					assert!(!holds(&o, r) || r >= c.radius() - 1e-9);
+					for k in j + 1..n {
+						if let Ok(t) = Circle::enclosing(&[points[i], points[j], points[k]]) {
+							assert!(!holds(&t.center(), t.radius()) || t.radius() >= c.radius() - 1e-9);
+						}
+					}
+				}
+			}
+		}
 	}
 	//CIRCLE TEST END
 
@@ -443,8 +1212,8 @@ mod tests {
 	#[test]
 	fn polygon_area_test() {
 		// This is a concave, regular polygon
-		let mut xs: [f64; 6] = [0.0, 0.0, 1.0, 2.0, 1.0, 2.0];
-		let mut ys: [f64; 6] = [0.0, 6.0, 7.0, 5.0, 2.0, 0.0];
+		let xs: [f64; 6] = [0.0, 0.0, 1.0, 2.0, 1.0, 2.0];
+		let ys: [f64; 6] = [0.0, 6.0, 7.0, 5.0, 2.0, 0.0];
 
 		let mut points: Vec<Point> = Vec::new();
 
@@ -452,12 +1221,12 @@ mod tests {
 			let p = Point::new(xs[i], ys[i]);
 			points.push(p);
 		}
-		let poly = Polygon::from_vec(points);
+		let poly = Polygon::from_vec(points).unwrap();
 		assert_eq!(poly.area(), 10.0);
 
 		// This is the same concave, regular polygon but with reversed orientation
-		let mut xs: [f64; 6] = [2.0, 1.0, 2.0, 1.0, 0.0, 0.0];
-		let mut ys: [f64; 6] = [0.0, 2.0, 5.0, 7.0, 6.0, 0.0];
+		let xs: [f64; 6] = [2.0, 1.0, 2.0, 1.0, 0.0, 0.0];
+		let ys: [f64; 6] = [0.0, 2.0, 5.0, 7.0, 6.0, 0.0];
 
 		let mut points: Vec<Point> = Vec::new();
 
@@ -465,9 +1234,962 @@ mod tests {
 			let p = Point::new(xs[i], ys[i]);
 			points.push(p);
 		}
-		let poly = Polygon::from_vec(points);
+		let poly = Polygon::from_vec(points).unwrap();
 		assert_eq!(poly.area(), 10.0);
 
 	}
+
+	#[test]
+	fn polygon_contains_point_test() {
+		//the concave polygon from the area test, with a notch on the right
+		let points = vec![Point::new(0.0, 0.0), Point::new(0.0, 6.0), Point::new(1.0, 7.0), Point::new(2.0, 5.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0)];
+		let poly = Polygon::from_vec(points).unwrap();
+		for rule in [ContainmentRule::CrossingNumber, ContainmentRule::WindingNumber] {
+			assert_eq!(poly.contains_point_with(&Point::new(0.5, 3.0), rule), Location::Inside);
+			assert_eq!(poly.contains_point_with(&Point::new(1.8, 2.0), rule), Location::Outside);
+			assert_eq!(poly.contains_point_with(&Point::new(3.0, 5.0), rule), Location::Outside);
+			assert_eq!(poly.contains_point_with(&Point::new(1.0, 2.0), rule), Location::OnBoundary);
+			assert_eq!(poly.contains_point_with(&Point::new(0.0, 3.0), rule), Location::OnBoundary);
+			assert_eq!(poly.contains_point_with(&Point::new(1.5, 3.5), rule), Location::OnBoundary);
+		}
+		//the ray from this point runs through the vertex at (2, 5)
+		assert_eq!(poly.contains_point(&Point::new(1.0, 5.0)), Location::Inside);
+		assert_eq!(poly.winding_number(&Point::new(1.0, 5.0)), -1);
+	}
+
+	#[test]
+	fn polygon_self_intersecting_test() {
+		//a pentagram: the middle is wound around twice
+		let star: Polygon<i32> = Polygon::from_vec(vec![Point::new(0, 10), Point::new(6, -8), Point::new(-10, 3), Point::new(10, 3), Point::new(-6, -8)]).unwrap();
+		let centre = Point::new(0, 0);
+		assert_eq!(star.winding_number(&centre).abs(), 2);
+		assert_eq!(star.crossing_number(&centre) % 2, 0);
+		assert_eq!(star.contains_point_with(&centre, ContainmentRule::WindingNumber), Location::Inside);
+		assert_eq!(star.contains_point_with(&centre, ContainmentRule::CrossingNumber), Location::Outside);
+
+		//a tip of the star is inside under both rules
+		let tip = Point::new(0, 8);
+		assert_eq!(star.contains_point_with(&tip, ContainmentRule::CrossingNumber), Location::Inside);
+		assert_eq!(star.contains_point(&tip), Location::Inside);
+	}
+
+	#[test]
+	fn polygon_contains_point_convex_test() {
+		//an octagon with a collinear vertex and a square with collinear runs on
+		//both edges at the first vertex, each given both ways round
+		let octagon: Vec<Point<i64>> = vec![Point::new(2, 0), Point::new(5, 0), Point::new(8, 0), Point::new(10, 2), Point::new(10, 8), Point::new(8, 10), Point::new(2, 10), Point::new(0, 8), Point::new(0, 2)];
+		let square: Vec<Point<i64>> = vec![Point::new(0, 0), Point::new(5, 0), Point::new(10, 0), Point::new(10, 5), Point::new(10, 10), Point::new(5, 10), Point::new(0, 10), Point::new(0, 5)];
+		for mut points in [octagon, square] {
+			for _ in 0..2 {
+				let poly = Polygon::from_vec(points.clone()).unwrap();
+				for x in -1..12 {
+					for y in -1..12 {
+						let p = Point::new(x, y);
+						assert_eq!(poly.contains_point_convex(&p), poly.contains_point(&p));
+					}
+				}
+				points.reverse();
+				points.rotate_right(1);
+			}
+		}
+	}
+
+	#[test]
+	fn polygon_validation_test() {
+		let p = |x: i32, y: i32| Point::new(x, y);
+
+		//counterclockwise, but the first three vertices turn clockwise
+		let notch = Polygon::validated(vec![p(4, 4), p(2, 2), p(0, 4), p(0, 0), p(4, 0)]).unwrap();
+		assert!(!notch.is_clockwise());
+		assert_eq!(notch.double_area(), 24);
+		assert!(Polygon::validated(vec![p(0, 0), p(0, 4), p(4, 0)]).unwrap().is_clockwise());
+
+		let error = |points: Vec<Point<i32>>| Polygon::validated(points).err();
+		assert_eq!(error(vec![p(0, 0), p(1, 0)]), Some(GeometryError::TooFewPoints { needed: 3, found: 2 }));
+		assert_eq!(error(vec![p(0, 0), p(1, 0), p(1, 0), p(0, 1)]), Some(GeometryError::ZeroLengthEdge { index: 1 }));
+		assert_eq!(error(vec![p(0, 0), p(1, 0), p(0, 1), p(0, 0)]), Some(GeometryError::ZeroLengthEdge { index: 3 }));
+		assert_eq!(error(vec![p(0, 0), p(2, 0), p(1, 1), p(2, 2), p(0, 2), p(1, 1)]), Some(GeometryError::DuplicateVertex { first: 2, second: 5 }));
+		assert_eq!(error(vec![p(0, 0), p(1, 1), p(3, 3)]), Some(GeometryError::DegenerateRing));
+		//a bow tie, and a spike doubling back along the previous edge
+		assert_eq!(error(vec![p(0, 0), p(2, 2), p(2, 0), p(0, 2)]), Some(GeometryError::SelfIntersection { first: 0, second: 2 }));
+		assert_eq!(error(vec![p(0, 0), p(4, 0), p(2, 0), p(2, 3)]), Some(GeometryError::SelfIntersection { first: 0, second: 1 }));
+		//a vertex touching the middle of another edge
+		assert_eq!(error(vec![p(0, 0), p(4, 0), p(4, 4), p(2, 0), p(0, 4)]), Some(GeometryError::SelfIntersection { first: 0, second: 3 }));
+
+		//from_vec still takes self-intersecting rings
+		let star = Polygon::from_vec(vec![p(0, 10), p(6, -8), p(-10, 3), p(10, 3), p(-6, -8)]).unwrap();
+		assert!(matches!(star.validate(), Err(GeometryError::SelfIntersection { .. })));
+	}
+
+	#[test]
+	fn polygon_repair_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+
+		//clockwise square with a repeated vertex, a straight vertex and a spike
+		let square = Polygon::from_vec(vec![p(0.0, 0.0), p(0.0, 4.0), p(4.0, 4.0), p(4.0, 6.0), p(4.0, 4.0), p(4.0, 2.0), p(4.0, 0.0), p(4.0, 0.0), p(2.0, 0.0)]).unwrap();
+		let repaired = square.repair();
+		assert_eq!(repaired.len(), 1);
+		assert_eq!(repaired[0].points().len(), 4);
+		assert!(!repaired[0].is_clockwise());
+		assert_eq!(repaired[0].area(), 16.0);
+
+		//a bow tie crossing mid edge, and a figure eight touching at a vertex
+		let bowtie = Polygon::from_vec(vec![p(0.0, 0.0), p(2.0, 2.0), p(2.0, 0.0), p(0.0, 2.0)]).unwrap();
+		let eight = Polygon::from_vec(vec![p(0.0, 0.0), p(2.0, 2.0), p(4.0, 0.0), p(4.0, 4.0), p(2.0, 2.0), p(0.0, 4.0)]).unwrap();
+		for (poly, area) in [(bowtie, 1.0), (eight, 4.0)] {
+			let pieces = poly.repair();
+			assert_eq!(pieces.len(), 2);
+			for piece in &pieces {
+				assert!(piece.validate().is_ok());
+				assert!(!piece.is_clockwise());
+				assert_eq!(piece.area(), area);
+			}
+		}
+
+		//nothing left of a ring that only doubles back
+		let flat = Polygon::from_vec(vec![p(0.0, 0.0), p(3.0, 0.0), p(1.0, 0.0)]).unwrap();
+		assert!(flat.repair().is_empty());
+	}
+
+	#[test]
+	fn polygon_validation_holes_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		let square = |x: f64, y: f64, side: f64| vec![p(x, y), p(x + side, y), p(x + side, y + side), p(x, y + side)];
+		let with = |holes: Vec<Vec<Point<f64>>>| Polygon::with_holes(square(0.0, 0.0, 10.0), holes).unwrap().validate();
+
+		assert!(with(vec![square(1.0, 1.0, 2.0), square(5.0, 5.0, 3.0)]).is_ok());
+
+		//each hole is checked like the exterior, indices counting through vertices
+		let bowtie = vec![p(1.0, 1.0), p(3.0, 3.0), p(3.0, 1.0), p(1.0, 3.0)];
+		assert_eq!(with(vec![square(5.0, 5.0, 3.0), bowtie]), Err(GeometryError::SelfIntersection { first: 8, second: 10 }));
+		assert_eq!(with(vec![vec![p(1.0, 1.0), p(2.0, 1.0), p(2.0, 1.0), p(1.0, 2.0)]]), Err(GeometryError::ZeroLengthEdge { index: 5 }));
+		assert_eq!(with(vec![vec![p(1.0, 1.0), p(2.0, 1.0), p(3.0, 1.0)]]), Err(GeometryError::DegenerateRing));
+
+		//holes crossing or touching the exterior or each other
+		assert_eq!(with(vec![square(8.0, 4.0, 4.0)]), Err(GeometryError::SelfIntersection { first: 1, second: 4 }));
+		assert!(matches!(with(vec![square(2.0, 2.0, 4.0), square(4.0, 4.0, 4.0)]), Err(GeometryError::SelfIntersection { .. })));
+		assert_eq!(with(vec![square(0.0, 0.0, 2.0)]), Err(GeometryError::SelfIntersection { first: 0, second: 4 }));
+
+		//touching at a point is fine, but not sharing an edge or crossing there
+		let (notch, wedge) = (vec![p(0.0, 0.0), p(2.0, 1.0), p(1.0, 2.0)], vec![p(1.0, 2.0), p(3.0, 3.0), p(1.0, 3.0)]);
+		assert!(with(vec![notch.clone(), wedge]).is_ok());
+		assert!(with(vec![vec![p(10.0, 5.0), p(8.0, 4.0), p(8.0, 6.0)]]).is_ok());
+		assert_eq!(with(vec![notch.clone(), vec![p(1.0, 2.0), p(2.0, 1.0), p(3.0, 3.0)]]), Err(GeometryError::SelfIntersection { first: 5, second: 7 }));
+		let crossing = vec![p(2.0, 2.0), p(6.0, 6.0), p(8.0, 1.0), p(1.0, 0.5)];
+		assert_eq!(with(vec![square(2.0, 2.0, 4.0), crossing]), Err(GeometryError::MisplacedHole { hole: 0 }));
+		assert_eq!(with(vec![vec![p(10.0, 4.0), p(12.0, 5.0), p(10.0, 6.0), p(8.0, 5.0)]]), Err(GeometryError::MisplacedHole { hole: 0 }));
+
+		//outside the exterior, or inside another hole
+		assert_eq!(with(vec![square(1.0, 1.0, 2.0), square(12.0, 1.0, 2.0)]), Err(GeometryError::MisplacedHole { hole: 1 }));
+		assert_eq!(with(vec![square(1.0, 1.0, 6.0), square(2.0, 2.0, 2.0)]), Err(GeometryError::MisplacedHole { hole: 1 }));
+		assert_eq!(with(vec![square(-5.0, -5.0, 20.0)]), Err(GeometryError::MisplacedHole { hole: 0 }));
+	}
+
+	#[test]
+	fn polygon_repair_holes_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+
+		//a bow tie with a hole in each half, one of them with a repeated
+		//vertex and a straight one
+		let bowtie = vec![p(0.0, 0.0), p(8.0, 8.0), p(8.0, 0.0), p(0.0, 8.0)];
+		let left = vec![p(1.0, 3.0), p(2.0, 3.0), p(2.0, 5.0), p(1.0, 5.0)];
+		let right = vec![p(6.0, 3.0), p(7.0, 3.0), p(7.0, 3.0), p(7.0, 4.0), p(7.0, 5.0), p(6.0, 5.0)];
+		let poly = Polygon::with_holes(bowtie, vec![left, right]).unwrap();
+		let pieces = poly.repair();
+		assert_eq!(pieces.len(), 2);
+		for piece in &pieces {
+			assert!(piece.validate().is_ok());
+			assert!(!piece.is_clockwise());
+			assert!(piece.holes().iter().all(|hole| Polygon::from_vec(hole.clone()).unwrap().is_clockwise()));
+		}
+		assert_eq!(pieces.iter().map(|piece| piece.holes().len()).sum::<usize>(), 2);
+		assert_eq!(pieces.iter().map(|piece| piece.area()).sum::<f64>(), 32.0 - 2.0 - 2.0);
+
+		//overlapping holes are merged, and a hole reaching out of the exterior
+		//cuts into its edge
+		let holes = vec![vec![p(1.0, 1.0), p(4.0, 1.0), p(4.0, 4.0), p(1.0, 4.0)], vec![p(3.0, 3.0), p(6.0, 3.0), p(6.0, 6.0), p(3.0, 6.0)], vec![p(8.0, 1.0), p(12.0, 1.0), p(12.0, 2.0), p(8.0, 2.0)]];
+		let poly = Polygon::with_holes(vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)], holes).unwrap();
+		let pieces = poly.repair();
+		assert_eq!(pieces.len(), 1);
+		assert!(pieces[0].validate().is_ok());
+		assert_eq!(pieces[0].holes().len(), 1);
+		assert_eq!(pieces[0].area(), 100.0 - 17.0 - 2.0);
+	}
+
+	#[test]
+	fn polygon_random_test() {
+		let mut next = lcg(3);
+		for _ in 0..200 {
+			//random rings on a small grid are often, but not always, simple
+			let n = 3 + next(6) as usize;
+			let mut points = Vec::new();
+			while points.len() < n {
+				let q = Point::new(next(6) as i64, next(6) as i64);
+				if !points.contains(&q) {
+					points.push(q);
+				}
+			}
+			let poly = Polygon::from_vec(points.clone()).unwrap();
+
+			let edges: Vec<Segment<i64>> = (0..n).map(|i| Segment::new(points[i], points[(i + 1) % n]).unwrap()).collect();
+			let mut simple = true;
+			for i in 0..n {
+				for j in i+1..n {
+					let neighbours = j == i + 1 || (i == 0 && j == n - 1);
+					if neighbours {
+						//the shared vertex is counted once from each side, any more is an overlap
+						let (a, b) = (edges[i], edges[j]);
+						let touching = [a.contains(&b.p1), a.contains(&b.p2), b.contains(&a.p1), b.contains(&a.p2)];
+						simple &= touching.iter().filter(|&&t| t).count() == 2;
+					} else {
+						simple &= !edges[i].intersects(&edges[j]);
+					}
+				}
+			}
+			assert_eq!(poly.validate().is_ok(), simple);
+		}
+
+		for _ in 0..100 {
+			//repeated vertices too, and exact so pieces meet where they should
+			let n = 3 + next(8) as usize;
+			let poly = Polygon::from_vec(to_exact(&random_points(&mut next, n, 6, 1.0, 0.0))).unwrap();
+			let pieces = poly.repair();
+			for piece in &pieces {
+				assert!(piece.validate().is_ok());
+				assert!(!piece.is_clockwise());
+			}
+			//anywhere the ring winds around is covered by some piece
+			for q in probes(0, 5) {
+				if poly.contains_point(&q) == Location::Inside {
+					assert!(pieces.iter().any(|piece| piece.contains_point(&q) != Location::Outside));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn polygon_holes_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		//one hole clockwise and one counterclockwise
+		let holes = vec![vec![p(1.0, 1.0), p(1.0, 3.0), p(3.0, 3.0), p(3.0, 1.0)], vec![p(6.0, 6.0), p(9.0, 6.0), p(9.0, 8.0), p(6.0, 8.0)]];
+		let poly = Polygon::with_holes(vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)], holes).unwrap();
+		assert_eq!(poly.area(), 90.0);
+		assert_eq!(poly.perimeter(), 58.0);
+		let c = poly.centroid().unwrap();
+		assert!((c.x - 447.0/90.0).abs() < 1e-12 && (c.y - 5.0).abs() < 1e-12);
+
+		for rule in [ContainmentRule::CrossingNumber, ContainmentRule::WindingNumber] {
+			assert_eq!(poly.contains_point_with(&p(2.0, 2.0), rule), Location::Outside);
+			assert_eq!(poly.contains_point_with(&p(1.0, 2.0), rule), Location::OnBoundary);
+			assert_eq!(poly.contains_point_with(&p(5.0, 5.0), rule), Location::Inside);
+			assert_eq!(poly.contains_point_with(&p(11.0, 5.0), rule), Location::Outside);
+		}
+		assert_eq!(poly.contains_point_convex(&p(7.0, 7.0)), Location::Outside);
+		assert_eq!(poly.contains_point_convex(&p(6.0, 7.0)), Location::OnBoundary);
+		assert_eq!(poly.contains_point_convex(&p(5.0, 5.0)), Location::Inside);
+
+		assert_eq!(Polygon::with_holes(poly.points().to_vec(), vec![vec![p(1.0, 1.0)]]).err(), Some(GeometryError::TooFewPoints { needed: 3, found: 1 }));
+		assert!(square(0.0, 0.0, 1.0, 1.0).difference(&square(0.0, 0.0, 1.0, 1.0)).centroid().is_none());
+	}
+
+	#[test]
+	fn multipolygon_test() {
+		let ring = square(0.0, 0.0, 4.0, 4.0).difference(&square(1.0, 1.0, 3.0, 3.0));
+		let shapes = MultiPolygon::new(vec![ring.polygons()[0].clone(), square(6.0, 0.0, 8.0, 4.0)]);
+		assert_eq!(shapes.len(), 2);
+		assert_eq!(shapes.area(), 20.0);
+		assert_eq!(shapes.perimeter(), 36.0);
+		//(2, 2) weighted by 12 and (7, 2) by 8
+		let c = shapes.centroid().unwrap();
+		assert!((c.x - 4.0).abs() < 1e-12 && (c.y - 2.0).abs() < 1e-12);
+		assert_eq!(shapes.contains_point(&Point::new(2.0, 2.0)), Location::Outside);
+		assert_eq!(shapes.contains_point(&Point::new(0.5, 2.0)), Location::Inside);
+		assert_eq!(shapes.contains_point(&Point::new(7.0, 2.0)), Location::Inside);
+		assert_eq!(shapes.contains_point(&Point::new(6.0, 2.0)), Location::OnBoundary);
+		assert_eq!(shapes.contains_point(&Point::new(5.0, 2.0)), Location::Outside);
+	}
 	//POLYGON TEST END
+
+	//BOOLEAN TEST START
+	fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> Polygon {
+		Polygon::from_vec(vec![Point::new(x0, y0), Point::new(x1, y0), Point::new(x1, y1), Point::new(x0, y1)]).unwrap()
+	}
+
+	//area, checking every ring runs the right way
+	fn area_with_holes(result: &MultiPolygon) -> f64 {
+		for poly in result.polygons() {
+			assert!(poly.validate().is_ok() && !poly.is_clockwise());
+			for hole in poly.holes() {
+				let hole = Polygon::from_vec(hole.clone()).unwrap();
+				assert!(hole.validate().is_ok() && hole.is_clockwise());
+			}
+		}
+		result.area()
+	}
+
+	#[test]
+	fn boolean_squares_test() {
+		let (a, b) = (square(0.0, 0.0, 2.0, 2.0), square(1.0, 1.0, 3.0, 3.0));
+		let union = a.union(&b);
+		assert_eq!(union.len(), 1);
+		assert_eq!(union.polygons()[0].points().len(), 8);
+		assert_eq!(area_with_holes(&union), 7.0);
+		let intersection = a.intersection(&b);
+		assert_eq!(intersection.polygons()[0].points().len(), 4);
+		assert_eq!(area_with_holes(&intersection), 1.0);
+		assert_eq!(area_with_holes(&a.difference(&b)), 3.0);
+		//two L shapes touching at two corners
+		let xor = a.xor(&b);
+		assert_eq!(xor.len(), 2);
+		assert_eq!(area_with_holes(&xor), 6.0);
+
+		//a shared edge merges, a shared vertex does not
+		let right = square(2.0, 0.0, 4.0, 2.0);
+		let union = a.union(&right);
+		assert_eq!(union.len(), 1);
+		assert_eq!(union.polygons()[0].points().len(), 4);
+		assert!(a.intersection(&right).is_empty());
+		assert_eq!(area_with_holes(&a.xor(&right)), 8.0);
+		assert_eq!(a.union(&square(2.0, 2.0, 4.0, 4.0)).len(), 2);
+		assert!(a.difference(&a).is_empty());
+	}
+
+	#[test]
+	fn boolean_holes_test() {
+		let outer = square(0.0, 0.0, 4.0, 4.0);
+		let ring = outer.difference(&square(1.0, 1.0, 3.0, 3.0));
+		assert_eq!(ring.len(), 1);
+		assert_eq!(ring.polygons()[0].holes().len(), 1);
+		assert_eq!(area_with_holes(&ring), 12.0);
+
+		//a hole touching the exterior at a vertex
+		let notch = Polygon::from_vec(vec![Point::new(0.0, 2.0), Point::new(2.0, 1.0), Point::new(2.0, 3.0)]).unwrap();
+		let touching = outer.difference(&notch);
+		assert_eq!(touching.len(), 1);
+		assert_eq!(touching.polygons()[0].holes().len(), 1);
+		assert_eq!(area_with_holes(&touching), 14.0);
+
+		//holes in the input, partly filled by the other shape
+		let holed = &ring.polygons()[0];
+		let union = holed.union(&square(2.0, 1.0, 6.0, 3.0));
+		assert_eq!(union.polygons()[0].holes().len(), 1);
+		assert_eq!(area_with_holes(&union), 18.0);
+		assert_eq!(area_with_holes(&holed.intersection(&square(2.0, 0.0, 6.0, 4.0))), 6.0);
+
+		//an island inside a hole stays its own polygon
+		let island = ring.union(&MultiPolygon::from(square(1.5, 1.5, 2.5, 2.5)));
+		assert_eq!(island.len(), 2);
+		assert_eq!(area_with_holes(&island), 13.0);
+	}
+
+	#[test]
+	fn boolean_random_test() {
+		let mut next = lcg(5);
+		//stars round a random centre, with many shared vertices and collinear
+		//edges between the two
+		let mut star = || {
+			let centre = (next(3) as i32, next(3) as i32);
+			Polygon::from_vec(star(&mut next, centre, &OCTANTS, 1, 1)).unwrap()
+		};
+		let inside = |poly: &Polygon<Rational>, q: &Point<Rational>| poly.contains_point(q) == Location::Inside;
+		for _ in 0..6 {
+			let (a, b) = (star(), star());
+			for op in [BooleanOp::Union, BooleanOp::Intersection, BooleanOp::Difference, BooleanOp::Xor] {
+				let result = boolean(std::slice::from_ref(&a), std::slice::from_ref(&b), op);
+				for q in probes(-3, 5) {
+					if a.contains_point(&q) == Location::OnBoundary || b.contains_point(&q) == Location::OnBoundary {
+						continue;
+					}
+					let found = result.contains_point(&q) == Location::Inside;
+					assert_eq!(found, op.keeps(inside(&a, &q), inside(&b, &q)));
+				}
+			}
+		}
+	}
+	//BOOLEAN TEST END
+
+	//CLIP TEST START
+	#[test]
+	fn clip_sutherland_hodgman_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		let window = square(0.0, 0.0, 4.0, 4.0);
+		let triangle = Polygon::from_vec(vec![p(2.0, -2.0), p(6.0, 2.0), p(2.0, 6.0)]).unwrap();
+		let clipped = triangle.clip_convex(&window).unwrap();
+		assert_eq!(clipped.area(), 8.0);
+		assert!(!clipped.is_clockwise());
+
+		//a clockwise window, a subject inside it and one outside
+		let mut reversed = window.points().to_vec();
+		reversed.reverse();
+		let reversed = Polygon::from_vec(reversed).unwrap();
+		assert_eq!(triangle.clip_convex(&reversed).unwrap().area(), 8.0);
+		assert_eq!(square(1.0, 1.0, 2.0, 2.0).clip_convex(&window).unwrap().points().len(), 4);
+		assert!(square(5.0, 5.0, 6.0, 6.0).clip_convex(&window).is_none());
+
+		//a U cut into two pieces comes back joined along the window edge
+		let u = Polygon::from_vec(vec![p(0.0, 0.0), p(3.0, 0.0), p(3.0, 6.0), p(2.0, 6.0), p(2.0, 1.0), p(1.0, 1.0), p(1.0, 6.0), p(0.0, 6.0)]).unwrap();
+		let viewport = square(-1.0, 2.0, 4.0, 5.0);
+		assert_eq!(u.clip_convex(&viewport).unwrap().area(), 6.0);
+
+		//holes are clipped too, or dropped when outside
+		let holed = Polygon::with_holes(square(0.0, 0.0, 6.0, 6.0).points().to_vec(), vec![square(1.0, 1.0, 3.0, 3.0).points().to_vec(), square(4.0, 4.0, 5.0, 5.0).points().to_vec()]).unwrap();
+		let clipped = holed.clip_convex(&window).unwrap();
+		assert_eq!(clipped.area(), 12.0);
+		assert_eq!(clipped.holes().len(), 1);
+	}
+
+	#[test]
+	fn clip_weiler_atherton_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		//two combs crossing at right angles give a grid of pieces
+		let comb = Polygon::from_vec(vec![p(0.0, 0.0), p(5.0, 0.0), p(5.0, 5.0), p(4.0, 5.0), p(4.0, 1.0), p(3.0, 1.0), p(3.0, 5.0), p(2.0, 5.0), p(2.0, 1.0), p(1.0, 1.0), p(1.0, 5.0), p(0.0, 5.0)]).unwrap();
+		let across = Polygon::from_vec(comb.points().iter().map(|q| p(q.y, q.x)).collect()).unwrap();
+		let pieces = comb.clip(&across);
+		assert_eq!(pieces.len(), 5);
+		assert_eq!(area_with_holes(&pieces), area_with_holes(&comb.intersection(&across)));
+		let teeth = comb.clip(&square(-1.0, 2.0, 6.0, 4.0));
+		assert_eq!(teeth.len(), 3);
+		assert_eq!(area_with_holes(&teeth), 6.0);
+
+		//containment either way, shared edges, and a single shared vertex
+		let window = square(0.0, 0.0, 4.0, 4.0);
+		assert_eq!(area_with_holes(&square(1.0, 1.0, 2.0, 2.0).clip(&window)), 1.0);
+		assert_eq!(area_with_holes(&window.clip(&square(1.0, 1.0, 2.0, 2.0))), 1.0);
+		assert_eq!(area_with_holes(&window.clip(&square(0.0, 0.0, 2.0, 4.0))), 8.0);
+		assert!(window.clip(&square(4.0, 0.0, 6.0, 4.0)).is_empty());
+		assert!(window.clip(&square(4.0, 4.0, 6.0, 6.0)).is_empty());
+		assert_eq!(window.clip(&window).polygons()[0].points().len(), 4);
+
+		//holes on either side are kept out of the result
+		let frame = Polygon::with_holes(window.points().to_vec(), vec![square(1.0, 1.0, 3.0, 3.0).points().to_vec()]).unwrap();
+		let half = square(0.0, 0.0, 2.0, 4.0);
+		for clipped in [frame.clip(&half), half.clip(&frame)] {
+			assert_eq!(clipped.len(), 1);
+			assert_eq!(area_with_holes(&clipped), 6.0);
+			assert_eq!(clipped.contains_point(&p(1.5, 2.0)), Location::Outside);
+		}
+		assert_eq!(area_with_holes(&frame.clip(&square(1.5, 1.5, 2.5, 2.5))), 0.0);
+	}
+
+	#[test]
+	fn clip_random_test() {
+		let mut next = lcg(17);
+		for _ in 0..40 {
+			let centre = (next(3) as i32, next(3) as i32);
+			let subject = Polygon::from_vec(star(&mut next, centre, &OCTANTS, 1, 1)).unwrap();
+			let cloud = to_exact(&random_points(&mut next, 8, 6, 1.0, 0.0));
+			let hull = convex_hull_with(&cloud, HullAlgorithm::MonotoneChain);
+			if hull.len() < 3 {
+				continue;
+			}
+			let window = Polygon::from_vec(hull).unwrap();
+
+			//both clippers cover exactly the area the full boolean finds
+			let area = |m: &MultiPolygon<Rational>| m.polygons().iter().fold(Rational::from_integer(0), |a, poly| a + poly.area());
+			let expected = area(&subject.intersection(&window));
+			assert_eq!(subject.clip_convex(&window).map_or(Rational::from_integer(0), |poly| poly.area()), expected);
+			let pieces = subject.clip(&window);
+			assert_eq!(area(&pieces), expected);
+			assert_eq!(area(&window.clip(&subject)), expected);
+			for poly in pieces.polygons() {
+				assert!(poly.validate().is_ok() && !poly.is_clockwise());
+			}
+		}
+	}
+	//CLIP TEST END
+
+	//TRIANGULATE TEST START
+	//triangles cover exactly the polygon: no area missing or counted twice, every one inside
+	fn check_triangulation(poly: &Polygon, triangles: &[[usize; 3]]) {
+		let vertices: Vec<Point> = poly.vertices().copied().collect();
+		let mut area = 0.0;
+		for t in triangles {
+			let (a, b, c) = (vertices[t[0]], vertices[t[1]], vertices[t[2]]);
+			assert_eq!(a.orientation(&b, &c), 2);
+			area += Polygon::from_vec(vec![a, b, c]).unwrap().double_area();
+			let centre = Point::new((a.x + b.x + c.x)/3.0, (a.y + b.y + c.y)/3.0);
+			assert_eq!(poly.contains_point(&centre), Location::Inside);
+		}
+		assert_eq!(area, poly.double_area());
+	}
+
+	#[test]
+	fn triangulate_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		let comb = Polygon::from_vec(vec![p(0.0, 0.0), p(5.0, 0.0), p(5.0, 5.0), p(4.0, 5.0), p(4.0, 1.0), p(3.0, 1.0), p(3.0, 5.0), p(2.0, 5.0), p(2.0, 1.0), p(1.0, 1.0), p(1.0, 5.0), p(0.0, 5.0)]).unwrap();
+		let mut clockwise = comb.points().to_vec();
+		clockwise.reverse();
+		let clockwise = Polygon::from_vec(clockwise).unwrap();
+		//a hole running each way, one with its rightmost edge vertical
+		let holes = vec![vec![p(1.0, 1.0), p(3.0, 2.0), p(1.0, 3.0)], vec![p(5.0, 5.0), p(5.0, 8.0), p(8.0, 8.0), p(8.0, 5.0)]];
+		let holed = Polygon::with_holes(vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)], holes).unwrap();
+		for algorithm in [TriangulationAlgorithm::EarClipping, TriangulationAlgorithm::Monotone] {
+			for poly in [&comb, &clockwise] {
+				let triangles = triangulate_with(poly, algorithm);
+				assert_eq!(triangles.len(), 10);
+				check_triangulation(poly, &triangles);
+			}
+			//n - 2 + 2 per hole
+			let triangles = triangulate_with(&holed, algorithm);
+			assert_eq!(triangles.len(), 11 - 2 + 4);
+			check_triangulation(&holed, &triangles);
+		}
+		//the first bridge, from the higher hole, is all that blocks the nearest
+		//vertex for the lower one
+		let holes = vec![vec![p(9.0, 6.0), p(9.0, 3.0), p(3.0, 3.0)], vec![p(12.0, 15.0), p(3.0, 24.0), p(6.0, 15.0)]];
+		let bridged = Polygon::with_holes(vec![p(0.0, 0.0), p(30.0, 0.0), p(30.0, 30.0), p(0.0, 30.0)], holes).unwrap();
+		for algorithm in [TriangulationAlgorithm::EarClipping, TriangulationAlgorithm::Monotone] {
+			let triangles = triangulate_with(&bridged, algorithm);
+			assert_eq!(triangles.len(), 10 - 2 + 4);
+			check_triangulation(&bridged, &triangles);
+		}
+		//holes touching the exterior inside an edge and at a corner
+		let touching = [vec![p(10.0, 5.0), p(6.0, 4.0), p(6.0, 6.0)], vec![p(10.0, 10.0), p(6.0, 7.0), p(7.0, 6.0)]];
+		for (hole, area) in touching.iter().zip([96.0, 96.5]) {
+			let poly = Polygon::with_holes(vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)], vec![hole.clone()]).unwrap();
+			assert_eq!(poly.area(), area);
+			for algorithm in [TriangulationAlgorithm::EarClipping, TriangulationAlgorithm::Monotone] {
+				check_triangulation(&poly, &triangulate_with(&poly, algorithm));
+			}
+		}
+		assert_eq!(square(0.0, 0.0, 1.0, 1.0).triangulate().len(), 2);
+		//indices past the exterior point into the holes
+		assert_eq!(holed.vertices().nth(8), Some(&p(5.0, 8.0)));
+	}
+
+	#[test]
+	fn triangulate_random_test() {
+		let mut next = lcg(23);
+		//stars with coordinates that are multiples of 3, so triangle centres are exact
+		let directions = [(1, 0), (2, 1), (1, 1), (1, 2), (0, 1), (-1, 2), (-1, 1), (-2, 1), (-1, 0), (-2, -1), (-1, -1), (-1, -2), (0, -1), (1, -2), (1, -1), (2, -1)];
+		for _ in 0..200 {
+			let points: Vec<Point> = star(&mut next, (0, 0), &directions, 2, 3);
+			//a square hole, or a triangle touching the exterior at its first vertex
+			let mut hole = vec![Point::new(-3.0, -3.0), Point::new(3.0, -3.0), Point::new(3.0, 3.0), Point::new(-3.0, 3.0)];
+			if next(2) == 0 {
+				hole = vec![Point::new(-3.0, -3.0), points[0], Point::new(-3.0, 3.0)];
+			}
+			if next(2) == 0 {
+				hole.reverse();
+			}
+			let holes = if next(3) != 0 {vec![hole]} else {Vec::new()};
+			let expected = points.len() + holes.iter().map(|hole| hole.len() + 2).sum::<usize>() - 2;
+			let poly = Polygon::with_holes(points, holes).unwrap();
+			assert!(poly.validate().is_ok());
+			for algorithm in [TriangulationAlgorithm::EarClipping, TriangulationAlgorithm::Monotone] {
+				let triangles = triangulate_with(&poly, algorithm);
+				//vertices in line with both neighbours can leave fewer triangles
+				assert!(triangles.len() <= expected);
+				check_triangulation(&poly, &triangles);
+			}
+		}
+	}
+	//TRIANGULATE TEST END
+
+	//DELAUNAY TEST START
+	//counterclockwise triangles that meet their neighbours edge to edge, cover
+	//the hull and have no point strictly inside their circumcircles
+	fn check_delaunay<T: Scalar>(mesh: &Triangulation<T>) {
+		let points = mesh.points();
+		let mut area = T::zero();
+		for (t, triangle) in mesh.triangles().iter().enumerate() {
+			let [a, b, c] = triangle.map(|v| points[v].clone());
+			assert_eq!(a.orientation(&b, &c), 2);
+			area = area + Polygon::from_vec(vec![a.clone(), b.clone(), c.clone()]).unwrap().double_area();
+			for p in points {
+				assert_ne!(T::incircle_sign(&a, &b, &c, p), std::cmp::Ordering::Greater);
+			}
+			for k in 0..3 {
+				if let Some(n) = mesh.neighbours()[t][k] {
+					let edge = [triangle[(k + 1) % 3], triangle[(k + 2) % 3]];
+					let across = mesh.triangles()[n];
+					assert!((0..3).any(|j| [across[(j + 1) % 3], across[(j + 2) % 3]] == [edge[1], edge[0]]));
+					assert!(mesh.neighbours()[n].contains(&Some(t)));
+				}
+			}
+		}
+		match Polygon::from_vec(convex_hull(points)) {
+			Ok(hull) => assert!((area - hull.double_area()).abs().to_f64() < 1e-9),
+			Err(_) => assert!(mesh.is_empty()),
+		}
+	}
+
+	#[test]
+	fn delaunay_test() {
+		//a grid is full of cocircular and collinear points
+		let mut grid = Vec::new();
+		for x in 0..6 {
+			for y in 0..6 {
+				grid.push(Point::new(x as i64, y as i64));
+			}
+		}
+		let mesh = delaunay(&grid);
+		//2n - 2 - h, with every point on the hull counted
+		assert_eq!(mesh.len(), 2*36 - 2 - 20);
+		check_delaunay(&mesh);
+
+		//twelve points on one circle with its centre, each repeated
+		let circle = [(5, 0), (4, 3), (3, 4), (0, 5), (-3, 4), (-4, 3), (-5, 0), (-4, -3), (-3, -4), (0, -5), (3, -4), (4, -3), (0, 0)];
+		let points: Vec<Point<i32>> = circle.iter().chain(circle.iter()).map(|&(x, y)| Point::new(x, y)).collect();
+		let mesh = delaunay(&points);
+		assert_eq!(mesh.len(), 12);
+		assert!(mesh.triangles().iter().flatten().all(|&v| v < 13));
+		check_delaunay(&mesh);
+
+		//nothing to triangulate
+		assert!(delaunay(&[Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 2.0), Point::new(1.0, 1.0)]).is_empty());
+		assert!(delaunay::<f64>(&[]).is_empty());
+	}
+
+	//locally Delaunay across every free edge, each constraint a chain of constrained edges
+	fn check_constrained<T: Field>(mesh: &Triangulation<T>, constraints: &[Segment<T>]) {
+		let points = mesh.points();
+		for (t, triangle) in mesh.triangles().iter().enumerate() {
+			let [a, b, c] = triangle.map(|v| &points[v]);
+			assert_eq!(a.orientation(b, c), 2);
+			for k in 0..3 {
+				if let Some(n) = mesh.neighbours()[t][k] {
+					let across = mesh.triangles()[n];
+					let back = (0..3).find(|&j| mesh.neighbours()[n][j] == Some(t)).unwrap();
+					assert_eq!([across[(back + 1) % 3], across[(back + 2) % 3]], [triangle[(k + 2) % 3], triangle[(k + 1) % 3]]);
+					assert_eq!(mesh.constrained()[t][k], mesh.constrained()[n][back]);
+					if !mesh.constrained()[t][k] {
+						assert_ne!(T::incircle_sign(a, b, c, &points[across[back]]), std::cmp::Ordering::Greater);
+					}
+				}
+			}
+		}
+		//points all in line give no triangles to hold the constraints
+		if mesh.is_empty() {
+			return;
+		}
+		for s in constraints {
+			let mut at = s.p1.clone();
+			while !at.equals(&s.p2) {
+				let step = mesh.triangles().iter().zip(mesh.constrained()).flat_map(|(triangle, flags)| {
+					(0..3).filter(move |&k| flags[k]).flat_map(move |k| {
+						let (u, w) = (&points[triangle[(k + 1) % 3]], &points[triangle[(k + 2) % 3]]);
+						[(u, w), (w, u)]
+					})
+				}).find(|(u, w)| u.equals(&at) && s.p1.orientation(&s.p2, w) == 0 && w.distance_squared(&s.p2) < at.distance_squared(&s.p2));
+				at = step.expect("constraint missing").1.clone();
+			}
+		}
+	}
+
+	#[test]
+	fn constrained_delaunay_test() {
+		let mut grid = Vec::new();
+		for x in 0..5 {
+			for y in 0..5 {
+				grid.push(Point::new(x as f64, y as f64));
+			}
+		}
+		//one through grid points, two crossing between them and one along the hull
+		let constraints = vec![
+			Segment::from_coordinates(0.0, 0.0, 4.0, 4.0).unwrap(),
+			Segment::from_coordinates(0.0, 0.0, 4.0, 1.0).unwrap(),
+			Segment::from_coordinates(0.0, 1.0, 4.0, 0.0).unwrap(),
+			Segment::from_coordinates(0.0, 4.0, 0.0, 0.0).unwrap(),
+		];
+		let exact: Vec<Segment<Rational>> = constraints.iter().map(|s| {
+			let ends = to_exact(&[s.p1, s.p2]);
+			Segment::new(ends[0].clone(), ends[1].clone()).unwrap()
+		}).collect();
+		let mesh = constrained_delaunay(&to_exact(&grid), &exact);
+		check_constrained(&mesh, &exact);
+		//Steiner points where the crossing pair meet, and where one crosses the diagonal
+		assert_eq!(mesh.points().len(), 25 + 8 + 2);
+		assert!(mesh.points()[33..].contains(&Point::new(Rational::from_integer(2), Rational::new(1, 2))));
+		assert!(mesh.points()[33..].contains(&Point::new(Rational::new(4, 5), Rational::new(4, 5))));
+		assert_eq!(mesh.len(), 2*27 - 2 - 16);
+		//rounded crossing points are only near the constraints
+		let mesh = constrained_delaunay(&grid, &constraints);
+		check_constrained(&mesh, &[]);
+		assert_eq!(mesh.len(), 2*27 - 2 - 16);
+
+		//a comb with a hole keeps only its inside
+		let p = |x: f64, y: f64| Point::new(x, y);
+		let comb = Polygon::with_holes(
+			vec![p(0.0, 0.0), p(6.0, 0.0), p(6.0, 5.0), p(4.0, 5.0), p(4.0, 2.0), p(2.0, 2.0), p(2.0, 5.0), p(0.0, 5.0)],
+			vec![vec![p(1.0, 0.5), p(1.0, 1.5), p(5.0, 1.5), p(5.0, 0.5)]],
+		).unwrap();
+		let mesh = constrained_delaunay_polygon(&comb);
+		assert_eq!(mesh.len(), 12 - 2 + 2);
+		let mut area = 0.0;
+		for triangle in mesh.triangles() {
+			let [a, b, c] = triangle.map(|v| mesh.points()[v]);
+			area += Polygon::from_vec(vec![a, b, c]).unwrap().double_area();
+			assert_eq!(comb.contains_point(&p((a.x + b.x + c.x)/3.0, (a.y + b.y + c.y)/3.0)), Location::Inside);
+		}
+		assert_eq!(area, comb.double_area());
+		assert_eq!(mesh.constrained().iter().flatten().filter(|&&c| c).count(), 12);
+	}
+
+	fn check_refined(mesh: &Triangulation, poly: &Polygon, min_angle: f64, max_area: f64) {
+		let mut area = 0.0;
+		for triangle in mesh.triangles() {
+			let [a, b, c] = triangle.map(|v| mesh.points()[v]);
+			let double = Polygon::from_vec(vec![a, b, c]).unwrap().double_area();
+			assert!(double <= 2.0*max_area);
+			area += double;
+			assert_eq!(poly.contains_point(&Point::new((a.x + b.x + c.x)/3.0, (a.y + b.y + c.y)/3.0)), Location::Inside);
+		}
+		assert!((area - poly.double_area()).abs() < 1e-9*poly.double_area());
+		assert!(mesh.min_angle().unwrap() >= min_angle);
+		check_constrained(mesh, &[]);
+	}
+
+	#[test]
+	fn refine_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		let comb = Polygon::with_holes(
+			vec![p(0.0, 0.0), p(6.0, 0.0), p(6.0, 5.0), p(4.0, 5.0), p(4.0, 2.0), p(2.0, 2.0), p(2.0, 5.0), p(0.0, 5.0)],
+			vec![vec![p(1.0, 0.5), p(1.0, 1.5), p(5.0, 1.5), p(5.0, 0.5)]],
+		).unwrap();
+		assert!(constrained_delaunay_polygon(&comb).min_angle().unwrap() < 0.2);
+		let mesh = refine(&comb, 0.36, None).unwrap();
+		check_refined(&mesh, &comb, 0.36, f64::INFINITY);
+		let small = refine(&comb, 0.36, Some(0.05)).unwrap();
+		check_refined(&small, &comb, 0.36, 0.05);
+		assert!(small.len() > 4*mesh.len());
+
+		//a 15 degree corner keeps its small angles but still finishes
+		let (s, c) = (0.25f64.sin(), 0.25f64.cos());
+		let wedge = Polygon::from_vec(vec![p(0.0, 0.0), p(10.0*c, 10.0*s), p(0.0, 10.0*s)]).unwrap();
+		let mesh = refine(&wedge, 0.36, Some(1.0)).unwrap();
+		let area: f64 = mesh.triangles().iter().map(|triangle| Polygon::from_vec(triangle.map(|v| mesh.points()[v]).to_vec()).unwrap().double_area()).sum();
+		assert!((area - wedge.double_area()).abs() < 1e-9);
+		assert!(mesh.min_angle().unwrap() < 0.36);
+
+		//bounds it could refine forever trying to meet
+		assert_eq!(refine(&comb, 0.4, None).err(), Some(GeometryError::InvalidBound));
+		assert_eq!(refine(&comb, f64::NAN, None).err(), Some(GeometryError::InvalidBound));
+		assert_eq!(refine(&comb, 0.3, Some(0.0)).err(), Some(GeometryError::InvalidBound));
+		assert_eq!(refine(&comb, 0.3614, None).err(), Some(GeometryError::InvalidBound));
+		assert!(refine(&comb, 0.3613, None).unwrap().min_angle().unwrap() >= 0.3613);
+	}
+
+	#[test]
+	fn delaunay_random_test() {
+		let mut random = lcg(31);
+		//constraints between quarter grid points, exact in Rational
+		for round in 0..20 {
+			let points = to_exact(&random_points(&mut random, 10, 64, 4.0, 0.0));
+			let constraints = random_segments(2 + round % 4, || {
+				let ends = to_exact(&random_points(&mut random, 2, 64, 4.0, 0.0));
+				(ends[0].clone(), ends[1].clone())
+			});
+			check_constrained(&constrained_delaunay(&points, &constraints), &constraints);
+		}
+		let mut next = || random(1 << 31) as f64/(1u64 << 31) as f64;
+		for size in [3, 10, 100, 400] {
+			let points: Vec<Point> = (0..size).map(|_| Point::new(next(), next())).collect();
+			check_delaunay(&delaunay(&points));
+		}
+		//snapped to a coarse grid, so with many duplicates, exact in Rational
+		let points: Vec<Point> = (0..60).map(|_| Point::new((next()*8.0).floor()/4.0, (next()*8.0).floor()/4.0)).collect();
+		check_delaunay(&delaunay(&points));
+		check_delaunay(&delaunay(&to_exact(&points)));
+		for _ in 0..20 {
+			//stars with wide enough corners
+			let n = 5 + (next()*8.0) as usize;
+			let points: Vec<Point> = (0..n).map(|i| {
+				let angle = (i as f64 + 0.3*next())*std::f64::consts::TAU/n as f64;
+				let radius = 4.0 + 2.0*next();
+				Point::new(radius*angle.cos(), radius*angle.sin())
+			}).collect();
+			let poly = Polygon::from_vec(points).unwrap();
+			check_refined(&refine(&poly, 0.35, Some(0.5)).unwrap(), &poly, 0.35, 0.5);
+		}
+	}
+	//DELAUNAY TEST END
+
+	//VORONOI TEST START
+	fn check_voronoi(diagram: &Voronoi, min: Point, max: Point) {
+		let sites = diagram.sites();
+		let mut area = 0.0;
+		for (i, cell) in diagram.cells().iter().enumerate() {
+			let Some(cell) = cell else {
+				assert!(diagram.boundary(i).is_empty());
+				continue;
+			};
+			assert!(!cell.is_clockwise());
+			area += cell.area();
+			for q in cell.points() {
+				assert!(q.x >= min.x && q.x <= max.x && q.y >= min.y && q.y <= max.y);
+				for s in sites {
+					assert!(q.distance(&sites[i]) <= q.distance(s) + 1e-9);
+				}
+			}
+			let edges = diagram.boundary(i);
+			assert_eq!(edges.len(), cell.points().len());
+			for (k, &e) in edges.iter().enumerate() {
+				let edge = diagram.half_edges()[e];
+				assert!(diagram.vertices()[edge.origin].equals(&cell.points()[k]));
+				assert_eq!((edge.site, diagram.half_edges()[edge.next].prev), (i, e));
+				if let Some(twin) = edge.twin {
+					let other = diagram.half_edges()[twin];
+					assert_eq!(other.twin, Some(e));
+					assert_ne!(other.site, i);
+					assert_eq!(other.origin, diagram.half_edges()[edge.next].origin);
+				}
+			}
+		}
+		let size = (max.x - min.x)*(max.y - min.y);
+		assert!((area - size).abs() < 1e-9*size);
+	}
+
+	#[test]
+	fn voronoi_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		let bisector = Line::bisector(&p(0.0, 0.0), &p(2.0, 0.0)).unwrap();
+		assert!(bisector.contains(&p(1.0, 5.0)) && bisector.direction() == (0.0, 2.0));
+		assert_eq!(Line::bisector(&p(1.0, 1.0), &p(1.0, 1.0)).err(), Some(GeometryError::DegenerateLine));
+
+		//a grid has four cocircular sites round each vertex
+		let grid: Vec<Point> = (0..9).map(|i| p(0.5 + (i % 3) as f64, 0.5 + (i/3) as f64)).collect();
+		let diagram = voronoi(&grid, p(3.0, 3.0), p(0.0, 0.0));
+		check_voronoi(&diagram, p(0.0, 0.0), p(3.0, 3.0));
+		assert_eq!(diagram.vertices().len(), 16);
+		assert_eq!(diagram.half_edges().len(), 36);
+		assert_eq!(diagram.half_edges().iter().filter(|e| e.twin.is_some()).count(), 24);
+		assert!(diagram.cells().iter().all(|cell| cell.as_ref().unwrap().area() == 1.0));
+
+		//eight cocircular sites meet at one vertex
+		let ring: Vec<Point> = (0..8).map(|i| {
+			let angle = i as f64*std::f64::consts::PI/4.0;
+			p(angle.cos(), angle.sin())
+		}).collect();
+		let diagram = voronoi(&ring, p(-2.0, -2.0), p(2.0, 2.0));
+		check_voronoi(&diagram, p(-2.0, -2.0), p(2.0, 2.0));
+		let middle: Vec<usize> = (0..diagram.vertices().len()).filter(|&v| diagram.vertices()[v].distance(&p(0.0, 0.0)) < 1e-9).collect();
+		assert_eq!(middle.len(), 1);
+		assert_eq!(diagram.half_edges().iter().filter(|e| e.origin == middle[0]).count(), 8);
+
+		//collinear sites make strips
+		let line: Vec<Point> = (0..5).map(|i| p(i as f64, i as f64)).collect();
+		let diagram = voronoi(&line, p(-1.0, -1.0), p(5.0, 5.0));
+		check_voronoi(&diagram, p(-1.0, -1.0), p(5.0, 5.0));
+		assert!(diagram.vertices().iter().all(|v| v.x == -1.0 || v.x == 5.0 || v.y == -1.0 || v.y == 5.0));
+		let flat: Vec<Point> = (0..4).map(|i| p(i as f64, 0.0)).collect();
+		let diagram = voronoi(&flat, p(-1.0, -1.0), p(4.0, 1.0));
+		check_voronoi(&diagram, p(-1.0, -1.0), p(4.0, 1.0));
+		assert_eq!(diagram.cell(1).unwrap().area(), 2.0);
+
+		//repeats and cells outside the box are None, a lone site gets the whole box
+		let diagram = voronoi(&[p(0.5, 0.5), p(0.5, 0.5), p(9.0, 0.5)], p(0.0, 0.0), p(1.0, 1.0));
+		check_voronoi(&diagram, p(0.0, 0.0), p(1.0, 1.0));
+		assert_eq!(diagram.cell(0).unwrap().area(), 1.0);
+		assert!(diagram.cell(1).is_none() && diagram.cell(2).is_none());
+		assert_eq!(voronoi(&[p(5.0, 5.0)], p(0.0, 0.0), p(1.0, 1.0)).cell(0).unwrap().area(), 1.0);
+		assert!(voronoi(&[], p(0.0, 0.0), p(1.0, 1.0)).half_edges().is_empty());
+	}
+
+	#[test]
+	fn voronoi_random_test() {
+		let mut next = lcg(43);
+		for round in 0..40 {
+			//every other round snapped to a small grid, full of cocircular and collinear sites
+			let (n, grid) = if round % 2 == 0 {(100, 1 << 20)} else {(40, 8)};
+			let sites = random_points(&mut next, n, grid, grid as f64/12.0, -1.0);
+			let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+			let diagram = voronoi(&sites, min, max);
+			check_voronoi(&diagram, min, max);
+			for _ in 0..50 {
+				let q = Point::new((next(999) + 1) as f64/100.0, (next(999) + 1) as f64/100.0);
+				//ties may go either way
+				let nearest = sites.iter().map(|s| q.distance(s)).fold(f64::INFINITY, f64::min);
+				assert!((0..n).any(|k| q.distance(&sites[k]) < nearest + 1e-9 && diagram.cell(k).is_some_and(|cell| cell.contains_point(&q) != Location::Outside)));
+			}
+		}
+	}
+	//VORONOI TEST END
+
+	//POWER TEST START
+	fn power(c: &Circle, q: &Point) -> f64 {
+		q.distance_squared(&c.center()) - c.radius()*c.radius()
+	}
+
+	fn check_power(diagram: &PowerDiagram, circles: &[Circle], bounds: &Polygon) {
+		let total: f64 = diagram.cells().iter().map(|cell| cell.area()).sum();
+		assert!((total - bounds.area()).abs() < 1e-9*bounds.area());
+		for (i, cell) in diagram.cells().iter().enumerate() {
+			for poly in cell.polygons() {
+				for q in poly.points() {
+					//boolean operations may round a vertex just off the bounds
+					assert!(bounds.contains_point(q) != Location::Outside || bounds.rings().any(|ring| (0..ring.len()).any(|k| {
+						Segment::new(ring[k], ring[(k + 1) % ring.len()]).unwrap().distance_to_point(q) < 1e-12
+					})));
+					for c in circles {
+						assert!(power(&circles[i], q) <= power(c, q) + 1e-9);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn power_diagram_test() {
+		let p = |x: f64, y: f64| Point::new(x, y);
+		let circle = |x: f64, y: f64, r: f64| Circle::new(p(x, y), r).unwrap();
+		let square = |a: f64, b: f64| Polygon::from_vec(vec![p(a, a), p(b, a), p(b, b), p(a, b)]).unwrap();
+
+		//equal radii give the Voronoi diagram
+		let grid: Vec<Circle> = (0..9).map(|i| circle(0.5 + (i % 3) as f64, 0.5 + (i/3) as f64, 0.3)).collect();
+		let diagram = power_diagram(&grid, &square(0.0, 3.0));
+		check_power(&diagram, &grid, &square(0.0, 3.0));
+		assert!(diagram.cells().iter().all(|cell| cell.len() == 1 && (cell.area() - 1.0).abs() < 1e-12));
+
+		//the radical axis of these two is x = 2.5
+		let bounds = Polygon::from_vec(vec![p(-5.0, -1.0), p(5.0, -1.0), p(5.0, 1.0), p(-5.0, 1.0)]).unwrap();
+		let pair = [circle(0.0, 0.0, 2.0), circle(4.0, 0.0, 0.0)];
+		let diagram = power_diagram(&pair, &bounds);
+		check_power(&diagram, &pair, &bounds);
+		assert!((diagram.cell(0).area() - 15.0).abs() < 1e-12 && (diagram.cell(1).area() - 5.0).abs() < 1e-12);
+
+		//a circle well inside a bigger one, a repeat and one outside the bounds are empty
+		let circles = [circle(1.0, 1.0, 2.0), circle(1.2, 1.0, 0.5), circle(1.0, 1.0, 2.0), circle(9.0, 9.0, 0.1), circle(3.0, 3.0, 1.0)];
+		let diagram = power_diagram(&circles, &square(0.0, 4.0));
+		check_power(&diagram, &circles, &square(0.0, 4.0));
+		assert_eq!(diagram.empty_cells(), vec![1, 2, 3]);
+
+		//concave bounds with a hole, which the first cell wraps round
+		let bounds = Polygon::with_holes(
+			vec![p(0.0, 0.0), p(6.0, 0.0), p(6.0, 4.0), p(4.0, 4.0), p(4.0, 1.0), p(2.0, 1.0), p(2.0, 4.0), p(0.0, 4.0)],
+			vec![vec![p(0.5, 0.2), p(0.5, 0.6), p(5.5, 0.6), p(5.5, 0.2)]],
+		).unwrap();
+		let circles = [circle(3.0, 0.5, 1.0), circle(1.0, 3.0, 0.0), circle(5.0, 3.0, 0.5)];
+		let diagram = power_diagram(&circles, &bounds);
+		check_power(&diagram, &circles, &bounds);
+		assert_eq!(diagram.cell(0).len(), 1);
+		assert_eq!(diagram.cell(0).polygons()[0].holes().len(), 1);
+		assert!(diagram.empty_cells().is_empty());
+		assert!(power_diagram(&[], &bounds).cells().is_empty());
+	}
+
+	#[test]
+	fn power_diagram_random_test() {
+		let mut next = lcg(47);
+		let bounds = Polygon::from_vec(vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(5.0, 4.0), Point::new(0.0, 10.0)]).unwrap();
+		for _ in 0..20 {
+			let circles: Vec<Circle> = random_points(&mut next, 60, 1200, 100.0, -1.0).into_iter().map(|center| Circle::new(center, next(150) as f64/100.0).unwrap()).collect();
+			let diagram = power_diagram(&circles, &bounds);
+			check_power(&diagram, &circles, &bounds);
+			//halfway between hundredths, so never on the slanted edges of bounds
+			for _ in 0..50 {
+				let q = Point::new(next(1000) as f64/100.0 + 0.005, next(1000) as f64/100.0 + 0.005);
+				if bounds.contains_point(&q) != Location::Inside {
+					continue;
+				}
+				//points about as close to two cells may fall on either side
+				let mut powers: Vec<(f64, usize)> = circles.iter().enumerate().map(|(k, c)| (power(c, &q), k)).collect();
+				powers.sort_by(|a, b| a.partial_cmp(b).unwrap());
+				if powers[1].0 - powers[0].0 > 1e-9 {
+					assert_eq!(diagram.cell(powers[0].1).contains_point(&q), Location::Inside);
+				}
+			}
+		}
+	}
+	//POWER TEST END
 }
\ No newline at end of file
@@ -1,202 +1,402 @@
+use crate::error::GeometryError;
+use crate::hull::lexicographic;
 use crate::point::Point;
-
-const EPSILON: f64 = 0.00001;
+use crate::predicates::cross_direction;
+use crate::ray::Ray;
+use crate::scalar::{Scalar, Field, Float};
+use crate::segment::Segment;
+use crate::tolerance::Tolerance;
+use std::cmp::Ordering;
 
 //LINE
-pub struct Line {
-	p1: Point,
-	p2: Point,
-	infinite: bool,
+//Infinite line through two distinct points
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Line<T = f64> {
+	pub(crate) p1: Point<T>,
+	pub(crate) p2: Point<T>,
 }
 
-//Constructors
-impl Line {
-	//TODO Ensure any two points used to create a line ARE NOT the same
-	//from 2 point objects
-	pub fn new(p1: Point, p2: Point, infinite: bool) -> Self {
-		Self {
-			p1,
-			p2,
-			infinite,
-		}
-	}
+//Result of intersecting two segments, rays or lines
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Intersection<T = f64> {
+	None,
+	Point(Point<T>),
+	//collinear overlap, endpoints in lexicographic order
+	Segment(Point<T>, Point<T>),
+	//collinear overlap that runs forever one way: the origin, then a point it passes through
+	Ray(Point<T>, Point<T>),
+	//coincident infinite lines
+	Line,
+}
 
-	//from y = mx + b equation (TODO how to handle vertical lines?)
-	pub fn from_equation(m:f64, b:f64, p1:Point) -> Self {
-		let p2x = p1.x + 1.0;
-		let p2y = m*p2x + b;
-		let p = Point::new(p2x, p2y);
-		Self {
-			p1,
-			p2: p,
-			infinite: true,
+// Shared behaviour of Segment, Ray and Line. All three are the points p1 + t(p2 - p1)
+// and only differ in whether t stops at 0 (p1) and at 1 (p2), so every pairwise
+// combination goes through the same code
+pub trait Linear<T: Scalar> {
+	fn p1(&self) -> Point<T>;
+	fn p2(&self) -> Point<T>;
+	fn bounded_start(&self) -> bool;
+	fn bounded_end(&self) -> bool;
+
+	//Exact, endpoints are included
+	fn contains(&self, p: &Point<T>) -> bool {
+		let (a, b) = (self.p1(), self.p2());
+		if a.orientation(&b, p) != 0 {
+			return false;
 		}
+		let before_start = self.bounded_start() && behind(&a, &b, p);
+		let past_end = self.bounded_end() && behind(&b, &a, p);
+		!before_start && !past_end
 	}
-	
 
-	//from 4 floats 
-	pub fn from_coordinates(x1: f64, y1: f64, x2: f64, y2:f64, infinite: bool) -> Self {
-		let p1 = Point::new(x1,y1);
-		let p2 = Point::new(x2,y2);
-		Line::new(p1, p2, infinite)
+	//(dx, dy) from p1 to p2
+	fn direction(&self) -> (T, T) {
+		let (a, b) = (self.p1(), self.p2());
+		(b.x - a.x, b.y - a.y)
 	}
-}
 
-// Methods
-impl Line {
-	pub fn equals(&self, other: &Line) -> bool {
-		self.p1.equals(&other.p1) && self.p2.equals(&other.p2) && self.infinite == other.infinite
+	//direction turned a quarter counterclockwise
+	fn normal(&self) -> (T, T) {
+		let (dx, dy) = self.direction();
+		(-dy, dx)
 	}
 
-	pub fn length(&self) -> f64 {
-		if self.infinite {
-			return -1.0;
-		}
-		self.p1.distance(&self.p2)
+	//(a, b, c) of ax + by + c = 0 for the line through the shape.
+	//Needs no division, so it is exact for integer coordinates
+	fn general_form(&self) -> (T, T, T) {
+		let p = self.p1();
+		let (a, b) = self.normal();
+		(a.clone(), b.clone(), -(a*p.x + b*p.y))
 	}
 
+	//angle of the direction from the positive x axis in radians, in (-pi, pi]
+	fn angle(&self) -> T where T: Float {
+		let (dx, dy) = self.direction();
+		dy.atan2(dx)
+	}
 
-	pub fn get_equation(&self) -> (f64, f64) {
-		let m = (self.p2.y - self.p1.y) / (self.p2.x - self.p1.x);
-		let b = self.p2.y - m*self.p2.x;
-		(m,b)
+	//check if slopes are equal. Exact, see Scalar::cross_sign
+	fn is_parallel<L: Linear<T>>(&self, other: &L) -> bool {
+		T::cross_sign(&self.p1(), &self.p2(), &other.p1(), &other.p2()) == Ordering::Equal
 	}
 
-	//works for infinte lines and segments
-	pub fn distance_to_point(&self, p: &Point) -> f64 {
-		if p.on_line(&self) {
-			return 0.0
+	//Orientation based, so vertical lines need no special handling
+	//Touching at an endpoint counts as an intersection
+	fn intersects<L: Linear<T>>(&self, other: &L) -> bool {
+		if self.is_parallel(other) {
+			return self.p1().orientation(&self.p2(), &other.p1()) == 0 && overlap(self, other) != Intersection::None;
 		}
-		//pretty easy if infinite
-		if !self.infinite {
-			//check if its closer to an endpoint of the segment
-			let dx = self.p2.x - self.p1.x;
-			let dy = self.p2.y - self.p1.y;
+		crosses_within(self, other) && crosses_within(other, self)
+	}
 
-			let d2x = self.p1.x - p.x;
-			let d2y = self.p1.y - p.y;
+	//Whether the shapes meet is decided exactly, only a crossing point
+	//that is not one of the defining points is rounded for float coordinates
+	fn intersection<L: Linear<T>>(&self, other: &L) -> Intersection<T> where T: Field {
+		if self.is_parallel(other) {
+			if self.p1().orientation(&self.p2(), &other.p1()) != 0 {
+				return Intersection::None;
+			}
+			return overlap(self, other);
+		}
+		if !(crosses_within(self, other) && crosses_within(other, self)) {
+			return Intersection::None;
+		}
 
-			let dot = dx*d2x + dy*d2y;
-			if dot > EPSILON {
-				return (d2x*d2x + d2y*d2y).sqrt();
+		//a defining point on the other line must be the crossing, so return it as is
+		let (a, b, c, d) = (self.p1(), self.p2(), other.p1(), other.p2());
+		for (p, l1, l2) in [(&a, &c, &d), (&b, &c, &d), (&c, &a, &b), (&d, &a, &b)] {
+			if l1.orientation(l2, p) == 0 {
+				return Intersection::Point(p.clone());
 			}
+		}
 
-			let d3x = p.x - self.p2.x;
-			let d3y = p.y - self.p2.y;
+		let a1 = b.y - a.y.clone();
+		let b1 = a.x.clone() - b.x;
+		let c1 = a1.clone()*a.x + b1.clone()*a.y;
 
-			let dot2 = dx*d3x + dy*d3y;
-			if dot2 > EPSILON {
-				return (d3x*d3x + d3y*d3y).sqrt();
-			}
+		let a2 = d.y - c.y.clone();
+		let b2 = c.x.clone() - d.x;
+		let c2 = a2.clone()*c.x + b2.clone()*c.y;
+
+		let det = a1.clone()*b2.clone() - a2.clone()*b1.clone();
+		Intersection::Point(Point {
+			x: (b2*c1.clone() - b1*c2.clone())/det.clone(),
+			y: (a1*c2 - a2*c1)/det,
+		})
+	}
+
+	//closest point of the shape to p
+	fn projection(&self, p: &Point<T>) -> Point<T> where T: Field {
+		let (a, b) = (self.p1(), self.p2());
+		let dx = b.x.clone() - a.x.clone();
+		let dy = b.y.clone() - a.y.clone();
+		let dot = (p.x.clone() - a.x.clone())*dx.clone() + (p.y.clone() - a.y.clone())*dy.clone();
+		let len = dx.clone()*dx.clone() + dy.clone()*dy.clone();
 
+		if self.bounded_start() && dot <= T::zero() {
+			return a;
 		}
-		let num = f64::abs((self.p2.y - self.p1.y)*p.x - (self.p2.x - self.p1.x)*p.y + self.p2.x*self.p1.y - self.p2.y*self.p1.x);
-		let denom = self.p1.distance(&self.p2);	
-		if f64::abs(denom) > EPSILON {
-			return num/denom;
+		if self.bounded_end() && dot >= len {
+			return b;
 		}
-		//basic error information. Figure out something better soon
-		return -1.0;
+		let t = dot/len;
+		Point::new(a.x + dx*t.clone(), a.y + dy*t)
+	}
 
+	fn distance_to_point(&self, p: &Point<T>) -> T where T: Float {
+		if self.contains(p) {
+			return T::zero();
+		}
+		p.distance(&self.projection(p))
 	}
 
-	pub fn distance_to_line(&self, other: &Line) -> f64 {
+	//points within tolerance of the shape, relative to the distance between
+	//its defining points, are on it
+	fn distance_to_point_tol(&self, p: &Point<T>, tol: &Tolerance) -> T where T: Float {
+		let d = self.distance_to_point(p);
+		if tol.is_zero(d.to_f64(), self.p1().distance(&self.p2()).to_f64()) {
+			return T::zero();
+		}
+		d
+	}
 
-		//might be a more efficient way to handle this case
-		if self.intersects(&other) {
-			return 0.0;
+	fn distance_to_line<L: Linear<T>>(&self, other: &L) -> T where T: Float {
+		if self.intersects(other) {
+			return T::zero();
 		}
 
-		//distance of 2 line segments is always min of the distance
-		//from one endpoint to the other line 
-		let d1 = self.distance_to_point(&other.p1);
-		let d2 = self.distance_to_point(&other.p2);
-		let d3 = other.distance_to_point(&self.p1);
-		let d4 = other.distance_to_point(&self.p2);
+		//shapes that do not meet are closest at one of the endpoints.
+		//Two lines have none, but then they are parallel
+		let mut distances = Vec::new();
+		if other.bounded_start() {
+			distances.push(self.distance_to_point(&other.p1()));
+		}
+		if other.bounded_end() {
+			distances.push(self.distance_to_point(&other.p2()));
+		}
+		if self.bounded_start() {
+			distances.push(other.distance_to_point(&self.p1()));
+		}
+		if self.bounded_end() {
+			distances.push(other.distance_to_point(&self.p2()));
+		}
+		distances.into_iter().reduce(T::min).unwrap_or_else(|| self.distance_to_point(&other.p1()))
+	}
 
-		f64::min(d1,f64::min(d2,f64::min(d3,d4)))
+	fn distance_to_line_tol<L: Linear<T>>(&self, other: &L, tol: &Tolerance) -> T where T: Float {
+		let d = self.distance_to_line(other);
+		let scale = self.p1().distance(&self.p2()).max(other.p1().distance(&other.p2()));
+		if tol.is_zero(d.to_f64(), scale.to_f64()) {
+			return T::zero();
+		}
+		d
 	}
 
-	//check if slopes are equal
-	pub fn is_parallel(&self, other: &Line) -> bool {
-		let a1 = self.p2.y - self.p1.y;
-		let b1 = self.p1.x - self.p2.x;
+	//the relative epsilon bounds the sine of the angle between the lines
+	fn is_parallel_tol<L: Linear<T>>(&self, other: &L, tol: &Tolerance) -> bool where T: Float {
+		let scale = self.p1().distance(&self.p2())*other.p1().distance(&other.p2());
+		let cross = cross_direction(&self.p1().to_f64(), &self.p2().to_f64(), &other.p1().to_f64(), &other.p2().to_f64());
+		tol.is_zero(cross, scale.to_f64())
+	}
+}
 
-		let a2 = other.p2.y - other.p1.y;
-		let b2 = other.p1.x - other.p2.x;
+// p is collinear with from and towards, and lies past from on the side away from towards
+fn behind<T: Scalar>(from: &Point<T>, towards: &Point<T>, p: &Point<T>) -> bool {
+	lexicographic(p, from) == lexicographic(from, towards)
+}
 
+// Whether the crossing with the line through l lies within s. With o(q) the signed
+// side of q, the crossing is at t = o(p1)/(o(p1) - o(p2)), so the bounds only need signs
+fn crosses_within<T: Scalar, A: Linear<T> + ?Sized, B: Linear<T> + ?Sized>(s: &A, l: &B) -> bool {
+	let (l1, l2) = (l.p1(), l.p2());
+	let o1 = T::cross_sign(&l1, &l2, &l1, &s.p1());
+	let o2 = T::cross_sign(&l1, &l2, &l1, &s.p2());
+	let d = T::cross_sign(&l1, &l2, &s.p2(), &s.p1());
+
+	let start = !s.bounded_start() || o1 == Ordering::Equal || o1 == d;
+	let end = !s.bounded_end() || o2 == Ordering::Equal || o2 != d;
+	start && end
+}
 
-		f64::abs(a1*b2 - a2*b1) < EPSILON
+// Ends of the shape in lexicographic order, None where it runs forever
+fn bounds<T: Scalar, L: Linear<T> + ?Sized>(s: &L) -> (Option<Point<T>>, Option<Point<T>>) {
+	let (a, b) = (s.p1(), s.p2());
+	if lexicographic(&a, &b) == Ordering::Less {
+		(s.bounded_start().then_some(a), s.bounded_end().then_some(b))
+	} else {
+		(s.bounded_end().then_some(b), s.bounded_start().then_some(a))
 	}
+}
 
-	// This needs to be retested for infinte/infinite and infinite/segment pairs in the parallel case
-	pub fn intersects(&self, other: &Line) -> bool {
-		if self.infinite && other.infinite && !self.is_parallel(&other) {
-			return true;
+// Shared part of two collinear shapes
+fn overlap<T: Scalar, A: Linear<T> + ?Sized, B: Linear<T> + ?Sized>(a: &A, b: &B) -> Intersection<T> {
+	let (a_lo, a_hi) = bounds(a);
+	let (b_lo, b_hi) = bounds(b);
+	let lo = match (a_lo, b_lo) {
+		(Some(p), Some(q)) => Some(if lexicographic(&p, &q) == Ordering::Less {q} else {p}),
+		(p, q) => p.or(q),
+	};
+	let hi = match (a_hi, b_hi) {
+		(Some(p), Some(q)) => Some(if lexicographic(&p, &q) == Ordering::Less {p} else {q}),
+		(p, q) => p.or(q),
+	};
+
+	//step along the line in lexicographic order, used to name a second point of a ray
+	let (p, q) = (a.p1(), a.p2());
+	let (dx, dy) = if lexicographic(&p, &q) == Ordering::Less {(q.x - p.x, q.y - p.y)} else {(p.x - q.x, p.y - q.y)};
+
+	match (lo, hi) {
+		(None, None) => Intersection::Line,
+		(Some(l), None) => {
+			let next = Point::new(l.x.clone() + dx, l.y.clone() + dy);
+			Intersection::Ray(l, next)
+		},
+		(None, Some(h)) => {
+			let next = Point::new(h.x.clone() - dx, h.y.clone() - dy);
+			Intersection::Ray(h, next)
+		},
+		(Some(l), Some(h)) => match lexicographic(&l, &h) {
+			Ordering::Less => Intersection::Segment(l, h),
+			Ordering::Equal => Intersection::Point(l),
+			Ordering::Greater => Intersection::None,
+		},
+	}
+}
+
+//Constructors
+impl<T: Scalar> Line<T> {
+	//from 2 point objects. The points must differ
+	pub fn new(p1: Point<T>, p2: Point<T>) -> Result<Self, GeometryError> {
+		if p1.equals(&p2) {
+			return Err(GeometryError::DegenerateLine);
 		}
-		
-		if self.infinite {
-			//equation of line
-			let m = (self.p2.y - self.p1.y) / (self.p2.x - self.p1.x);
-			let b = self.p2.y - m*self.p2.x;
+		Ok(Self {
+			p1,
+			p2,
+		})
+	}
 
-			let d1 = other.p1.y - m*other.p1.x - b;
-			let d2 = other.p2.y - m*other.p2.x - b;
+	//from y = mx + b equation. Vertical lines have no slope, see vertical
+	pub fn from_equation(m:T, b:T) -> Self {
+		Self {
+			p1: Point::new(T::zero(), b.clone()),
+			p2: Point::new(T::one(), m + b),
+		}
+	}
 
-			return (d1 < 0.0 && d2 > 0.0) || (d1 > 0.0 && d2 < 0.0); 
+	//x = c
+	pub fn vertical(x: T) -> Self {
+		Self {
+			p1: Point::new(x.clone(), T::zero()),
+			p2: Point::new(x, T::one()),
+		}
+	}
 
+	//y = c
+	pub fn horizontal(y: T) -> Self {
+		Self {
+			p1: Point::new(T::zero(), y.clone()),
+			p2: Point::new(T::one(), y),
 		}
-		else if other.infinite {
-			let m = (other.p2.y - other.p1.y) / (other.p2.x - other.p1.x);
-			let b = other.p2.y - m*other.p2.x;
+	}
 
-			let d1 = self.p1.y - m*self.p1.x - b;
-			let d2 = self.p2.y - m*self.p2.x - b; 
+	//through p, running along (dx, dy)
+	pub fn from_point_direction(p: Point<T>, dx: T, dy: T) -> Result<Self, GeometryError> {
+		let q = Point::new(p.x.clone() + dx, p.y.clone() + dy);
+		Line::new(p, q)
+	}
 
-			return (d1 < 0.0 && d2 > 0.0) || (d1 > 0.0 && d2 < 0.0); 
-		}
+	//through p, at right angles to (nx, ny)
+	pub fn from_point_normal(p: Point<T>, nx: T, ny: T) -> Result<Self, GeometryError> {
+		Line::from_point_direction(p, ny, -nx)
+	}
 
-		//its possible line segments are parallel, collinear, and dont overlap. Check that case
-		if self.is_parallel(&other) {
-			if self.p1.on_line(&other) || self.p2.on_line(&other) || other.p1.on_line(&self) || other.p2.on_line(&self) {
-				return true
-			} 
-			return false
-		}
+	//from 4 coordinates
+	pub fn from_coordinates(x1: T, y1: T, x2: T, y2:T) -> Result<Self, GeometryError> {
+		let p1 = Point::new(x1,y1);
+		let p2 = Point::new(x2,y2);
+		Line::new(p1, p2)
+	}
+}
 
-		let o1 = self.p1.orientation(&self.p2, &other.p1);
-		let o2 = self.p1.orientation(&self.p2, &other.p2);
-		let o3 = other.p1.orientation(&other.p2, &self.p1);
-		let o4 = other.p1.orientation(&other.p2, &self.p2);
+// Methods
+impl<T: Scalar> Line<T> {
+	pub fn equals(&self, other: &Line<T>) -> bool {
+		self.p1.equals(&other.p1) && self.p2.equals(&other.p2)
+	}
 
-		o1 != o2 && o3 != o4
+	//segment between the two points the line was built from
+	pub fn to_segment(&self) -> Segment<T> {
+		Segment { p1: self.p1.clone(), p2: self.p2.clone() }
+	}
 
+	//ray from the first point through the second
+	pub fn to_ray(&self) -> Ray<T> {
+		Ray { origin: self.p1.clone(), through: self.p2.clone() }
 	}
+}
 
-	//Intersection of lines. Untested atm
-	pub fn intersection(&self, other: &Line) -> Point {
-		//do a parallel check. Would just call the function but these values are needed later
-		let a1 = self.p2.y - self.p1.y;
-		let b1 = self.p1.x - self.p2.x;
-		let c1 = a1*self.p1.x + b1*self.p1.y;
+impl<T: Field> Line<T> {
+	//ax + by + c = 0. a and b can not both be zero
+	pub fn from_general(a: T, b: T, c: T) -> Result<Self, GeometryError> {
+		let p = if b != T::zero() {
+			Point::new(T::zero(), -c/b.clone())
+		} else if a != T::zero() {
+			Point::new(-c/a.clone(), T::zero())
+		} else {
+			return Err(GeometryError::DegenerateLine);
+		};
+		Line::from_point_direction(p, b, -a)
+	}
 
-		let a2 = other.p2.y - other.p1.y;
-		let b2 = other.p1.x - other.p2.x;
-		let c2 = a2*other.p1.x + b2*other.p1.y;
+	//the points as far from a as from b, running with a on the left. a and b must differ
+	pub fn bisector(a: &Point<T>, b: &Point<T>) -> Result<Self, GeometryError> {
+		if a.equals(b) {
+			return Err(GeometryError::DegenerateLine);
+		}
+		let two = T::from_i32(2);
+		Line::from_point_direction(Point::new((a.x.clone() + b.x.clone())/two.clone(), (a.y.clone() + b.y.clone())/two), a.y.clone() - b.y.clone(), b.x.clone() - a.x.clone())
+	}
 
-		let d = a1*b2 - a2*b1;
-		if f64::abs(d) < EPSILON {
-			//Throw an error here, lines were parallel
-			return Point {
-				x: f64::MAX,
-				y: f64::MAX,
-			}
+	//(m, b) of y = mx + b. None for vertical lines, which have no slope
+	pub fn get_equation(&self) -> Option<(T, T)> {
+		let (a, b, c) = self.general_form();
+		if b == T::zero() {
+			return None;
 		}
-		Point {
-			x: (b2*c1 - b1*c2)/d,
-			y: (a1*c2 - a2*c1)/d,
+		Some((-a/b.clone(), -c/b))
+	}
+
+	//where the line crosses a horizontal line at y. None if it is horizontal itself
+	pub fn x_at(&self, y: T) -> Option<T> {
+		let (a, b, c) = self.general_form();
+		if a == T::zero() {
+			return None;
 		}
+		Some(-(b*y + c)/a)
 	}
 
+	//where the line crosses a vertical line at x. None if it is vertical itself
+	pub fn y_at(&self, x: T) -> Option<T> {
+		let (a, b, c) = self.general_form();
+		if b == T::zero() {
+			return None;
+		}
+		Some(-(a*x + c)/b)
+	}
+}
+
+impl<T: Float> Line<T> {
+	//through p at the given angle from the positive x axis, in radians.
+	//Fails if p is so large that a unit step rounds back onto it
+	pub fn from_angle(p: Point<T>, angle: T) -> Result<Self, GeometryError> {
+		Line::from_point_direction(p, angle.cos(), angle.sin())
+	}
+}
 
+impl<T: Scalar> Linear<T> for Line<T> {
+	fn p1(&self) -> Point<T> {self.p1.clone()}
+	fn p2(&self) -> Point<T> {self.p2.clone()}
+	fn bounded_start(&self) -> bool {false}
+	fn bounded_end(&self) -> bool {false}
 }
@@ -0,0 +1,106 @@
+use crate::boolean::{boolean, BooleanOp};
+use crate::point::Point;
+use crate::polygon::{Location, Polygon};
+use crate::scalar::{Scalar, Field, Float};
+
+//MULTIPOLYGON
+//Any number of polygons that do not overlap, though they may touch
+#[derive(Clone, Debug)]
+pub struct MultiPolygon<T = f64> {
+	pub(crate) polygons: Vec<Polygon<T>>,
+}
+
+//Constructor
+impl<T: Scalar> MultiPolygon<T> {
+	pub fn new(polygons: Vec<Polygon<T>>) -> Self {
+		MultiPolygon {
+			polygons,
+		}
+	}
+}
+
+impl<T: Scalar> From<Polygon<T>> for MultiPolygon<T> {
+	fn from(polygon: Polygon<T>) -> Self {
+		MultiPolygon::new(vec![polygon])
+	}
+}
+
+//Methods
+impl<T: Scalar> MultiPolygon<T> {
+	pub fn polygons(&self) -> &[Polygon<T>] {
+		&self.polygons
+	}
+
+	pub fn len(&self) -> usize {
+		self.polygons.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.polygons.is_empty()
+	}
+
+	//twice the total area, holes excluded
+	pub fn double_area(&self) -> T {
+		self.polygons.iter().fold(T::zero(), |sum, poly| sum + poly.double_area())
+	}
+
+	//on the boundary of any polygon wins over inside another, which only
+	//happens where two polygons touch
+	pub fn contains_point(&self, p: &Point<T>) -> Location {
+		let mut location = Location::Outside;
+		for poly in &self.polygons {
+			match poly.contains_point(p) {
+				Location::OnBoundary => return Location::OnBoundary,
+				Location::Inside => location = Location::Inside,
+				Location::Outside => {}
+			}
+		}
+		location
+	}
+}
+
+impl<T: Field> MultiPolygon<T> {
+	pub fn area(&self) -> T {
+		self.double_area()/T::from_i32(2)
+	}
+
+	//centroid of all the pieces weighted by their areas. None if there is no area
+	pub fn centroid(&self) -> Option<Point<T>> {
+		let (mut area, mut x, mut y) = (T::zero(), T::zero(), T::zero());
+		for poly in &self.polygons {
+			if let Some(c) = poly.centroid() {
+				let a = poly.area();
+				area = area + a.clone();
+				x = x + c.x*a.clone();
+				y = y + c.y*a;
+			}
+		}
+		if area == T::zero() {
+			return None;
+		}
+		Some(Point::new(x/area.clone(), y/area))
+	}
+
+	//Boolean operations, as for Polygon
+	pub fn union(&self, other: &MultiPolygon<T>) -> MultiPolygon<T> {
+		boolean(&self.polygons, &other.polygons, BooleanOp::Union)
+	}
+
+	pub fn intersection(&self, other: &MultiPolygon<T>) -> MultiPolygon<T> {
+		boolean(&self.polygons, &other.polygons, BooleanOp::Intersection)
+	}
+
+	pub fn difference(&self, other: &MultiPolygon<T>) -> MultiPolygon<T> {
+		boolean(&self.polygons, &other.polygons, BooleanOp::Difference)
+	}
+
+	pub fn xor(&self, other: &MultiPolygon<T>) -> MultiPolygon<T> {
+		boolean(&self.polygons, &other.polygons, BooleanOp::Xor)
+	}
+}
+
+impl<T: Float> MultiPolygon<T> {
+	pub fn perimeter(&self) -> T {
+		self.polygons.iter().fold(T::zero(), |sum, poly| sum + poly.perimeter())
+	}
+}
@@ -1,18 +1,22 @@
-use crate::line::Line;
+use crate::error::GeometryError;
+use crate::line::Linear;
+use crate::hull::{convex_hull_with, HullAlgorithm};
+use crate::predicates::orient2d;
+use crate::scalar::{Scalar, Float};
+use crate::tolerance::Tolerance;
+use std::cmp::Ordering;
 use std::vec::Vec;
 
-const EPSILON: f64 = 0.00001;
-
 //POINT
-#[derive(Copy, Clone)]
-pub struct Point {
-	pub x: f64,
-	pub y: f64,
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Point<T = f64> {
+	pub x: T,
+	pub y: T,
 }
 
 //Constructor
-impl Point {
-	pub fn new(x: f64, y:f64) -> Self {
+impl<T: Scalar> Point<T> {
+	pub fn new(x: T, y: T) -> Self {
 		Self {
 			x,
 			y, 
@@ -21,116 +25,111 @@ impl Point {
 }
 
 //Methods
-impl Point {
-	pub fn equals(&self, other: &Point) -> bool {
+impl<T: Scalar> Point<T> {
+	pub fn equals(&self, other: &Point<T>) -> bool {
 		self.x == other.x && self.y == other.y
 	}
 
-	pub fn distance(&self, other: &Point) -> f64{
-		((self.x - other.x)*(self.x-other.x) + (self.y - other.y)*(self.y - other.y)).sqrt()
-	}
-
-	pub fn distance_squared(&self, other: &Point) -> f64{
-		(self.x - other.x)*(self.x-other.x) + (self.y - other.y)*(self.y - other.y)
-	}
-
-	pub fn distance_to_line(&self, other: &Line) -> f64 {
-		other.distance_to_point(&self)
+	pub fn distance_squared(&self, other: &Point<T>) -> T {
+		(self.x.clone() - other.x.clone())*(self.x.clone()-other.x.clone()) + (self.y.clone() - other.y.clone())*(self.y.clone() - other.y.clone())
 	}
 
-	pub fn collinear(&self, p1: &Point, p2: &Point) -> bool {
-		self.triangle_area(p1, p2) < EPSILON
+	pub fn to_f64(&self) -> Point<f64> {
+		Point::new(self.x.to_f64(), self.y.to_f64())
 	}
 
-	pub(crate) fn triangle_area(&self, p1: &Point, p2: &Point) -> f64 {
-		f64::abs(0.5*(self.x - p1.x)*(p1.y - p2.y) - (p1.x-p2.x)*(self.y-p1.y))
+	//exact for every coordinate type
+	pub fn collinear(&self, p1: &Point<T>, p2: &Point<T>) -> bool {
+		self.orientation(p1, p2) == 0
 	}
 
 	//helper function to find orientation of 3 points (collinear, clockwise, or counterclockwise)
-	pub fn orientation(&self, p1: &Point, p2: &Point) -> i32 {
-		let o = (p1.y - self.y)*(p2.x - p1.x) - (p1.x - self.x)*(p2.y - p1.y);
-		
-		if f64::abs(o) < EPSILON {
-			return 0;
+	//0: collinear - 1: clockwise - 2: counterclockwise
+	//The sign is exact for every input, see predicates::orient2d and Scalar::cross_sign
+	pub fn orientation(&self, p1: &Point<T>, p2: &Point<T>) -> i32 {
+		match T::cross_sign(self, p1, self, p2) {
+			Ordering::Equal => 0,
+			Ordering::Less => 1,
+			Ordering::Greater => 2,
 		}
-
-		return if o > 0.0 {1} else {2}
 	}
 
-	pub fn on_line(&self, line:&Line) -> bool {
-		let l1 = Line::new(*self, line.p1, false);
-		let l2 = Line::new(*self, line.p2, false);
-		let p1 = Point::new(&self.x - line.p1.x, &self.y - line.p1.y);
-		let p2 = Point::new(&self.x - line.p2.x, &self.y - line.p2.y);
-		l1.is_parallel(&l2) && p1.x*p2.y + p2.x*p1.y < 0.0   
-	} 
+	//true if the point lies on the segment, ray or line. Endpoints are included
+	pub fn on_line<L: Linear<T>>(&self, line: &L) -> bool {
+		line.contains(self)
+	}
 }
 
-// Returns a vector of points in convex hull
-// This is an implementation of Jarvis's Algorithm, 
-// Will be replaced with something quicker soon 
-// Currently this is O(m*n) where n is # of points and m is # of points in the hull
-pub fn convex_hull(points: &Vec<Point>) -> Vec<Point>{
-	let n = points.len();
-
-	if n <= 3 {
-		return points.to_vec();
+impl<T: Float> Point<T> {
+	pub fn distance(&self, other: &Point<T>) -> T {
+		self.distance_squared(other).sqrt()
 	}
 
-	let mut hull = Vec::new();
+	pub fn distance_to_line<L: Linear<T>>(&self, other: &L) -> T {
+		other.distance_to_point(self)
+	}
 
-	let l = leftmost_index(points);
+	pub fn distance_to_line_tol<L: Linear<T>>(&self, other: &L, tol: &Tolerance) -> T {
+		other.distance_to_point_tol(self, tol)
+	}
 
-	let mut p = l;
-	let mut q = 0;
+	pub fn equals_tol(&self, other: &Point<T>, tol: &Tolerance) -> bool {
+		tol.approx_eq(self.x.to_f64(), other.x.to_f64()) && tol.approx_eq(self.y.to_f64(), other.y.to_f64())
+	}
 
-	loop {
-		hull.push(points[p]);
-		q = (p+1)%n;
+	//same as orientation, but nearly collinear points count as collinear.
+	//The relative epsilon bounds the sine of the angle at self
+	pub fn orientation_tol(&self, p1: &Point<T>, p2: &Point<T>, tol: &Tolerance) -> i32 {
+		let (a, b, c) = (self.to_f64(), p1.to_f64(), p2.to_f64());
+		let o = orient2d(&a, &b, &c);
 
-		for i in 0..n {
-			if points[p].orientation(&points[i], &points[q]) == 2 {
-				q = i;
-			}
+		if tol.is_zero(o, a.distance(&b)*a.distance(&c)) {
+			return 0;
 		}
 
-		p = q;
+		if o < 0.0 {1} else {2}
+	}
 
-		if p == l {
-			break;
-		}
+	pub fn collinear_tol(&self, p1: &Point<T>, p2: &Point<T>, tol: &Tolerance) -> bool {
+		self.orientation_tol(p1, p2, tol) == 0
+	}
+
+	//within tolerance of the line, relative to the distance between its points
+	pub fn on_line_tol<L: Linear<T>>(&self, line: &L, tol: &Tolerance) -> bool {
+		self.on_line(line) || line.distance_to_point_tol(self, tol) == T::zero()
 	}
-	hull
+}
+
+// Returns a vector of points in convex hull, counterclockwise and starting
+// from the leftmost point. Uses Andrew's monotone chain, O(n log n).
+// See hull::convex_hull_with to pick a different algorithm
+pub fn convex_hull<T: Scalar>(points: &[Point<T>]) -> Vec<Point<T>>{
+	convex_hull_with(points, HullAlgorithm::MonotoneChain)
 }
 
 
 //Helper function for convex hull
-pub(crate) fn leftmost_index(points: &Vec<Point>) -> usize {
+pub(crate) fn leftmost_index<T: Scalar>(points: &[Point<T>]) -> usize {
 	let mut m = 0;
 	for i in 1..points.len() {
-		if points[i].x < points[m].x {
+		if points[i].x < points[m].x || (points[i].x == points[m].x && points[i].y > points[m].y) {
 			m = i
 		}
-		else if points[i].x == points[m].x {
-			if points[i].y > points[m].y {
-				m = i
-			}
-		}
 	}
 	m
 }
 
 
 
-pub(crate) fn closest_brute_force(points: Vec<Point>, n:usize) -> (Point, Point, f64){
-	let mut min = f64::MAX;
+pub(crate) fn closest_brute_force<T: Float>(points: Vec<Point<T>>, n:usize) -> (Point<T>, Point<T>, T){
 	let mut p1 = points[0];
 	let mut p2 = points[1];
+	let mut min = p1.distance_squared(&p2);
 
 	//check all points against all other points and return minimum
 	for i in 0..n {
 		for j in i+1..n {
-			let mut d = points[i].distance_squared(&points[j]); 
+			let d = points[i].distance_squared(&points[j]); 
 			if d < min {
 				min = d;
 				p1 = points[i];
@@ -141,7 +140,7 @@ pub(crate) fn closest_brute_force(points: Vec<Point>, n:usize) -> (Point, Point,
 	(p1, p2, min)
 }
 
-pub(crate) fn closest_strip(points: Vec<Point>, p1: &Point, p2:&Point, n:usize, d:f64) -> (Point, Point, f64) {
+pub(crate) fn closest_strip<T: Float>(points: Vec<Point<T>>, p1: &Point<T>, p2:&Point<T>, n:usize, d:T) -> (Point<T>, Point<T>, T) {
 	let mut min = d;
 	let mut p1r = *p1;
 	let mut p2r = *p2;
@@ -154,10 +153,14 @@ pub(crate) fn closest_strip(points: Vec<Point>, p1: &Point, p2:&Point, n:usize,
 	//as it only runs up to 6n times. 
 	for i in 0..n {
 		let mut j = i+1;
-		while j < n && strip[j].y - strip[i].y < min {
-			min = strip[i].distance_squared(&strip[j]);
-			p1r = strip[i];
-			p2r = strip[j];
+		//min is a squared distance, so compare against the squared y gap
+		while j < n && (strip[j].y - strip[i].y)*(strip[j].y - strip[i].y) < min {
+			let d = strip[i].distance_squared(&strip[j]);
+			if d < min {
+				min = d;
+				p1r = strip[i];
+				p2r = strip[j];
+			}
 			j+=1;
 		}
 
@@ -165,7 +168,7 @@ pub(crate) fn closest_strip(points: Vec<Point>, p1: &Point, p2:&Point, n:usize,
 	(p1r, p2r, min)
 }
 
-pub(crate) fn closest_util(points: Vec<Point>, n:usize) -> (Point, Point, f64) { 
+pub(crate) fn closest_util<T: Float>(points: Vec<Point<T>>, n:usize) -> (Point<T>, Point<T>, T) { 
 	//base case
 	if n <= 3 {
 		return closest_brute_force(points, n);
@@ -184,9 +187,9 @@ pub(crate) fn closest_util(points: Vec<Point>, n:usize) -> (Point, Point, f64) {
 
 	//collect eligible points close to the middle line
 	let mut strip = Vec::new();
-	for i in 0..n {
-		if f64::abs(points[i].x - p.x) < d {
-			strip.push(points[i]);
+	for q in &points {
+		if (q.x - p.x)*(q.x - p.x) < d {
+			strip.push(*q);
 		}
 	}
 
@@ -201,9 +204,13 @@ pub(crate) fn closest_util(points: Vec<Point>, n:usize) -> (Point, Point, f64) {
 	(ps1,ps2,d3)
 }
 
-pub fn closest_pair(points: &Vec<Point>) -> (Point, Point, f64) {
+pub fn closest_pair<T: Float>(points: &[Point<T>]) -> Result<(Point<T>, Point<T>, T), GeometryError> {
+	if points.len() < 2 {
+		return Err(GeometryError::TooFewPoints { needed: 2, found: points.len() });
+	}
+
 	//sort vector by x value
-	let mut sortable = points.clone();
+	let mut sortable = points.to_vec();
 	sortable.sort_unstable_by(|a,b| a.x.partial_cmp(&b.x).unwrap());
 
 	//get points and distance
@@ -211,5 +218,5 @@ pub fn closest_pair(points: &Vec<Point>) -> (Point, Point, f64) {
 
 	//all distance calculations within the algorithm use distance_squared for more speed/precision
 	//so take sqrt here on the returned minimum
-	(p1,p2,f64::sqrt(d))
+	Ok((p1,p2,d.sqrt()))
 }
@@ -1,53 +1,558 @@
+use crate::error::GeometryError;
+use crate::boolean::{boolean, BooleanOp};
+use crate::clip::{sutherland_hodgman, weiler_atherton};
+use crate::hull::lexicographic;
+use crate::multipolygon::MultiPolygon;
 use crate::point::Point;
+use crate::scalar::{Scalar, Field, Float};
+use crate::segment::Segment;
+use crate::sweep::{first_intersection, subdivide};
+use crate::triangulate::{triangulate_with, TriangulationAlgorithm};
+use std::collections::HashMap;
 use std::vec::Vec;
 
-const EPSILON: f64 = 0.00001;
-
 //Struct
-pub struct Polygon {
-	points: Vec<Point>,
+#[derive(Clone, Debug)]
+pub struct Polygon<T = f64> {
+	//the exterior ring
+	pub(crate) points: Vec<Point<T>>,
 	//true: clockwise - false: counterclockwise
-	orientation: bool,
+	pub(crate) orientation: bool,
+	//interior rings, each cut out of the exterior
+	pub(crate) holes: Vec<Vec<Point<T>>>,
+}
+
+//Where a point is relative to a polygon
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Location {
+	Inside,
+	Outside,
+	OnBoundary,
+}
+
+//How to decide which parts of a self-intersecting polygon are inside.
+//Both agree for simple polygons
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ContainmentRule {
+	//inside if a ray from the point crosses the boundary an odd number of times
+	CrossingNumber,
+	//inside if the boundary winds around the point a nonzero number of times
+	#[default]
+	WindingNumber,
 }
 
 //Constructors
-impl Polygon {
-	// This assumes: 
-	//	1. There are 3+ points in the vector
-	//	2. The first 3 points are not collinear
-	// Should handle cases where that is not guaranteed
-	pub fn from_vec(points: Vec<Point>) -> Self {
-		let o = points[0].orientation(&points[1], &points[2]);
-
-		Polygon {
+impl<T: Scalar> Polygon<T> {
+	// Needs 3+ points. The ring is not checked otherwise, so self-intersecting
+	// outlines are allowed. See validated
+	pub fn from_vec(points: Vec<Point<T>>) -> Result<Self, GeometryError> {
+		if points.len() < 3 {
+			return Err(GeometryError::TooFewPoints { needed: 3, found: points.len() });
+		}
+		let mut poly = Polygon {
 			points,
-			orientation: o==1,
+			orientation: false,
+			holes: Vec::new(),
+		};
+		poly.orientation = poly.signed_double_area() < T::zero();
+		Ok(poly)
+	}
+
+	// Exterior ring and any number of holes, each needing 3+ points
+	pub fn with_holes(points: Vec<Point<T>>, holes: Vec<Vec<Point<T>>>) -> Result<Self, GeometryError> {
+		if let Some(hole) = holes.iter().find(|hole| hole.len() < 3) {
+			return Err(GeometryError::TooFewPoints { needed: 3, found: hole.len() });
 		}
+		let mut poly = Polygon::from_vec(points)?;
+		poly.holes = holes;
+		Ok(poly)
 	}
 
+	// Only accepts simple polygons, see validate
+	pub fn validated(points: Vec<Point<T>>) -> Result<Self, GeometryError> {
+		let poly = Polygon::from_vec(points)?;
+		poly.validate()?;
+		Ok(poly)
+	}
 }
 
 //Methods
-impl Polygon {
-	//find the area of any regular polygon
-	pub fn area(&self) -> f64{
+impl<T: Scalar> Polygon<T> {
+	//Checks that every ring is simple: no zero length edges, no repeated
+	//vertices, not flat, and no edges meeting other than neighbours at their
+	//shared vertex. Rings may touch each other at single points but not cross
+	//or run along each other, and every hole must lie inside the exterior and
+	//outside the other holes. Indices count through vertices. O(n log n + m*n)
+	//for m vertices in holes, exact for every coordinate type
+	pub fn validate(&self) -> Result<(), GeometryError> {
+		//where each ring starts among the vertices, and one past the last
+		let mut starts = vec![0];
+		for ring in self.rings() {
+			starts.push(starts[starts.len() - 1] + ring.len());
+		}
+		let ring_of = |i: usize| starts.partition_point(|&start| start <= i) - 1;
+		let next = |i: usize| if i + 1 == starts[ring_of(i) + 1] {starts[ring_of(i)]} else {i + 1};
+		let vertices: Vec<&Point<T>> = self.vertices().collect();
+
+		for i in 0..vertices.len() {
+			if vertices[i].equals(vertices[next(i)]) {
+				return Err(GeometryError::ZeroLengthEdge { index: i });
+			}
+		}
+
+		let mut seen = HashMap::new();
+		for (i, p) in vertices.iter().enumerate() {
+			if let Some(first) = seen.insert((p.x.key(), p.y.key(), ring_of(i)), i) {
+				return Err(GeometryError::DuplicateVertex { first, second: i });
+			}
+		}
+
+		for ring in self.rings() {
+			let (a, b) = (&ring[0], &ring[1]);
+			if ring.iter().all(|p| a.orientation(b, p) == 0) {
+				return Err(GeometryError::DegenerateRing);
+			}
+		}
+
+		//edges are numbered like the vertices they start from
+		let segments: Vec<Segment<T>> = (0..vertices.len()).map(|i| Segment { p1: vertices[i].clone(), p2: vertices[next(i)].clone() }).collect();
+		let allowed = |i: usize, j: usize| ring_of(i) != ring_of(j) || j == next(i) || i == next(j);
+		if let Some((first, second)) = first_intersection(&segments, &allowed) {
+			return Err(GeometryError::SelfIntersection { first, second });
+		}
+
+		//rings now only meet at single points, so a hole is in the right place
+		//if its vertices and edge midpoints off each other ring are all on the
+		//right side of it. Coordinates are doubled to keep the midpoints exact
+		let sum = |a: &Point<T>, b: &Point<T>| Point::new(a.x.clone() + b.x.clone(), a.y.clone() + b.y.clone());
+		let doubled: Vec<Vec<Point<T>>> = self.rings().map(|ring| ring.iter().map(|p| sum(p, p)).collect()).collect();
+		let rule = ContainmentRule::default();
+		for (h, hole) in self.holes.iter().enumerate() {
+			let probes: Vec<Point<T>> = edges(hole).flat_map(|(a, b)| [sum(a, a), sum(a, b)]).collect();
+			for (r, ring) in doubled.iter().enumerate().filter(|&(r, _)| r != h + 1) {
+				let mut off = probes.iter().filter(|q| !edges(ring).any(|(a, b)| on_edge(a, b, q))).peekable();
+				if off.peek().is_none() || off.any(|q| inside_ring(ring, q, rule) != (r == 0)) {
+					return Err(GeometryError::MisplacedHole { hole: h });
+				}
+			}
+		}
+		Ok(())
+	}
+
+	//the exterior ring
+	pub fn points(&self) -> &[Point<T>] {
+		&self.points
+	}
+
+	pub fn holes(&self) -> &[Vec<Point<T>>] {
+		&self.holes
+	}
+
+	//every vertex, the exterior ring first and then each hole. Triangulations index into this
+	pub fn vertices(&self) -> impl Iterator<Item = &Point<T>> {
+		self.rings().flatten()
+	}
+
+	//counterclockwise index triples into vertices, see triangulate::triangulate_with
+	pub fn triangulate(&self) -> Vec<[usize; 3]> {
+		triangulate_with(self, TriangulationAlgorithm::default())
+	}
+
+	//exterior ring first, then the holes
+	pub(crate) fn rings(&self) -> impl Iterator<Item = &[Point<T>]> {
+		std::iter::once(self.points.as_slice()).chain(self.holes.iter().map(|hole| hole.as_slice()))
+	}
+
+	//true if the vertices run clockwise, from the sign of the area
+	pub fn is_clockwise(&self) -> bool {
+		self.orientation
+	}
+
+	//twice the area inside the exterior ring, negative if the points are clockwise
+	pub(crate) fn signed_double_area(&self) -> T {
+		shoelace(&self.points)
+	}
+
+	//twice the area, minus the holes. Needs no division, so it is exact for integer coordinates
+	pub fn double_area(&self) -> T {
+		//if points were clockwise area will be negative, and holes may run either way
+		let mut area = self.signed_double_area().abs();
+		for hole in &self.holes {
+			area = area - shoelace(hole).abs();
+		}
+		area
+	}
+
+	//exact for every coordinate type, see Scalar::cross_sign
+	pub fn contains_point(&self, p: &Point<T>) -> Location {
+		self.contains_point_with(p, ContainmentRule::default())
+	}
+
+	//inside the exterior and not inside any hole, each judged by the rule
+	pub fn contains_point_with(&self, p: &Point<T>, rule: ContainmentRule) -> Location {
+		if self.edges().any(|(a, b)| on_edge(a, b, p)) {
+			return Location::OnBoundary;
+		}
+		if !inside_ring(&self.points, p, rule) {
+			return Location::Outside;
+		}
+		self.hole_location(p, rule).unwrap_or(Location::Inside)
+	}
+
+	//where p is if it is on or inside a hole
+	fn hole_location(&self, p: &Point<T>, rule: ContainmentRule) -> Option<Location> {
+		if self.holes.iter().any(|hole| edges(hole).any(|(a, b)| on_edge(a, b, p))) {
+			return Some(Location::OnBoundary);
+		}
+		if self.holes.iter().any(|hole| inside_ring(hole, p, rule)) {
+			return Some(Location::Outside);
+		}
+		None
+	}
+
+	//times a ray from p to the right crosses the exterior ring
+	pub fn crossing_number(&self, p: &Point<T>) -> usize {
+		crossing_number(&self.points, p)
+	}
+
+	//times the exterior ring winds counterclockwise around p, negative for
+	//clockwise. Only meaningful for points off the ring
+	pub fn winding_number(&self, p: &Point<T>) -> i32 {
+		winding_number(&self.points, p)
+	}
+
+	//O(log n) test for convex polygons by binary search over the fan of
+	//triangles at the first vertex, plus the general test for each hole.
+	//Gives wrong answers for other polygons
+	pub fn contains_point_convex(&self, p: &Point<T>) -> Location {
 		let n = self.points.len();
-		let mut xs = Vec::new();
-		let mut ys = Vec::new();
-		
-		for p in &self.points {
-			xs.push(p.x);
-			ys.push(p.y);
+		//walk the vertices counterclockwise whichever way they were given
+		let v = |i: usize| if self.orientation {&self.points[(n - i) % n]} else {&self.points[i]};
+		let o = v(0);
+		if p.equals(o) {
+			return Location::OnBoundary;
+		}
+
+		//q is on the line from o to a, on the same side of o as a
+		let same_way = |a: &Point<T>, q: &Point<T>| o.orientation(a, q) == 0 && lexicographic(o, a) == lexicographic(o, q);
+		//end of the run of vertices in line with the first edge, and start of the one in line with the last
+		let first_run = || {
+			let (mut lo, mut hi) = (1, n - 1);
+			while hi - lo > 1 {
+				let mid = (lo + hi)/2;
+				if same_way(v(1), v(mid)) {lo = mid} else {hi = mid}
+			}
+			lo
+		};
+		let last_run = || {
+			let (mut lo, mut hi) = (1, n - 1);
+			while hi - lo > 1 {
+				let mid = (lo + hi)/2;
+				if same_way(v(n - 1), v(mid)) {hi = mid} else {lo = mid}
+			}
+			hi
+		};
+
+		//outside the wedge between the first and last edge. On either edge line,
+		//p can only be on the run of collinear vertices that starts at o
+		let on_run = |end: usize| if on_edge(o, v(end), p) {Location::OnBoundary} else {Location::Outside};
+		if same_way(v(1), p) {
+			return on_run(first_run());
+		}
+		if same_way(v(n - 1), p) {
+			return on_run(last_run());
+		}
+		if o.orientation(v(1), p) != 2 || o.orientation(v(n - 1), p) != 1 {
+			return Location::Outside;
+		}
+
+		//last fan edge that p is left of
+		let (mut lo, mut hi) = (1, n - 1);
+		while hi - lo > 1 {
+			let mid = (lo + hi)/2;
+			if o.orientation(v(mid), p) == 2 {lo = mid} else {hi = mid}
+		}
+
+		match v(lo).orientation(v(lo + 1), p) {
+			2 => self.hole_location(p, ContainmentRule::default()).unwrap_or(Location::Inside),
+			0 => Location::OnBoundary,
+			_ => Location::Outside,
+		}
+	}
+
+	//each edge of the exterior as a pair of points, closing the ring
+	fn edges(&self) -> impl Iterator<Item = (&Point<T>, &Point<T>)> {
+		edges(&self.points)
+	}
+}
+
+fn edges<T>(ring: &[Point<T>]) -> impl Iterator<Item = (&Point<T>, &Point<T>)> {
+	let n = ring.len();
+	(0..n).map(move |i| (&ring[i], &ring[(i + 1) % n]))
+}
+
+// twice the area of a ring, negative if it runs clockwise
+fn shoelace<T: Scalar>(ring: &[Point<T>]) -> T {
+	let n = ring.len();
+	let mut xs = Vec::new();
+	let mut ys = Vec::new();
+	
+	for p in ring {
+		xs.push(p.x.clone());
+		ys.push(p.y.clone());
+	}
+
+	let mut area = T::zero();
+
+	for i in 0..n-1 {
+		area = area + xs[i].clone()*ys[i+1].clone() - xs[i+1].clone()*ys[i].clone()
+	}
+	area = area + xs[n-1].clone()*ys[0].clone() - xs[0].clone()*ys[n-1].clone();
+	area
+}
+
+// Edges are half open in y so a ray through a vertex is counted once
+fn crossing_number<T: Scalar>(ring: &[Point<T>], p: &Point<T>) -> usize {
+	let mut count = 0;
+	for (a, b) in edges(ring) {
+		if (a.y <= p.y) != (b.y <= p.y) {
+			//upward edges cross right of p when p is left of them, downward ones the reverse
+			let left = if a.y < b.y {2} else {1};
+			if a.orientation(b, p) == left {
+				count += 1;
+			}
+		}
+	}
+	count
+}
+
+fn winding_number<T: Scalar>(ring: &[Point<T>], p: &Point<T>) -> i32 {
+	let mut wn = 0;
+	for (a, b) in edges(ring) {
+		if a.y <= p.y {
+			if b.y > p.y && a.orientation(b, p) == 2 {
+				wn += 1;
+			}
+		} else if b.y <= p.y && a.orientation(b, p) == 1 {
+			wn -= 1;
+		}
+	}
+	wn
+}
+
+fn inside_ring<T: Scalar>(ring: &[Point<T>], p: &Point<T>, rule: ContainmentRule) -> bool {
+	match rule {
+		ContainmentRule::CrossingNumber => crossing_number(ring, p) % 2 == 1,
+		ContainmentRule::WindingNumber => winding_number(ring, p) != 0,
+	}
+}
+
+// twice the signed area, and the first moments of the ring times six
+fn moments<T: Scalar>(ring: &[Point<T>]) -> (T, T, T) {
+	let (mut area, mut mx, mut my) = (T::zero(), T::zero(), T::zero());
+	for (a, b) in edges(ring) {
+		let cross = a.x.clone()*b.y.clone() - b.x.clone()*a.y.clone();
+		area = area + cross.clone();
+		mx = mx + (a.x.clone() + b.x.clone())*cross.clone();
+		my = my + (a.y.clone() + b.y.clone())*cross;
+	}
+	(area, mx, my)
+}
+
+// p lies on the edge from a to b, endpoints included
+fn on_edge<T: Scalar>(a: &Point<T>, b: &Point<T>, p: &Point<T>) -> bool {
+	a.orientation(b, p) == 0
+		&& p.x >= a.x.clone().min(b.x.clone()) && p.x <= a.x.clone().max(b.x.clone())
+		&& p.y >= a.y.clone().min(b.y.clone()) && p.y <= a.y.clone().max(b.y.clone())
+}
+
+// cuts a ring into loops that each visit a point at most once, splitting it
+// wherever it comes back to a point it already passed through
+pub(crate) fn split_loops<T: Scalar>(ring: Vec<Point<T>>) -> Vec<Vec<Point<T>>> {
+	let mut loops = Vec::new();
+	let mut stack: Vec<Point<T>> = Vec::new();
+	let mut seen = HashMap::new();
+	for p in ring {
+		let key = (p.x.key(), p.y.key());
+		match seen.get(&key) {
+			Some(&start) => {
+				let mut piece = vec![p];
+				piece.extend(stack.split_off(start + 1));
+				for q in &piece[1..] {
+					seen.remove(&(q.x.key(), q.y.key()));
+				}
+				loops.push(piece);
+			}
+			None => {
+				seen.insert(key, stack.len());
+				stack.push(p);
+			}
 		}
+	}
+	loops.push(stack);
+	loops
+}
 
-		let mut area: f64 = 0.0;
+// drops repeated points and vertices in line with their neighbours, which
+// also removes spikes that double back on themselves
+pub(crate) fn simplify<T: Scalar>(points: &[Point<T>]) -> Vec<Point<T>> {
+	let mut out: Vec<Point<T>> = Vec::new();
+	for p in points {
+		loop {
+			let n = out.len();
+			if n >= 1 && out[n - 1].equals(p) {
+				break;
+			}
+			if n >= 2 && out[n - 2].orientation(&out[n - 1], p) == 0 {
+				out.pop();
+				continue;
+			}
+			out.push(p.clone());
+			break;
+		}
+	}
 
-		for i in 0..n-1 {
-			area += xs[i]*ys[i+1] - xs[i+1]*ys[i]
+	//same again across the closing edge
+	loop {
+		let n = out.len();
+		if (n >= 2 && out[n - 1].equals(&out[0])) || (n >= 3 && out[n - 2].orientation(&out[n - 1], &out[0]) == 0) {
+			out.pop();
+		} else if n >= 3 && out[n - 1].orientation(&out[0], &out[1]) == 0 {
+			out.remove(0);
+		} else {
+			break;
 		}
-		area += xs[n-1]*ys[0] - xs[0]*ys[n-1];
+	}
+	out
+}
+
+// the simple counterclockwise pieces of one ring, see Polygon::repair
+fn repair_ring<T: Field>(ring: &[Point<T>]) -> Vec<Polygon<T>> {
+	let points = simplify(ring);
+	let n = points.len();
+	if n < 3 {
+		return Vec::new();
+	}
 
-		//if points were counterclockwise area will be negative
-		return f64::abs(area)/2.0
+	//insert every crossing into the edges it lies on, then cut the ring
+	//into loops wherever it comes back to a point
+	let edges: Vec<Segment<T>> = (0..n).map(|i| Segment { p1: points[i].clone(), p2: points[(i + 1) % n].clone() }).collect();
+	let mut ring = Vec::new();
+	for mut cut in subdivide(&edges) {
+		cut.pop();
+		ring.extend(cut);
 	}
-}
\ No newline at end of file
+
+	let mut polygons = Vec::new();
+	for piece in split_loops(ring) {
+		let piece = simplify(&piece);
+		if piece.len() < 3 {
+			continue;
+		}
+		let mut poly = Polygon { points: piece, orientation: false, holes: Vec::new() };
+		let signed = poly.signed_double_area();
+		if signed == T::zero() {
+			continue;
+		}
+		if signed < T::zero() {
+			poly.points.reverse();
+		}
+		polygons.push(poly);
+	}
+	polygons
+}
+
+impl<T: Field> Polygon<T> {
+	//find the area of any simple polygon, minus its holes
+	pub fn area(&self) -> T {
+		self.double_area()/T::from_i32(2)
+	}
+
+	//centre of mass of the area between the exterior and the holes.
+	//None if there is no area
+	pub fn centroid(&self) -> Option<Point<T>> {
+		let (mut area, mut mx, mut my) = (T::zero(), T::zero(), T::zero());
+		for (i, ring) in self.rings().enumerate() {
+			let (a, x, y) = moments(ring);
+			//the exterior counts positive and holes negative, whichever way each runs
+			if (a > T::zero()) == (i == 0) {
+				area = area + a;
+				mx = mx + x;
+				my = my + y;
+			} else {
+				area = area - a;
+				mx = mx - x;
+				my = my - y;
+			}
+		}
+		if area == T::zero() {
+			return None;
+		}
+		let three = T::from_i32(3)*area;
+		Some(Point::new(mx/three.clone(), my/three))
+	}
+
+	//Splits the polygon into simple counterclockwise polygons. Duplicate and
+	//collinear vertices and spikes are removed, then each ring is cut at every
+	//point where it touches or crosses itself, so a figure eight becomes two
+	//pieces. Pieces with no area are dropped. Holes are repaired the same way,
+	//merged where they overlap and cut out of the exterior pieces, coming back
+	//clockwise. Crossing points are computed in T
+	pub fn repair(&self) -> Vec<Polygon<T>> {
+		let pieces = repair_ring(&self.points);
+		if self.holes.is_empty() {
+			return pieces;
+		}
+
+		let mut holes = Vec::new();
+		for piece in self.holes.iter().flat_map(|hole| repair_ring(hole)) {
+			holes = boolean(&holes, std::slice::from_ref(&piece), BooleanOp::Union).polygons;
+		}
+		pieces.iter()
+			.flat_map(|piece| boolean(std::slice::from_ref(piece), &holes, BooleanOp::Difference).polygons)
+			.collect()
+	}
+
+	//Boolean operations. Both polygons may be concave and have holes. The
+	//result is split into simple counterclockwise pieces with clockwise holes,
+	//see boolean::boolean
+	pub fn union(&self, other: &Polygon<T>) -> MultiPolygon<T> {
+		boolean(std::slice::from_ref(self), std::slice::from_ref(other), BooleanOp::Union)
+	}
+
+	pub fn intersection(&self, other: &Polygon<T>) -> MultiPolygon<T> {
+		boolean(std::slice::from_ref(self), std::slice::from_ref(other), BooleanOp::Intersection)
+	}
+
+	//the parts of self not in other
+	pub fn difference(&self, other: &Polygon<T>) -> MultiPolygon<T> {
+		boolean(std::slice::from_ref(self), std::slice::from_ref(other), BooleanOp::Difference)
+	}
+
+	//the parts in exactly one of the two
+	pub fn xor(&self, other: &Polygon<T>) -> MultiPolygon<T> {
+		boolean(std::slice::from_ref(self), std::slice::from_ref(other), BooleanOp::Xor)
+	}
+
+	//clipped to a convex window, see clip::sutherland_hodgman
+	pub fn clip_convex(&self, window: &Polygon<T>) -> Option<Polygon<T>> {
+		sutherland_hodgman(self, window)
+	}
+
+	//clipped to any simple polygon, see clip::weiler_atherton. That only
+	//follows the exterior rings, so shapes with holes go through intersection
+	pub fn clip(&self, other: &Polygon<T>) -> MultiPolygon<T> {
+		if self.holes.is_empty() && other.holes.is_empty() {
+			weiler_atherton(self, other)
+		} else {
+			self.intersection(other)
+		}
+	}
+}
+
+impl<T: Float> Polygon<T> {
+	//length of the exterior and every hole
+	pub fn perimeter(&self) -> T {
+		self.rings().flat_map(edges).fold(T::zero(), |sum, (a, b)| sum + a.distance(b))
+	}
+}
@@ -0,0 +1,122 @@
+use crate::circle::Circle;
+use crate::multipolygon::MultiPolygon;
+use crate::point::Point;
+use crate::polygon::Polygon;
+use crate::scalar::Float;
+use std::cmp::Ordering;
+
+//POWER DIAGRAM
+// Cells of a power diagram, one per circle in the order given. An empty cell
+// has no polygons
+#[derive(Clone, Debug)]
+pub struct PowerDiagram<T = f64> {
+	pub(crate) cells: Vec<MultiPolygon<T>>,
+}
+
+//Methods
+impl<T: Float> PowerDiagram<T> {
+	pub fn cells(&self) -> &[MultiPolygon<T>] {
+		&self.cells
+	}
+
+	pub fn cell(&self, circle: usize) -> &MultiPolygon<T> {
+		&self.cells[circle]
+	}
+
+	//circles left with no cell, because others outweigh them everywhere or it misses bounds
+	pub fn empty_cells(&self) -> Vec<usize> {
+		(0..self.cells.len()).filter(|&i| self.cells[i].is_empty()).collect()
+	}
+}
+
+// Power (Laguerre) diagram of the circles inside bounds, which may be any
+// polygon with holes. A point belongs to the circle it has the least power
+// |p - center|^2 - radius^2 to, so each cell is the part of bounds on the near
+// side of the radical axis with every other circle. Each cell is cut from the
+// bounding box of bounds one axis at a time, trying circles in order of their
+// distance along x until none left could reach it, then intersected with bounds.
+// About O(n^1.5) for evenly spread circles, O(n^2) at worst. A repeated circle
+// gets an empty cell, and its first copy the cell
+pub fn power_diagram<T: Float>(circles: &[Circle<T>], bounds: &Polygon<T>) -> PowerDiagram<T> {
+	let ring = &bounds.points;
+	let (mut min, mut max) = (ring[0], ring[0]);
+	for p in ring {
+		min = Point::new(min.x.min(p.x), min.y.min(p.y));
+		max = Point::new(max.x.max(p.x), max.y.max(p.y));
+	}
+	let frame = vec![min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)];
+	let largest = circles.iter().fold(T::zero(), |r, c| r.max(c.radius()));
+
+	let mut order: Vec<usize> = (0..circles.len()).collect();
+	order.sort_by(|&a, &b| circles[a].center().x.partial_cmp(&circles[b].center().x).unwrap_or(Ordering::Equal));
+	let mut cells = Vec::with_capacity(circles.len());
+	let mut rank = vec![0; circles.len()];
+	for (k, &i) in order.iter().enumerate() {
+		rank[i] = k;
+	}
+	for i in 0..circles.len() {
+		let (c, r) = (circles[i].center(), circles[i].radius());
+		let mut cell = frame.clone();
+
+		//nearest along x first, from both sides
+		let (mut below, mut above) = (rank[i].checked_sub(1), rank[i] + 1);
+		while !cell.is_empty() {
+			let gap = |j: usize| (circles[j].center().x - c.x).abs();
+			let j = match (below.map(|k| order[k]), order.get(above).copied()) {
+				(Some(a), Some(b)) => if gap(a) <= gap(b) {a} else {b},
+				(Some(a), None) => a,
+				(None, Some(b)) => b,
+				(None, None) => break,
+			};
+			if order.get(above) == Some(&j) {
+				above += 1;
+			} else {
+				below = below.and_then(|k| k.checked_sub(1));
+			}
+
+			//the radical axis with anything this far off along x is at least
+			//(dx^2 + r^2 - largest^2)/(2 dx) from the center, past every corner
+			let dx = gap(j);
+			let reach = cell.iter().fold(T::zero(), |m, p| m.max(p.distance_squared(&c)));
+			let least = dx*dx + r*r - largest*largest;
+			if least > T::zero() && least*least > T::from_i32(4)*dx*dx*reach {
+				break;
+			}
+			cell = cut(&cell, &circles[i], &circles[j], j < i);
+		}
+		cells.push(match Polygon::from_vec(cell) {
+			Ok(cell) => bounds.intersection(&cell),
+			Err(_) => MultiPolygon::new(Vec::new()),
+		});
+	}
+	PowerDiagram { cells }
+}
+
+// The part of the convex ring with no more power to a than to b. Circles with
+// the same center keep the larger, and for equal ones first decides
+fn cut<T: Float>(ring: &[Point<T>], a: &Circle<T>, b: &Circle<T>, first: bool) -> Vec<Point<T>> {
+	let (c, e) = (a.center(), Point::new(b.center().x - a.center().x, b.center().y - a.center().y));
+	let bound = e.x*e.x + e.y*e.y + a.radius()*a.radius() - b.radius()*b.radius();
+	if e.x == T::zero() && e.y == T::zero() {
+		let keep = bound > T::zero() || (bound == T::zero() && !first);
+		return if keep {ring.to_vec()} else {Vec::new()};
+	}
+	//positive past the radical axis
+	let side = |p: &Point<T>| T::from_i32(2)*(e.x*(p.x - c.x) + e.y*(p.y - c.y)) - bound;
+	let mut out = Vec::with_capacity(ring.len() + 1);
+	for k in 0..ring.len() {
+		let (p, q) = (&ring[k], &ring[(k + 1) % ring.len()]);
+		let (sp, sq) = (side(p), side(q));
+		if sp <= T::zero() {
+			out.push(*p);
+		}
+		if (sp < T::zero() && sq > T::zero()) || (sp > T::zero() && sq < T::zero()) {
+			let t = sp/(sp - sq);
+			out.push(Point::new(p.x + (q.x - p.x)*t, p.y + (q.y - p.y)*t));
+		}
+	}
+	if out.len() < 3 {
+		out.clear();
+	}
+	out
+}
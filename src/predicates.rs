@@ -0,0 +1,285 @@
+use crate::point::Point;
+use std::vec::Vec;
+
+// Adaptive precision geometric predicates in the style of Jonathan Shewchuk's
+// "Adaptive Precision Floating-Point Arithmetic and Fast Robust Geometric Predicates".
+// Each predicate first evaluates its determinant in plain floating point and
+// only falls back to exact expansion arithmetic when the result is too close to
+// zero for the error bound to vouch for its sign. The sign of the returned value
+// is always correct; its magnitude is only an approximation.
+// Overflow and underflow are not handled, as in the original.
+
+//half an ulp of 1.0, 2^-53
+const EPSILON: f64 = 1.1102230246251565e-16;
+const RESULT_ERR_BOUND: f64 = (3.0 + 8.0*EPSILON)*EPSILON;
+const CCW_ERR_BOUND_A: f64 = (3.0 + 16.0*EPSILON)*EPSILON;
+const CCW_ERR_BOUND_B: f64 = (2.0 + 12.0*EPSILON)*EPSILON;
+const CCW_ERR_BOUND_C: f64 = (9.0 + 64.0*EPSILON)*EPSILON*EPSILON;
+const ICC_ERR_BOUND_A: f64 = (10.0 + 96.0*EPSILON)*EPSILON;
+
+// Positive if a, b, c are in counterclockwise order, negative if clockwise
+// and zero if they are collinear. The magnitude approximates twice the signed
+// area of the triangle
+pub fn orient2d(a: &Point, b: &Point, c: &Point) -> f64 {
+	let detleft = (a.x - c.x)*(b.y - c.y);
+	let detright = (a.y - c.y)*(b.x - c.x);
+	let det = detleft - detright;
+
+	let detsum = if detleft > 0.0 {
+		if detright <= 0.0 {
+			return det;
+		}
+		detleft + detright
+	}
+	else if detleft < 0.0 {
+		if detright >= 0.0 {
+			return det;
+		}
+		-detleft - detright
+	}
+	else {
+		return det;
+	};
+
+	let errbound = CCW_ERR_BOUND_A*detsum;
+	if det >= errbound || -det >= errbound {
+		return det;
+	}
+	orient2d_adapt(a, b, c, detsum)
+}
+
+fn orient2d_adapt(a: &Point, b: &Point, c: &Point, detsum: f64) -> f64 {
+	let acx = a.x - c.x;
+	let bcx = b.x - c.x;
+	let acy = a.y - c.y;
+	let bcy = b.y - c.y;
+
+	//the differences are rounded, but their products are computed exactly
+	let (detleft, detlefttail) = two_product(acx, bcy);
+	let (detright, detrighttail) = two_product(acy, bcx);
+	let b_exp = expansion_diff(&[detlefttail, detleft], &[detrighttail, detright]);
+
+	let mut det = estimate(&b_exp);
+	let errbound = CCW_ERR_BOUND_B*detsum;
+	if det >= errbound || -det >= errbound {
+		return det;
+	}
+
+	let acxtail = two_diff_tail(a.x, c.x, acx);
+	let bcxtail = two_diff_tail(b.x, c.x, bcx);
+	let acytail = two_diff_tail(a.y, c.y, acy);
+	let bcytail = two_diff_tail(b.y, c.y, bcy);
+
+	if acxtail == 0.0 && acytail == 0.0 && bcxtail == 0.0 && bcytail == 0.0 {
+		return det;
+	}
+
+	let errbound = CCW_ERR_BOUND_C*detsum + RESULT_ERR_BOUND*f64::abs(det);
+	det += (acx*bcytail + bcy*acxtail) - (acy*bcxtail + bcx*acytail);
+	if det >= errbound || -det >= errbound {
+		return det;
+	}
+
+	estimate(&cross_exact(a.x, a.y, c.x, c.y, b.x, b.y, c.x, c.y))
+}
+
+// Sign of the cross product of the directions a1 -> a2 and b1 -> b2.
+// Positive when b's direction is counterclockwise from a's, zero when the two
+// directions are parallel
+pub fn cross_direction(a1: &Point, a2: &Point, b1: &Point, b2: &Point) -> f64 {
+	let dax = a2.x - a1.x;
+	let day = a2.y - a1.y;
+	let dbx = b2.x - b1.x;
+	let dby = b2.y - b1.y;
+
+	let detleft = dax*dby;
+	let detright = day*dbx;
+	let det = detleft - detright;
+
+	//same structure as orient2d, four rounded differences and two products
+	let detsum = f64::abs(detleft) + f64::abs(detright);
+	let errbound = CCW_ERR_BOUND_A*detsum;
+	if (det > errbound || -det > errbound) || detsum == 0.0 {
+		return det;
+	}
+
+	estimate(&cross_exact(a2.x, a2.y, a1.x, a1.y, b2.x, b2.y, b1.x, b1.y))
+}
+
+// Positive if d lies inside the circle through a, b and c, negative if it lies
+// outside and zero if the four points are cocircular. a, b and c must be in
+// counterclockwise order, otherwise the sign is reversed
+pub fn incircle(a: &Point, b: &Point, c: &Point, d: &Point) -> f64 {
+	let adx = a.x - d.x;
+	let bdx = b.x - d.x;
+	let cdx = c.x - d.x;
+	let ady = a.y - d.y;
+	let bdy = b.y - d.y;
+	let cdy = c.y - d.y;
+
+	let bdxcdy = bdx*cdy;
+	let cdxbdy = cdx*bdy;
+	let alift = adx*adx + ady*ady;
+
+	let cdxady = cdx*ady;
+	let adxcdy = adx*cdy;
+	let blift = bdx*bdx + bdy*bdy;
+
+	let adxbdy = adx*bdy;
+	let bdxady = bdx*ady;
+	let clift = cdx*cdx + cdy*cdy;
+
+	let det = alift*(bdxcdy - cdxbdy) + blift*(cdxady - adxcdy) + clift*(adxbdy - bdxady);
+
+	let permanent = (f64::abs(bdxcdy) + f64::abs(cdxbdy))*alift
+		+ (f64::abs(cdxady) + f64::abs(adxcdy))*blift
+		+ (f64::abs(adxbdy) + f64::abs(bdxady))*clift;
+	let errbound = ICC_ERR_BOUND_A*permanent;
+	if det > errbound || -det > errbound {
+		return det;
+	}
+
+	incircle_exact(a, b, c, d)
+}
+
+fn incircle_exact(a: &Point, b: &Point, c: &Point, d: &Point) -> f64 {
+	//every difference is kept as an exact two term expansion
+	let adx = two_diff(a.x, d.x);
+	let ady = two_diff(a.y, d.y);
+	let bdx = two_diff(b.x, d.x);
+	let bdy = two_diff(b.y, d.y);
+	let cdx = two_diff(c.x, d.x);
+	let cdy = two_diff(c.y, d.y);
+
+	let lift = |x: &[f64], y: &[f64]| expansion_sum(&expansion_product(x, x), &expansion_product(y, y));
+	let minor = |x1: &[f64], y1: &[f64], x2: &[f64], y2: &[f64]| {
+		expansion_diff(&expansion_product(x1, y2), &expansion_product(y1, x2))
+	};
+
+	let aterm = expansion_product(&lift(&adx, &ady), &minor(&bdx, &bdy, &cdx, &cdy));
+	let bterm = expansion_product(&lift(&bdx, &bdy), &minor(&cdx, &cdy, &adx, &ady));
+	let cterm = expansion_product(&lift(&cdx, &cdy), &minor(&adx, &ady, &bdx, &bdy));
+
+	estimate(&expansion_sum(&expansion_sum(&aterm, &bterm), &cterm))
+}
+
+// Exact (a - b) x (c - d) as an expansion
+#[allow(clippy::too_many_arguments)]
+fn cross_exact(ax: f64, ay: f64, bx: f64, by: f64, cx: f64, cy: f64, dx: f64, dy: f64) -> Vec<f64> {
+	let abx = two_diff(ax, bx);
+	let aby = two_diff(ay, by);
+	let cdx = two_diff(cx, dx);
+	let cdy = two_diff(cy, dy);
+	expansion_diff(&expansion_product(&abx, &cdy), &expansion_product(&aby, &cdx))
+}
+
+// EXPANSION ARITHMETIC
+// An expansion is a sum of non-overlapping f64 components stored from the
+// smallest magnitude to the largest. Zero components are dropped
+
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+	let x = a + b;
+	let bvirt = x - a;
+	let avirt = x - bvirt;
+	let bround = b - bvirt;
+	let around = a - avirt;
+	(x, around + bround)
+}
+
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+	let x = a + b;
+	let bvirt = x - a;
+	(x, b - bvirt)
+}
+
+fn two_diff_tail(a: f64, b: f64, x: f64) -> f64 {
+	let bvirt = a - x;
+	let avirt = x + bvirt;
+	let bround = bvirt - b;
+	let around = a - avirt;
+	around + bround
+}
+
+// a - b as an expansion
+fn two_diff(a: f64, b: f64) -> Vec<f64> {
+	let x = a - b;
+	let y = two_diff_tail(a, b, x);
+	[y, x].iter().copied().filter(|v| *v != 0.0).collect()
+}
+
+// a * b = x + y exactly, using a fused multiply-add for the error term
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+	let x = a*b;
+	(x, a.mul_add(b, -x))
+}
+
+fn grow_expansion(e: &[f64], b: f64) -> Vec<f64> {
+	let mut h = Vec::with_capacity(e.len() + 1);
+	let mut q = b;
+	for component in e {
+		let (sum, tail) = two_sum(q, *component);
+		if tail != 0.0 {
+			h.push(tail);
+		}
+		q = sum;
+	}
+	if q != 0.0 || h.is_empty() {
+		h.push(q);
+	}
+	h
+}
+
+fn expansion_sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+	let mut h = e.to_vec();
+	for component in f {
+		h = grow_expansion(&h, *component);
+	}
+	h
+}
+
+fn expansion_diff(e: &[f64], f: &[f64]) -> Vec<f64> {
+	let negated: Vec<f64> = f.iter().map(|v| -v).collect();
+	expansion_sum(e, &negated)
+}
+
+fn scale_expansion(e: &[f64], b: f64) -> Vec<f64> {
+	let mut h = Vec::with_capacity(2*e.len());
+	if e.is_empty() {
+		return h;
+	}
+
+	let (mut q, tail) = two_product(e[0], b);
+	if tail != 0.0 {
+		h.push(tail);
+	}
+	for component in &e[1..] {
+		let (product1, product0) = two_product(*component, b);
+		let (sum, tail) = two_sum(q, product0);
+		if tail != 0.0 {
+			h.push(tail);
+		}
+		let (new_q, tail) = fast_two_sum(product1, sum);
+		if tail != 0.0 {
+			h.push(tail);
+		}
+		q = new_q;
+	}
+	if q != 0.0 || h.is_empty() {
+		h.push(q);
+	}
+	h
+}
+
+fn expansion_product(e: &[f64], f: &[f64]) -> Vec<f64> {
+	let mut h = vec![0.0];
+	for component in f {
+		h = expansion_sum(&h, &scale_expansion(e, *component));
+	}
+	h
+}
+
+// Floating point approximation of an expansion. The components are added from
+// smallest to largest, so the sign of the result is the sign of the expansion
+fn estimate(e: &[f64]) -> f64 {
+	e.iter().sum()
+}
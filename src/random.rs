@@ -0,0 +1,33 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+// Linear congruential generator for shuffled insertion orders and treap
+// priorities. Only needs to be quick and repeatable, not good
+pub(crate) struct Lcg(u64);
+
+// Seed used wherever a fixed order is wanted
+pub(crate) const SEED: u64 = 0x2545F4914F6CDD1D;
+
+// A different seed on every call, from the keys std picks for HashMap
+pub(crate) fn random_seed() -> u64 {
+	RandomState::new().build_hasher().finish()
+}
+
+impl Lcg {
+	pub(crate) fn new(seed: u64) -> Self {
+		Lcg(seed)
+	}
+
+	pub(crate) fn next(&mut self) -> u64 {
+		self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+		self.0
+	}
+
+	// Fisher-Yates, taking the high bits as the low ones of an LCG repeat quickly
+	pub(crate) fn shuffle<X>(&mut self, items: &mut [X]) {
+		for i in (1..items.len()).rev() {
+			let j = (self.next() >> 33) as usize % (i + 1);
+			items.swap(i, j);
+		}
+	}
+}
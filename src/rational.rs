@@ -0,0 +1,573 @@
+use crate::point::Point;
+use crate::scalar::{Scalar, Field};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::vec::Vec;
+
+// Exact rational numbers for running the generic algorithms without any
+// rounding. Numerators and denominators are big integers on the heap that
+// grow as far as a result needs, so Rational is Clone but not Copy.
+
+// Unsigned big integer, little endian 32 bit limbs. The top limb is never
+// zero, so the derived comparisons and hash only see the value
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct BigUint {
+	limbs: Vec<u32>,
+}
+
+impl BigUint {
+	const ZERO: BigUint = BigUint { limbs: Vec::new() };
+
+	fn from_u64(v: u64) -> Self {
+		BigUint::from_slice(&[v as u32, (v >> 32) as u32])
+	}
+
+	fn from_slice(digits: &[u32]) -> Self {
+		BigUint::from_vec(digits.to_vec())
+	}
+
+	fn from_vec(mut limbs: Vec<u32>) -> Self {
+		while limbs.last() == Some(&0) {
+			limbs.pop();
+		}
+		BigUint { limbs }
+	}
+
+	fn len(&self) -> usize {
+		self.limbs.len()
+	}
+
+	// Limb i, zero past the top
+	fn limb(&self, i: usize) -> u32 {
+		self.limbs.get(i).copied().unwrap_or(0)
+	}
+
+	fn is_zero(&self) -> bool {
+		self.limbs.is_empty()
+	}
+
+	fn is_one(&self) -> bool {
+		self.limbs == [1]
+	}
+
+	fn bits(&self) -> usize {
+		match self.limbs.last() {
+			Some(top) => 32*self.len() - top.leading_zeros() as usize,
+			None => 0,
+		}
+	}
+
+	fn trailing_zeros(&self) -> usize {
+		let mut n = 0;
+		for d in &self.limbs {
+			if *d != 0 {
+				return n + d.trailing_zeros() as usize;
+			}
+			n += 32;
+		}
+		n
+	}
+
+	fn cmp(&self, other: &BigUint) -> Ordering {
+		self.len().cmp(&other.len()).then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+	}
+
+	fn add(&self, other: &BigUint) -> BigUint {
+		let n = usize::max(self.len(), other.len());
+		let mut out = Vec::with_capacity(n + 1);
+		let mut carry = 0u64;
+		for i in 0..n {
+			let t = self.limb(i) as u64 + other.limb(i) as u64 + carry;
+			out.push(t as u32);
+			carry = t >> 32;
+		}
+		out.push(carry as u32);
+		BigUint::from_vec(out)
+	}
+
+	//self must not be smaller than other
+	fn sub(&self, other: &BigUint) -> BigUint {
+		let mut out = Vec::with_capacity(self.len());
+		let mut borrow = 0i64;
+		for i in 0..self.len() {
+			let mut t = self.limbs[i] as i64 - other.limb(i) as i64 - borrow;
+			borrow = 0;
+			if t < 0 {
+				t += 1 << 32;
+				borrow = 1;
+			}
+			out.push(t as u32);
+		}
+		BigUint::from_vec(out)
+	}
+
+	fn mul(&self, other: &BigUint) -> BigUint {
+		if self.is_zero() || other.is_zero() {
+			return BigUint::ZERO;
+		}
+		let mut out = vec![0u32; self.len() + other.len()];
+		for i in 0..self.len() {
+			let mut carry = 0u64;
+			for j in 0..other.len() {
+				let t = self.limbs[i] as u64*other.limbs[j] as u64 + out[i+j] as u64 + carry;
+				out[i+j] = t as u32;
+				carry = t >> 32;
+			}
+			out[i + other.len()] = carry as u32;
+		}
+		BigUint::from_vec(out)
+	}
+
+	fn shl(&self, bits: usize) -> BigUint {
+		if self.is_zero() {
+			return BigUint::ZERO;
+		}
+		let (words, shift) = (bits/32, bits%32);
+		let mut out = vec![0u32; self.len() + words + 1];
+		for i in 0..self.len() {
+			let v = (self.limbs[i] as u64) << shift;
+			out[i + words] |= v as u32;
+			out[i + words + 1] |= (v >> 32) as u32;
+		}
+		BigUint::from_vec(out)
+	}
+
+	fn shr(&self, bits: usize) -> BigUint {
+		let (words, shift) = (bits/32, bits%32);
+		if words >= self.len() {
+			return BigUint::ZERO;
+		}
+		let mut out = vec![0u32; self.len() - words];
+		for (i, o) in out.iter_mut().enumerate() {
+			let lo = self.limbs[i + words] as u64;
+			let hi = self.limb(i + words + 1) as u64;
+			*o = (((hi << 32) | lo) >> shift) as u32;
+		}
+		BigUint::from_vec(out)
+	}
+
+	// Knuth's algorithm D, as laid out in Hacker's Delight
+	fn divrem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+		if divisor.is_zero() {
+			panic!("Rational division by zero");
+		}
+		if self.cmp(divisor) == Ordering::Less {
+			return (BigUint::ZERO, self.clone());
+		}
+
+		if divisor.len() == 1 {
+			let d = divisor.limbs[0] as u64;
+			let mut q = vec![0u32; self.len()];
+			let mut r = 0u64;
+			for i in (0..self.len()).rev() {
+				let cur = (r << 32) | self.limbs[i] as u64;
+				q[i] = (cur/d) as u32;
+				r = cur%d;
+			}
+			return (BigUint::from_vec(q), BigUint::from_u64(r));
+		}
+
+		let n = divisor.len();
+		let m = self.len() - n;
+		let s = divisor.limbs[n-1].leading_zeros() as usize;
+
+		//normalize so the top limb of the divisor has its high bit set,
+		//un gets one extra limb for what is shifted out of the top
+		let vn = divisor.shl(s).limbs;
+		let mut un = vec![0u32; self.len() + 1];
+		for i in 0..self.len() {
+			let v = (self.limbs[i] as u64) << s;
+			un[i] |= v as u32;
+			un[i+1] |= (v >> 32) as u32;
+		}
+
+		let base = 1u64 << 32;
+		let mut q = vec![0u32; m + 1];
+		for j in (0..=m).rev() {
+			let num = ((un[j+n] as u64) << 32) | un[j+n-1] as u64;
+			let mut qhat = num/vn[n-1] as u64;
+			let mut rhat = num%vn[n-1] as u64;
+			while qhat >= base || qhat*vn[n-2] as u64 > ((rhat << 32) | un[j+n-2] as u64) {
+				qhat -= 1;
+				rhat += vn[n-1] as u64;
+				if rhat >= base {
+					break;
+				}
+			}
+
+			//multiply and subtract
+			let mut k = 0i64;
+			for i in 0..n {
+				let p = qhat*vn[i] as u64;
+				let t = un[i+j] as i64 - k - (p & 0xFFFF_FFFF) as i64;
+				un[i+j] = t as u32;
+				k = (p >> 32) as i64 - (t >> 32);
+			}
+			let t = un[j+n] as i64 - k;
+			un[j+n] = t as u32;
+
+			q[j] = qhat as u32;
+			if t < 0 {
+				//subtracted one time too many, add the divisor back
+				q[j] = q[j].wrapping_sub(1);
+				let mut carry = 0u64;
+				for i in 0..n {
+					let t = un[i+j] as u64 + vn[i] as u64 + carry;
+					un[i+j] = t as u32;
+					carry = t >> 32;
+				}
+				un[j+n] = un[j+n].wrapping_add(carry as u32);
+			}
+		}
+
+		let r = BigUint::from_slice(&un[..n]).shr(s);
+		(BigUint::from_vec(q), r)
+	}
+
+	fn gcd(a: &BigUint, b: &BigUint) -> BigUint {
+		let mut a = a.clone();
+		let mut b = b.clone();
+		while !b.is_zero() {
+			let (_, r) = a.divrem(&b);
+			a = b;
+			b = r;
+		}
+		a
+	}
+
+	//the top 64 bits, rounded down, scaled back to the full value
+	fn approx_f64(&self) -> f64 {
+		let bits = self.bits();
+		if bits <= 64 {
+			let lo = self.limb(0) as u64 | (self.limb(1) as u64) << 32;
+			return lo as f64;
+		}
+		let top = self.shr(bits - 64);
+		let lo = top.limb(0) as u64 | (top.limb(1) as u64) << 32;
+		scale2(lo as f64, (bits - 64) as i32)
+	}
+
+	fn decimal_string(&self) -> String {
+		if self.is_zero() {
+			return "0".to_string();
+		}
+		let billion = BigUint::from_u64(1_000_000_000);
+		let mut chunks = Vec::new();
+		let mut v = self.clone();
+		while !v.is_zero() {
+			let (q, r) = v.divrem(&billion);
+			chunks.push(r.limb(0));
+			v = q;
+		}
+		let mut s = chunks.pop().unwrap().to_string();
+		for c in chunks.iter().rev() {
+			s.push_str(&format!("{:09}", c));
+		}
+		s
+	}
+}
+
+// x * 2^e without overflowing the intermediate power of two
+fn scale2(mut x: f64, mut e: i32) -> f64 {
+	while e > 1000 {
+		x *= 2f64.powi(1000);
+		e -= 1000;
+	}
+	while e < -1000 {
+		x *= 2f64.powi(-1000);
+		e += 1000;
+	}
+	x*2f64.powi(e)
+}
+
+//RATIONAL
+// Always stored in lowest terms with a positive denominator, so two equal
+// values have the same representation
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+	negative: bool,
+	num: BigUint,
+	den: BigUint,
+}
+
+//Constructors
+impl Rational {
+	pub fn new(num: i64, den: i64) -> Self {
+		if den == 0 {
+			panic!("Rational division by zero");
+		}
+		Rational::from_parts(
+			(num < 0) != (den < 0),
+			BigUint::from_u64(num.unsigned_abs()),
+			BigUint::from_u64(den.unsigned_abs()),
+		)
+	}
+
+	pub fn from_integer(v: i64) -> Self {
+		Rational::new(v, 1)
+	}
+
+	// Every finite f64 is a rational number, so this conversion is exact
+	pub fn from_f64(v: f64) -> Self {
+		if !v.is_finite() {
+			panic!("Rational::from_f64 needs a finite value, got {}", v);
+		}
+		if v == 0.0 {
+			return Rational::from_integer(0);
+		}
+
+		let bits = v.to_bits();
+		let negative = bits >> 63 == 1;
+		let exponent = ((bits >> 52) & 0x7ff) as i32;
+		let fraction = bits & ((1 << 52) - 1);
+
+		//subnormals have no implicit leading bit
+		let (mantissa, exponent) = if exponent == 0 {
+			(fraction, -1074)
+		}
+		else {
+			(fraction | (1 << 52), exponent - 1075)
+		};
+
+		let num = BigUint::from_u64(mantissa);
+		if exponent >= 0 {
+			return Rational::from_parts(negative, num.shl(exponent as usize), BigUint::from_u64(1));
+		}
+		Rational::from_parts(negative, num, BigUint::from_u64(1).shl((-exponent) as usize))
+	}
+
+	fn from_parts(negative: bool, num: BigUint, den: BigUint) -> Self {
+		if num.is_zero() {
+			return Rational {
+				negative: false,
+				num,
+				den: BigUint::from_u64(1),
+			}
+		}
+		if den.is_one() {
+			return Rational { negative, num, den };
+		}
+
+		//powers of two are by far the most common common factor for values
+		//that started out as f64, strip them before the general gcd
+		let twos = usize::min(num.trailing_zeros(), den.trailing_zeros());
+		let (num, den) = (num.shr(twos), den.shr(twos));
+
+		let g = BigUint::gcd(&num, &den);
+		let (num, den) = if g.is_one() {(num, den)} else {(num.divrem(&g).0, den.divrem(&g).0)};
+		Rational { negative, num, den }
+	}
+}
+
+//Methods
+impl Rational {
+	pub fn is_zero(&self) -> bool {
+		self.num.is_zero()
+	}
+
+	pub fn is_negative(&self) -> bool {
+		self.negative
+	}
+
+	pub fn is_integer(&self) -> bool {
+		self.den.is_one()
+	}
+
+	// Nearest f64 within a couple of ulps. Values outside the f64 range
+	// become infinite or zero
+	pub fn to_f64(&self) -> f64 {
+		if self.is_zero() {
+			return 0.0;
+		}
+
+		//shift so that the integer quotient keeps 64 significant bits
+		let shift = 64 + self.den.bits() as i64 - self.num.bits() as i64;
+		let q = if shift >= 0 {
+			self.num.shl(shift as usize).divrem(&self.den).0
+		}
+		else {
+			self.num.divrem(&self.den.shl((-shift) as usize)).0
+		};
+
+		let v = scale2(q.approx_f64(), -shift as i32);
+		if self.negative {-v} else {v}
+	}
+
+	pub fn recip(&self) -> Rational {
+		if self.is_zero() {
+			panic!("Rational division by zero");
+		}
+		Rational {
+			negative: self.negative,
+			num: self.den.clone(),
+			den: self.num.clone(),
+		}
+	}
+
+	// Sum of two signed magnitudes
+	fn signed_add(an: bool, a: &BigUint, bn: bool, b: &BigUint) -> (bool, BigUint) {
+		if an == bn {
+			return (an, a.add(b));
+		}
+		match a.cmp(b) {
+			Ordering::Less => (bn, b.sub(a)),
+			_ => (an, a.sub(b)),
+		}
+	}
+}
+
+impl Add for Rational {
+	type Output = Rational;
+
+	fn add(self, other: Rational) -> Rational {
+		//work over the lcm of the denominators to keep intermediates small
+		let g = BigUint::gcd(&self.den, &other.den);
+		let (ad, bd) = (self.den.divrem(&g).0, other.den.divrem(&g).0);
+		let (negative, num) = Rational::signed_add(self.negative, &self.num.mul(&bd), other.negative, &other.num.mul(&ad));
+		Rational::from_parts(negative, num, self.den.mul(&bd))
+	}
+}
+
+impl Sub for Rational {
+	type Output = Rational;
+
+	fn sub(self, other: Rational) -> Rational {
+		self + (-other)
+	}
+}
+
+impl Mul for Rational {
+	type Output = Rational;
+
+	fn mul(self, other: Rational) -> Rational {
+		if self.is_zero() || other.is_zero() {
+			return Rational::from_integer(0);
+		}
+		//cancel across before multiplying
+		let g1 = BigUint::gcd(&self.num, &other.den);
+		let g2 = BigUint::gcd(&other.num, &self.den);
+		let num = self.num.divrem(&g1).0.mul(&other.num.divrem(&g2).0);
+		let den = self.den.divrem(&g2).0.mul(&other.den.divrem(&g1).0);
+		Rational {
+			negative: self.negative != other.negative,
+			num,
+			den,
+		}
+	}
+}
+
+impl Div for Rational {
+	type Output = Rational;
+
+	#[allow(clippy::suspicious_arithmetic_impl)]
+	fn div(self, other: Rational) -> Rational {
+		self*other.recip()
+	}
+}
+
+impl Neg for Rational {
+	type Output = Rational;
+
+	fn neg(self) -> Rational {
+		Rational {
+			negative: !self.negative && !self.is_zero(),
+			num: self.num,
+			den: self.den,
+		}
+	}
+}
+
+impl PartialOrd for Rational {
+	fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Rational {
+	fn cmp(&self, other: &Rational) -> Ordering {
+		if self.negative != other.negative {
+			return if self.negative {Ordering::Less} else {Ordering::Greater};
+		}
+		let magnitude = self.num.mul(&other.den).cmp(&other.num.mul(&self.den));
+		if self.negative {magnitude.reverse()} else {magnitude}
+	}
+}
+
+impl From<i64> for Rational {
+	fn from(v: i64) -> Self {
+		Rational::from_integer(v)
+	}
+}
+
+impl From<f64> for Rational {
+	fn from(v: f64) -> Self {
+		Rational::from_f64(v)
+	}
+}
+
+impl fmt::Display for Rational {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.negative {
+			write!(f, "-")?;
+		}
+		write!(f, "{}", self.num.decimal_string())?;
+		if !self.den.is_one() {
+			write!(f, "/{}", self.den.decimal_string())?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Debug for Rational {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self)
+	}
+}
+
+impl Scalar for Rational {
+	type Key = Rational;
+
+	fn zero() -> Self {Rational::from_integer(0)}
+	fn one() -> Self {Rational::from_integer(1)}
+	fn from_i32(v: i32) -> Self {Rational::from_integer(v as i64)}
+	fn to_f64(&self) -> f64 {Rational::to_f64(self)}
+	fn key(&self) -> Rational {self.clone()}
+
+	fn cross_sign(a1: &Point<Rational>, a2: &Point<Rational>, b1: &Point<Rational>, b2: &Point<Rational>) -> Ordering {
+		let left = (a2.x.clone() - a1.x.clone())*(b2.y.clone() - b1.y.clone());
+		let right = (a2.y.clone() - a1.y.clone())*(b2.x.clone() - b1.x.clone());
+		left.cmp(&right)
+	}
+
+	fn incircle_sign(a: &Point<Rational>, b: &Point<Rational>, c: &Point<Rational>, d: &Point<Rational>) -> Ordering {
+		let [(adx, ady), (bdx, bdy), (cdx, cdy)] = [a, b, c].map(|p| (p.x.clone() - d.x.clone(), p.y.clone() - d.y.clone()));
+		let lift = |x: &Rational, y: &Rational| x.clone()*x.clone() + y.clone()*y.clone();
+		let cross = |ax: &Rational, ay: &Rational, bx: &Rational, by: &Rational| ax.clone()*by.clone() - bx.clone()*ay.clone();
+		let det = lift(&adx, &ady)*cross(&bdx, &bdy, &cdx, &cdy)
+			+ lift(&bdx, &bdy)*cross(&cdx, &cdy, &adx, &ady)
+			+ lift(&cdx, &cdy)*cross(&adx, &ady, &bdx, &bdy);
+		det.cmp(&Rational::zero())
+	}
+}
+
+impl Field for Rational {
+	fn from_f64(v: f64) -> Self {Rational::from_f64(v)}
+}
+
+// EXACT MODE
+// Every algorithm generic over Scalar or Field runs on Point<Rational>.
+// These convert whole point sets in and out of exact coordinates
+
+pub fn to_exact(points: &[Point<f64>]) -> Vec<Point<Rational>> {
+	points.iter().map(|p| Point::new(Rational::from_f64(p.x), Rational::from_f64(p.y))).collect()
+}
+
+pub fn from_exact(points: &[Point<Rational>]) -> Vec<Point<f64>> {
+	points.iter().map(|p| p.to_f64()).collect()
+}
+
+// Run f on an exact copy of the points, e.g. exact(&points, |p| convex_hull(p))
+pub fn exact<R, F: FnOnce(&[Point<Rational>]) -> R>(points: &[Point<f64>], f: F) -> R {
+	f(&to_exact(points))
+}
@@ -0,0 +1,60 @@
+use crate::error::GeometryError;
+use crate::line::{Line, Linear};
+use crate::point::Point;
+use crate::scalar::Scalar;
+use crate::segment::Segment;
+
+//RAY
+//Starts at origin and runs forever through the second point
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray<T = f64> {
+	pub(crate) origin: Point<T>,
+	pub(crate) through: Point<T>,
+}
+
+//Constructors
+impl<T: Scalar> Ray<T> {
+	//the points must differ, otherwise there is no direction
+	pub fn new(origin: Point<T>, through: Point<T>) -> Result<Self, GeometryError> {
+		if origin.equals(&through) {
+			return Err(GeometryError::DegenerateLine);
+		}
+		Ok(Self {
+			origin,
+			through,
+		})
+	}
+
+	//origin first, then the point it passes through
+	pub fn from_coordinates(x1: T, y1: T, x2: T, y2:T) -> Result<Self, GeometryError> {
+		Ray::new(Point::new(x1,y1), Point::new(x2,y2))
+	}
+}
+
+// Methods
+impl<T: Scalar> Ray<T> {
+	pub fn equals(&self, other: &Ray<T>) -> bool {
+		self.origin.equals(&other.origin) && self.through.equals(&other.through)
+	}
+
+	pub fn origin(&self) -> Point<T> {
+		self.origin.clone()
+	}
+
+	//infinite line the ray lies on
+	pub fn to_line(&self) -> Line<T> {
+		Line { p1: self.origin.clone(), p2: self.through.clone() }
+	}
+
+	//segment from the origin to the point the ray was built through
+	pub fn to_segment(&self) -> Segment<T> {
+		Segment { p1: self.origin.clone(), p2: self.through.clone() }
+	}
+}
+
+impl<T: Scalar> Linear<T> for Ray<T> {
+	fn p1(&self) -> Point<T> {self.origin.clone()}
+	fn p2(&self) -> Point<T> {self.through.clone()}
+	fn bounded_start(&self) -> bool {true}
+	fn bounded_end(&self) -> bool {false}
+}
@@ -0,0 +1,202 @@
+use crate::point::Point;
+use crate::predicates::{cross_direction, incircle};
+use crate::rational::Rational;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// Coordinate types. Every shape is generic over one of these three traits:
+//	Scalar: ring arithmetic and an exact cross product sign. Enough for
+//	        orientation, hulls, intersection tests and twice the polygon area.
+//	        Implemented for i32, i64, f32, f64 and Rational
+//	Field:  adds division and conversion from f64, so intersection points and
+//	        areas can be computed. Implemented for f32, f64 and the exact
+//	        rational::Rational
+//	Float:  adds square roots and trigonometry for distances and circles.
+//	        Implemented for f32 and f64
+// Rational is not Copy, so the generic code clones values it uses twice.
+// Float types are all Copy
+pub trait Scalar: Clone + Debug + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self> {
+	// Hashable stand-in for the value, used to find duplicate points quickly
+	type Key: Hash + Eq;
+
+	fn zero() -> Self;
+	fn one() -> Self;
+	fn from_i32(v: i32) -> Self;
+	fn to_f64(&self) -> f64;
+	fn key(&self) -> Self::Key;
+
+	// Sign of the cross product (a2 - a1) x (b2 - b1). Must be exact
+	fn cross_sign(a1: &Point<Self>, a2: &Point<Self>, b1: &Point<Self>, b2: &Point<Self>) -> Ordering;
+
+	// Greater if d is inside the circle through a, b and c, given counterclockwise,
+	// Less if outside and Equal if the four are cocircular. Must be exact
+	fn incircle_sign(a: &Point<Self>, b: &Point<Self>, c: &Point<Self>, d: &Point<Self>) -> Ordering;
+
+	fn abs(self) -> Self {
+		if self < Self::zero() {-self} else {self}
+	}
+
+	fn min(self, other: Self) -> Self {
+		if other < self {other} else {self}
+	}
+
+	fn max(self, other: Self) -> Self {
+		if other > self {other} else {self}
+	}
+}
+
+pub trait Field: Scalar + Div<Output = Self> {
+	fn from_f64(v: f64) -> Self;
+}
+
+pub trait Float: Field + Copy {
+	fn sqrt(self) -> Self;
+	fn acos(self) -> Self;
+	fn sin(self) -> Self;
+	fn cos(self) -> Self;
+	fn atan2(self, x: Self) -> Self;
+	fn pi() -> Self;
+}
+
+fn sign(v: f64) -> Ordering {
+	v.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+}
+
+impl Scalar for f64 {
+	type Key = u64;
+
+	fn zero() -> Self {0.0}
+	fn one() -> Self {1.0}
+	fn from_i32(v: i32) -> Self {v as f64}
+	fn to_f64(&self) -> f64 {*self}
+
+	//0.0 is added so that -0.0 and 0.0 share a key
+	fn key(&self) -> u64 {
+		(self + 0.0).to_bits()
+	}
+
+	fn cross_sign(a1: &Point<f64>, a2: &Point<f64>, b1: &Point<f64>, b2: &Point<f64>) -> Ordering {
+		sign(cross_direction(a1, a2, b1, b2))
+	}
+
+	fn incircle_sign(a: &Point<f64>, b: &Point<f64>, c: &Point<f64>, d: &Point<f64>) -> Ordering {
+		sign(incircle(a, b, c, d))
+	}
+}
+
+impl Field for f64 {
+	fn from_f64(v: f64) -> Self {v}
+}
+
+impl Float for f64 {
+	fn sqrt(self) -> Self {f64::sqrt(self)}
+	fn acos(self) -> Self {f64::acos(self)}
+	fn sin(self) -> Self {f64::sin(self)}
+	fn cos(self) -> Self {f64::cos(self)}
+	fn atan2(self, x: Self) -> Self {f64::atan2(self, x)}
+	fn pi() -> Self {std::f64::consts::PI}
+}
+
+impl Scalar for f32 {
+	type Key = u32;
+
+	fn zero() -> Self {0.0}
+	fn one() -> Self {1.0}
+	fn from_i32(v: i32) -> Self {v as f32}
+	fn to_f64(&self) -> f64 {*self as f64}
+
+	fn key(&self) -> u32 {
+		(self + 0.0).to_bits()
+	}
+
+	//every f32 is exactly representable as an f64
+	fn cross_sign(a1: &Point<f32>, a2: &Point<f32>, b1: &Point<f32>, b2: &Point<f32>) -> Ordering {
+		sign(cross_direction(&a1.to_f64(), &a2.to_f64(), &b1.to_f64(), &b2.to_f64()))
+	}
+
+	fn incircle_sign(a: &Point<f32>, b: &Point<f32>, c: &Point<f32>, d: &Point<f32>) -> Ordering {
+		sign(incircle(&a.to_f64(), &b.to_f64(), &c.to_f64(), &d.to_f64()))
+	}
+}
+
+impl Field for f32 {
+	fn from_f64(v: f64) -> Self {v as f32}
+}
+
+impl Float for f32 {
+	fn sqrt(self) -> Self {f32::sqrt(self)}
+	fn acos(self) -> Self {f32::acos(self)}
+	fn sin(self) -> Self {f32::sin(self)}
+	fn cos(self) -> Self {f32::cos(self)}
+	fn atan2(self, x: Self) -> Self {f32::atan2(self, x)}
+	fn pi() -> Self {std::f32::consts::PI}
+}
+
+impl Scalar for i32 {
+	type Key = i32;
+
+	fn zero() -> Self {0}
+	fn one() -> Self {1}
+	fn from_i32(v: i32) -> Self {v}
+	fn to_f64(&self) -> f64 {*self as f64}
+	fn key(&self) -> i32 {*self}
+
+	//differences fit in 33 bits, so the products fit easily in an i128
+	fn cross_sign(a1: &Point<i32>, a2: &Point<i32>, b1: &Point<i32>, b2: &Point<i32>) -> Ordering {
+		let ax = a2.x as i128 - a1.x as i128;
+		let ay = a2.y as i128 - a1.y as i128;
+		let bx = b2.x as i128 - b1.x as i128;
+		let by = b2.y as i128 - b1.y as i128;
+		(ax*by).cmp(&(ay*bx))
+	}
+
+	//too wide for an i128, but every i32 is exact in an f64 and the adaptive test is exact
+	fn incircle_sign(a: &Point<i32>, b: &Point<i32>, c: &Point<i32>, d: &Point<i32>) -> Ordering {
+		sign(incircle(&a.to_f64(), &b.to_f64(), &c.to_f64(), &d.to_f64()))
+	}
+}
+
+impl Scalar for i64 {
+	type Key = i64;
+
+	fn zero() -> Self {0}
+	fn one() -> Self {1}
+	fn from_i32(v: i32) -> Self {v as i64}
+	fn to_f64(&self) -> f64 {*self as f64}
+	fn key(&self) -> i64 {*self}
+
+	//differences need 65 bits, so their products can overflow an i128.
+	//Compare the two products by sign and then by unsigned magnitude instead
+	fn cross_sign(a1: &Point<i64>, a2: &Point<i64>, b1: &Point<i64>, b2: &Point<i64>) -> Ordering {
+		let ax = a2.x as i128 - a1.x as i128;
+		let ay = a2.y as i128 - a1.y as i128;
+		let bx = b2.x as i128 - b1.x as i128;
+		let by = b2.y as i128 - b1.y as i128;
+		compare_products(ax, by, ay, bx)
+	}
+
+	//products of lifts need over 260 bits, so fall back to Rational
+	fn incircle_sign(a: &Point<i64>, b: &Point<i64>, c: &Point<i64>, d: &Point<i64>) -> Ordering {
+		let exact = |p: &Point<i64>| Point::new(Rational::from_integer(p.x), Rational::from_integer(p.y));
+		Rational::incircle_sign(&exact(a), &exact(b), &exact(c), &exact(d))
+	}
+}
+
+// Compare a*b with c*d where every factor is below 2^64 in magnitude
+fn compare_products(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+	let left = a.signum()*b.signum();
+	let right = c.signum()*d.signum();
+	if left != right {
+		return left.cmp(&right);
+	}
+
+	let left_mag = a.unsigned_abs()*b.unsigned_abs();
+	let right_mag = c.unsigned_abs()*d.unsigned_abs();
+	match left {
+		1 => left_mag.cmp(&right_mag),
+		-1 => right_mag.cmp(&left_mag),
+		_ => Ordering::Equal,
+	}
+}
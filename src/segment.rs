@@ -0,0 +1,62 @@
+use crate::error::GeometryError;
+use crate::line::{Line, Linear};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::scalar::{Scalar, Float};
+
+//SEGMENT
+//Finite piece of a line, both endpoints included
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Segment<T = f64> {
+	pub(crate) p1: Point<T>,
+	pub(crate) p2: Point<T>,
+}
+
+//Constructors
+impl<T: Scalar> Segment<T> {
+	//from 2 endpoints. The points must differ
+	pub fn new(p1: Point<T>, p2: Point<T>) -> Result<Self, GeometryError> {
+		if p1.equals(&p2) {
+			return Err(GeometryError::DegenerateLine);
+		}
+		Ok(Self {
+			p1,
+			p2,
+		})
+	}
+
+	//from 4 coordinates
+	pub fn from_coordinates(x1: T, y1: T, x2: T, y2:T) -> Result<Self, GeometryError> {
+		Segment::new(Point::new(x1,y1), Point::new(x2,y2))
+	}
+}
+
+// Methods
+impl<T: Scalar> Segment<T> {
+	pub fn equals(&self, other: &Segment<T>) -> bool {
+		self.p1.equals(&other.p1) && self.p2.equals(&other.p2)
+	}
+
+	//infinite line through both endpoints
+	pub fn to_line(&self) -> Line<T> {
+		Line { p1: self.p1.clone(), p2: self.p2.clone() }
+	}
+
+	//ray from the first endpoint through the second
+	pub fn to_ray(&self) -> Ray<T> {
+		Ray { origin: self.p1.clone(), through: self.p2.clone() }
+	}
+}
+
+impl<T: Float> Segment<T> {
+	pub fn length(&self) -> T {
+		self.p1.distance(&self.p2)
+	}
+}
+
+impl<T: Scalar> Linear<T> for Segment<T> {
+	fn p1(&self) -> Point<T> {self.p1.clone()}
+	fn p2(&self) -> Point<T> {self.p2.clone()}
+	fn bounded_start(&self) -> bool {true}
+	fn bounded_end(&self) -> bool {true}
+}
@@ -0,0 +1,533 @@
+use crate::hull::lexicographic;
+use crate::line::{Intersection, Linear};
+use crate::point::Point;
+use crate::random::{Lcg, SEED};
+use crate::scalar::{Scalar, Field};
+use crate::segment::Segment;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+// A point where two or more segments meet, with the indices of every
+// segment through it in increasing order. Collinear overlaps are
+// reported at both ends of the shared piece
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepIntersection<T = f64> {
+	pub point: Point<T>,
+	pub segments: Vec<usize>,
+}
+
+// Every intersection of the segments, in lexicographic order of the points.
+// Bentley-Ottmann, O((n + k) log n) for n segments and k intersections.
+// Which segments meet is decided with exact orientation tests. Crossing points
+// that are not endpoints are computed in T, so they are only exact for Rational
+pub fn all_intersections<T: Field>(segments: &[Segment<T>]) -> Vec<SweepIntersection<T>> {
+	let mut sweep = Sweep::new(segments);
+	let mut found = Vec::new();
+	while let Some((p, event)) = sweep.events.pop_first() {
+		let p = p.0;
+		let group = sweep.handle(&p, &event);
+		if group.len() > 1 {
+			found.push(SweepIntersection { point: p, segments: group });
+		}
+		for (q, a, b) in sweep.crossed.drain(..) {
+			found.push(SweepIntersection { point: q, segments: vec![a.min(b), a.max(b)] });
+		}
+	}
+	found.sort_by(|x, y| lexicographic(&x.point, &y.point));
+	found
+}
+
+// Each segment as the list of points it is cut into by the others, from p1
+// to p2, so consecutive points bound pieces that only meet at their ends
+pub(crate) fn subdivide<T: Field>(segments: &[Segment<T>]) -> Vec<Vec<Point<T>>> {
+	let mut cuts: Vec<Vec<Point<T>>> = vec![Vec::new(); segments.len()];
+	for found in all_intersections(segments) {
+		for &i in &found.segments {
+			cuts[i].push(found.point.clone());
+		}
+	}
+	segments.iter().zip(cuts).map(|(s, mut cut)| {
+		let (dx, dy) = (s.p2.x.clone() - s.p1.x.clone(), s.p2.y.clone() - s.p1.y.clone());
+		let along = |p: &Point<T>| (p.x.clone() - s.p1.x.clone())*dx + (p.y.clone() - s.p1.y.clone())*dy;
+		cut.sort_by(|a, b| along.clone()(a).partial_cmp(&along.clone()(b)).unwrap_or(Ordering::Equal));
+		let mut points = vec![s.p1.clone()];
+		for p in cut.into_iter().chain([s.p2.clone()]) {
+			if !points[points.len() - 1].equals(&p) {
+				points.push(p);
+			}
+		}
+		points
+	}).collect()
+}
+
+// First pair of segments found to touch or cross, smaller index first.
+// Shamos-Hoey, O(n log n). Only endpoints are ever queued and every test is an
+// exact orientation test, so the answer is exact for every coordinate type
+pub fn any_intersection<T: Scalar>(segments: &[Segment<T>]) -> Option<(usize, usize)> {
+	first_intersection(segments, &|_, _| false)
+}
+
+// Same as any_intersection, but pairs for which allowed(smaller, larger) is true
+// may touch at a single point that is an end of one of them. Used for polygon rings
+pub(crate) fn first_intersection<T: Scalar>(segments: &[Segment<T>], allowed: &dyn Fn(usize, usize) -> bool) -> Option<(usize, usize)> {
+	let mut sweep = Sweep::new(segments);
+	while let Some((p, event)) = sweep.events.pop_first() {
+		if let Some(pair) = sweep.handle_any(&p.0, &event, allowed) {
+			return Some(pair);
+		}
+	}
+	None
+}
+
+// Points in the event queue, ordered left to right and bottom to top
+struct Key<T>(Point<T>);
+
+impl<T: Scalar> PartialEq for Key<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<T: Scalar> Eq for Key<T> {}
+
+impl<T: Scalar> PartialOrd for Key<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T: Scalar> Ord for Key<T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		lexicographic(&self.0, &other.0)
+	}
+}
+
+#[derive(Default)]
+struct Event {
+	//segments whose first endpoint is here
+	starts: Vec<usize>,
+	//and whose last
+	ends: Vec<usize>,
+	//segments found to cross here, in case rounding moved the point off them
+	crossing: Vec<usize>,
+	//segments whose crossing was rounded past the end of one of them, which is here
+	rounded: Vec<usize>,
+}
+
+const NIL: usize = usize::MAX;
+
+struct Node {
+	segment: usize,
+	priority: u64,
+	left: usize,
+	right: usize,
+}
+
+// Segments crossing the sweep line from bottom to top, kept in a treap.
+// Segments are never compared with each other directly. Instead the tree is
+// split around each event point with exact orientation tests, so the order
+// only has to be right where the sweep line currently is
+struct Sweep<T> {
+	//endpoints of each segment in lexicographic order
+	ends: Vec<(Point<T>, Point<T>)>,
+	events: BTreeMap<Key<T>, Event>,
+	nodes: Vec<Node>,
+	root: usize,
+	random: Lcg,
+	//crossings reported away from the event that handled them
+	crossed: Vec<(Point<T>, usize, usize)>,
+}
+
+impl<T: Scalar> Sweep<T> {
+	fn new(segments: &[Segment<T>]) -> Self {
+		let mut sweep = Sweep {
+			ends: Vec::with_capacity(segments.len()),
+			events: BTreeMap::new(),
+			nodes: Vec::new(),
+			root: NIL,
+			random: Lcg::new(SEED),
+			crossed: Vec::new(),
+		};
+		for (i, s) in segments.iter().enumerate() {
+			let (a, b) = if lexicographic(&s.p1, &s.p2) == Ordering::Less {(s.p1.clone(), s.p2.clone())} else {(s.p2.clone(), s.p1.clone())};
+			sweep.ends.push((a.clone(), b.clone()));
+			sweep.events.entry(Key(a)).or_default().starts.push(i);
+			sweep.events.entry(Key(b)).or_default().ends.push(i);
+		}
+		sweep
+	}
+
+	// Moves the sweep line past p. Stops at the first two segments that meet,
+	// while nothing has crossed yet the status order is exact
+	fn handle_any(&mut self, p: &Point<T>, event: &Event, allowed: &dyn Fn(usize, usize) -> bool) -> Option<(usize, usize)> {
+		let (below, rest) = self.split(self.root, &|s: &Sweep<T>, i| s.side(i, p) == Ordering::Less);
+		let (through, above) = self.split(rest, &|s: &Sweep<T>, i| s.side(i, p) == Ordering::Equal);
+
+		let mut group = Vec::new();
+		self.collect(through, &mut group);
+		group.extend(event.starts.iter().copied());
+		for (k, &a) in group.iter().enumerate() {
+			for &b in &group[k+1..] {
+				if !self.touches_only(a, b, allowed) {
+					return Some((a.min(b), a.max(b)));
+				}
+			}
+		}
+
+		let mut next: Vec<usize> = group.into_iter().filter(|&i| !self.ends[i].1.equals(p)).collect();
+		next.sort_by(|&a, &b| self.by_direction(a, b));
+		let lower = self.last(below);
+		let upper = self.first(above);
+		let found = match (next.first(), next.last()) {
+			(Some(&first), Some(&last)) => self.meets(lower, first, allowed).or_else(|| self.meets(last, upper, allowed)),
+			_ => self.meets(lower, upper, allowed),
+		};
+		if found.is_some() {
+			return found;
+		}
+
+		let mut middle = NIL;
+		for &i in &next {
+			let node = self.node(i);
+			middle = self.merge(middle, node);
+		}
+		let left = self.merge(below, middle);
+		self.root = self.merge(left, above);
+		None
+	}
+
+	fn meets(&self, a: usize, b: usize, allowed: &dyn Fn(usize, usize) -> bool) -> Option<(usize, usize)> {
+		if a == NIL || b == NIL {
+			return None;
+		}
+		(self.segment(a).intersects(&self.segment(b)) && !self.touches_only(a, b, allowed)).then_some((a.min(b), a.max(b)))
+	}
+
+	fn segment(&self, i: usize) -> Segment<T> {
+		let (p1, p2) = self.ends[i].clone();
+		Segment { p1, p2 }
+	}
+
+	// a and b are allowed to touch, and meet only at one point, an end of at
+	// least one of them. Only called for segments that do meet
+	fn touches_only(&self, a: usize, b: usize, allowed: &dyn Fn(usize, usize) -> bool) -> bool {
+		if !allowed(a.min(b), a.max(b)) {
+			return false;
+		}
+		let (a1, a2) = &self.ends[a];
+		let (b1, b2) = &self.ends[b];
+		let (o1, o2) = (a1.orientation(a2, b1), a1.orientation(a2, b2));
+		if o1 != 0 || o2 != 0 {
+			//not in line, so they meet at one point, an end if either lies on the other's line
+			return o1 == 0 || o2 == 0 || b1.orientation(b2, a1) == 0 || b1.orientation(b2, a2) == 0;
+		}
+		//in line, they must share an end and leave it in opposite directions
+		let shared = [(a1, a2, b1, b2), (a1, a2, b2, b1), (a2, a1, b1, b2), (a2, a1, b2, b1)];
+		shared.iter().any(|(s, x, t, y)| s.equals(t) && lexicographic(s, x) != lexicographic(s, y))
+	}
+
+	// Order just right of a shared point, steepest on top
+	fn by_direction(&self, a: usize, b: usize) -> Ordering {
+		let (a1, a2) = &self.ends[a];
+		let (b1, b2) = &self.ends[b];
+		T::cross_sign(a1, a2, b1, b2).reverse().then(a.cmp(&b))
+	}
+
+	// Less if segment i passes below p, Equal if through it
+	fn side(&self, i: usize, p: &Point<T>) -> Ordering {
+		let (a, b) = &self.ends[i];
+		T::cross_sign(a, b, a, p).reverse()
+	}
+
+	fn node(&mut self, segment: usize) -> usize {
+		let priority = self.random.next();
+		self.nodes.push(Node { segment, priority, left: NIL, right: NIL });
+		self.nodes.len() - 1
+	}
+
+	// Left part holds the segments for which pred is true. pred must be true
+	// for a prefix of the sweep order
+	fn split(&mut self, t: usize, pred: &dyn Fn(&Sweep<T>, usize) -> bool) -> (usize, usize) {
+		if t == NIL {
+			return (NIL, NIL);
+		}
+		if pred(self, self.nodes[t].segment) {
+			let (l, r) = self.split(self.nodes[t].right, pred);
+			self.nodes[t].right = l;
+			(t, r)
+		} else {
+			let (l, r) = self.split(self.nodes[t].left, pred);
+			self.nodes[t].left = r;
+			(l, t)
+		}
+	}
+
+	fn merge(&mut self, a: usize, b: usize) -> usize {
+		if a == NIL {
+			return b;
+		}
+		if b == NIL {
+			return a;
+		}
+		if self.nodes[a].priority > self.nodes[b].priority {
+			let right = self.nodes[a].right;
+			self.nodes[a].right = self.merge(right, b);
+			a
+		} else {
+			let left = self.nodes[b].left;
+			self.nodes[b].left = self.merge(a, left);
+			b
+		}
+	}
+
+	fn first(&self, mut t: usize) -> usize {
+		if t == NIL {
+			return NIL;
+		}
+		while self.nodes[t].left != NIL {
+			t = self.nodes[t].left;
+		}
+		self.nodes[t].segment
+	}
+
+	fn last(&self, mut t: usize) -> usize {
+		if t == NIL {
+			return NIL;
+		}
+		while self.nodes[t].right != NIL {
+			t = self.nodes[t].right;
+		}
+		self.nodes[t].segment
+	}
+
+	fn collect(&self, t: usize, out: &mut Vec<usize>) {
+		if t == NIL {
+			return;
+		}
+		self.collect(self.nodes[t].left, out);
+		out.push(self.nodes[t].segment);
+		self.collect(self.nodes[t].right, out);
+	}
+}
+
+impl<T: Field> Sweep<T> {
+	// Moves the sweep line past p and returns every segment through it
+	fn handle(&mut self, p: &Point<T>, event: &Event) -> Vec<usize> {
+		//split the status into segments below p, through p and above p
+		let (below, rest) = self.split(self.root, &|s: &Sweep<T>, i| s.side(i, p) == Ordering::Less);
+		let (mut through, mut above) = self.split(rest, &|s: &Sweep<T>, i| s.side(i, p) == Ordering::Equal);
+		let mut below = below;
+
+		//pull in neighbours that only missed p because it was rounded
+		loop {
+			let last = self.last(below);
+			if last == NIL || !(self.crosses_at(last, event) || event.rounded.contains(&last)) {
+				break;
+			}
+			let (rest, moved) = self.split(below, &|_: &Sweep<T>, i| i != last);
+			below = rest;
+			through = self.merge(moved, through);
+		}
+		loop {
+			let first = self.first(above);
+			if first == NIL || !(self.crosses_at(first, event) || event.rounded.contains(&first)) {
+				break;
+			}
+			let (moved, rest) = self.split(above, &|_: &Sweep<T>, i| i == first);
+			above = rest;
+			through = self.merge(through, moved);
+		}
+
+		let mut found = Vec::new();
+		self.collect(through, &mut found);
+		//and segments ending here that rounding left outside, past ones out of order
+		while event.ends.iter().any(|i| !found.contains(i)) && (below != NIL || above != NIL) {
+			let (last, first) = (self.last(below), self.first(above));
+			if last != NIL {
+				below = self.split(below, &|_: &Sweep<T>, i| i != last).0;
+				found.insert(0, last);
+			}
+			if first != NIL {
+				above = self.split(above, &|_: &Sweep<T>, i| i == first).1;
+				found.push(first);
+			}
+		}
+		//a crossing rounded past the end of a nearly vertical segment is handled at
+		//that end, which leaves segments out of order between ones through p. They
+		//go back beside them, crossing each they were on the wrong side of
+		let sides: Vec<Ordering> = found.iter().map(|&i| {
+			if self.crosses_at(i, event) {Ordering::Equal} else {self.side(i, p)}
+		}).collect();
+		let (mut group, mut lower, mut upper) = (Vec::new(), NIL, NIL);
+		for (k, &i) in found.iter().enumerate() {
+			let wrong = match sides[k] {
+				Ordering::Less => 0..k,
+				Ordering::Greater => k + 1..found.len(),
+				Ordering::Equal => {
+					group.push(i);
+					continue;
+				}
+			};
+			for t in wrong.filter(|&t| sides[t] == Ordering::Equal) {
+				if let Some(q) = self.meeting(i, found[t]) {
+					self.crossed.push((q, i, found[t]));
+				}
+			}
+			let node = self.node(i);
+			if sides[k] == Ordering::Less {
+				lower = self.merge(lower, node);
+			} else {
+				upper = self.merge(upper, node);
+			}
+		}
+		//with their new neighbours on the far side too
+		if lower != NIL {
+			self.check(self.last(below), self.first(lower), p);
+			below = self.merge(below, lower);
+		}
+		if upper != NIL {
+			self.check(self.last(upper), self.first(above), p);
+			above = self.merge(upper, above);
+		}
+		//segments that go on past p, and those that start here
+		let mut next: Vec<usize> = group.iter().copied().filter(|&i| !self.ends[i].1.equals(p)).collect();
+		next.extend(event.starts.iter().copied());
+		group.extend(event.starts.iter().copied());
+		group.sort_unstable();
+		group.dedup();
+
+		next.sort_by(|&a, &b| self.by_direction(a, b));
+		next.dedup();
+
+		//a neighbour whose crossing with them was rounded onto p or behind it
+		//crosses here too, so it is ordered with them by direction. Those pairs
+		//are reported where the crossing was found
+		let mut order = next.clone();
+		let mut moved = false;
+		loop {
+			let (lower, upper) = (self.last(below), self.first(above));
+			if next.is_empty() {
+				if !self.behind(lower, upper, p) {
+					break;
+				}
+				below = self.split(below, &|_: &Sweep<T>, i| i != lower).0;
+				above = self.split(above, &|_: &Sweep<T>, i| i == upper).1;
+				next = vec![lower, upper];
+				order = next.clone();
+			} else if self.behind(lower, next[0], p) {
+				below = self.split(below, &|_: &Sweep<T>, i| i != lower).0;
+				next.push(lower);
+				order.insert(0, lower);
+			} else if self.behind(next[next.len() - 1], upper, p) {
+				above = self.split(above, &|_: &Sweep<T>, i| i == upper).1;
+				next.push(upper);
+				order.push(upper);
+			} else {
+				break;
+			}
+			next.sort_by(|&a, &b| self.by_direction(a, b));
+			moved = true;
+		}
+		if moved {
+			for (k, &a) in order.iter().enumerate() {
+				for &b in &order[k + 1..] {
+					let flipped = next.iter().position(|&i| i == a) > next.iter().position(|&i| i == b);
+					if let (true, Some(q)) = (flipped, self.proper(a, b)) {
+						self.crossed.push((q, a, b));
+					}
+				}
+			}
+		}
+
+		let lower = self.last(below);
+		let upper = self.first(above);
+		if next.is_empty() {
+			self.check(lower, upper, p);
+		} else {
+			self.check(lower, next[0], p);
+			self.check(next[next.len() - 1], upper, p);
+		}
+
+		let mut middle = NIL;
+		for &i in &next {
+			let node = self.node(i);
+			middle = self.merge(middle, node);
+		}
+		let left = self.merge(below, middle);
+		self.root = self.merge(left, above);
+		group
+	}
+
+	// Segment i was found to cross at the event, or lies on the same line as one that was
+	fn crosses_at(&self, i: usize, event: &Event) -> bool {
+		let (a, b) = &self.ends[i];
+		event.crossing.iter().any(|&j| {
+			let (c, d) = &self.ends[j];
+			j == i || (c.orientation(d, a) == 0 && c.orientation(d, b) == 0)
+		})
+	}
+
+	// Where a, below b, properly crosses it ahead of the sweep line, kept in the
+	// box both share as rounding can put it off both. None if they only touch
+	fn proper(&self, a: usize, b: usize) -> Option<Point<T>> {
+		let (a1, a2) = &self.ends[a];
+		let (b1, b2) = &self.ends[b];
+		if a1.orientation(a2, b1) == 0 || a1.orientation(a2, b2) == 0 || b1.orientation(b2, a1) == 0 || b1.orientation(b2, a2) == 0 {
+			return None;
+		}
+		//a still has to end up above b
+		if b1.orientation(b2, a2) != 2 {
+			return None;
+		}
+		self.meeting(a, b)
+	}
+
+	// Where a and b meet, kept in the box both share
+	fn meeting(&self, a: usize, b: usize) -> Option<Point<T>> {
+		let (a1, a2) = &self.ends[a];
+		let (b1, b2) = &self.ends[b];
+		let Intersection::Point(q) = self.segment(a).intersection(&self.segment(b)) else {
+			return None;
+		};
+		let low = a1.y.clone().min(a2.y.clone()).max(b1.y.clone().min(b2.y.clone()));
+		let high = a1.y.clone().max(a2.y.clone()).min(b1.y.clone().max(b2.y.clone()));
+		Some(Point::new(q.x.max(a1.x.clone().max(b1.x.clone())).min(a2.x.clone().min(b2.x.clone())), q.y.max(low).min(high)))
+	}
+
+	// Neighbours a below b cross ahead, but the point found is not past p
+	fn behind(&self, a: usize, b: usize, p: &Point<T>) -> bool {
+		a != NIL && b != NIL && self.proper(a, b).is_some_and(|q| lexicographic(&q, p) != Ordering::Greater)
+	}
+
+	// Queue a crossing of two neighbours, a below b, if it is still ahead of the sweep line
+	fn check(&mut self, a: usize, b: usize, p: &Point<T>) {
+		if a == NIL || b == NIL {
+			return;
+		}
+		let (a1, a2) = &self.ends[a];
+		let (b1, b2) = &self.ends[b];
+		let q = match self.proper(a, b) {
+			Some(q) => q,
+			//touching, where the point is exact. Collinear overlaps start and end
+			//at segment endpoints, which are queued already
+			None => match self.segment(a).intersection(&self.segment(b)) {
+				Intersection::Point(q) if [a1, a2, b1, b2].iter().any(|e| e.equals(&q)) => q,
+				_ => return,
+			},
+		};
+		if lexicographic(&q, p) != Ordering::Greater {
+			return;
+		}
+		//a point rounded past the end of a nearly vertical segment is handled at that end
+		let end = if lexicographic(a2, b2) == Ordering::Less {a2.clone()} else {b2.clone()};
+		if lexicographic(&q, &end) != Ordering::Greater {
+			let event = self.events.entry(Key(q)).or_default();
+			event.crossing.push(a);
+			event.crossing.push(b);
+		} else {
+			let event = self.events.entry(Key(end)).or_default();
+			event.rounded.push(a);
+			event.rounded.push(b);
+		}
+	}
+}
@@ -0,0 +1,63 @@
+// Tolerance used by the approximate (_tol) predicates and distance routines.
+// Two values are treated as equal when they differ by no more than the
+// absolute epsilon, or by no more than the relative epsilon times their size.
+// The absolute epsilon suits coordinates of a known scale, the relative one
+// lets the same code work on millimetre and kilometre data.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tolerance {
+	pub absolute: f64,
+	pub relative: f64,
+}
+
+//Constructors
+impl Tolerance {
+	// The fixed 0.00001 epsilon every module used before Tolerance existed
+	pub const DEFAULT: Tolerance = Tolerance::new(0.00001, 0.0);
+
+	// No tolerance at all, values must match exactly
+	pub const EXACT: Tolerance = Tolerance::new(0.0, 0.0);
+
+	pub const fn new(absolute: f64, relative: f64) -> Self {
+		Self {
+			absolute,
+			relative,
+		}
+	}
+
+	pub const fn absolute(absolute: f64) -> Self {
+		Tolerance::new(absolute, 0.0)
+	}
+
+	pub const fn relative(relative: f64) -> Self {
+		Tolerance::new(0.0, relative)
+	}
+}
+
+impl Default for Tolerance {
+	fn default() -> Self {
+		Tolerance::DEFAULT
+	}
+}
+
+//Methods
+impl Tolerance {
+	// Largest difference still treated as zero for values of the given magnitude
+	pub fn epsilon(&self, scale: f64) -> f64 {
+		f64::max(self.absolute, self.relative*f64::abs(scale))
+	}
+
+	pub fn approx_eq(&self, a: f64, b: f64) -> bool {
+		a == b || f64::abs(a - b) <= self.epsilon(f64::max(f64::abs(a), f64::abs(b)))
+	}
+
+	// v is a quantity computed from values of magnitude scale,
+	// e.g. a cross product and the product of the vector lengths
+	pub fn is_zero(&self, v: f64, scale: f64) -> bool {
+		f64::abs(v) <= self.epsilon(scale)
+	}
+
+	// a < b by more than the tolerance
+	pub fn less(&self, a: f64, b: f64) -> bool {
+		a < b && !self.approx_eq(a, b)
+	}
+}
@@ -0,0 +1,638 @@
+use crate::boolean::clockwise_from;
+use crate::point::Point;
+use crate::polygon::Polygon;
+use crate::scalar::Scalar;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+// Both algorithms return triangles as counterclockwise index triples into
+// Polygon::vertices, the exterior ring followed by each hole in turn. The
+// polygon and its holes should be simple and the holes inside the exterior,
+// though rings may touch at single points as Polygon::validate allows.
+// Every test is an exact orientation test, so integer coordinates work too.
+// Vertices in line with their neighbours are used like any other, though a
+// triangulation only made of such vertices has no triangles
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TriangulationAlgorithm {
+	// Ear clipping, O(n^2). Holes are first joined to the exterior by bridge
+	// edges, from the rightmost point of each hole to a vertex a ray cast
+	// right from it shows to be visible
+	EarClipping,
+	// Split into y-monotone pieces by a sweep that adds a diagonal at every
+	// split and merge vertex, then triangulate each piece with a stack.
+	// O(n log n) tests, the sweep status is a BTreeSet ordered by them
+	#[default]
+	Monotone,
+}
+
+pub fn triangulate_with<T: Scalar>(poly: &Polygon<T>, algorithm: TriangulationAlgorithm) -> Vec<[usize; 3]> {
+	let rings = Rings::new(poly);
+	let triangles = match algorithm {
+		TriangulationAlgorithm::EarClipping => ear_clipping(&rings),
+		TriangulationAlgorithm::Monotone => monotone(&rings),
+	};
+	triangles.into_iter().map(|t| t.map(|v| rings.ids[v])).collect()
+}
+
+// Every vertex of the polygon with its neighbours, turned so the inside is
+// always on the left: the exterior counterclockwise and the holes clockwise.
+// Rings are rejoined where they touch, so each runs round one side of the
+// point. A hole touching the exterior twice cuts the polygon in two, leaving
+// two counterclockwise rings
+struct Rings<T> {
+	points: Vec<Point<T>>,
+	next: Vec<usize>,
+	prev: Vec<usize>,
+	//index of each vertex in Polygon::vertices, as a vertex touching the
+	//inside of an edge adds a copy of itself there
+	ids: Vec<usize>,
+}
+
+impl<T: Scalar> Rings<T> {
+	fn new(poly: &Polygon<T>) -> Self {
+		let mut rings = Rings { points: Vec::new(), next: Vec::new(), prev: Vec::new(), ids: Vec::new() };
+		for (i, ring) in poly.rings().enumerate() {
+			let (start, n) = (rings.points.len(), ring.len());
+			let counterclockwise = Polygon { points: ring.to_vec(), orientation: false, holes: Vec::new() }.signed_double_area() > T::zero();
+			let forward = counterclockwise == (i == 0);
+			for k in 0..n {
+				let (after, before) = (start + (k + 1) % n, start + (k + n - 1) % n);
+				rings.next.push(if forward {after} else {before});
+				rings.prev.push(if forward {before} else {after});
+			}
+			rings.points.extend_from_slice(ring);
+		}
+		rings.ids = (0..rings.points.len()).collect();
+		rings.join_touching();
+		rings
+	}
+
+	// Splits edges at the vertices touching them, then swaps the next vertex
+	// of two in the same place whenever one ring sits in the other's corner.
+	// A sweep finds them, O(n log n)
+	fn join_touching(&mut self) {
+		let (points, n) = (&self.points, self.points.len());
+		let horizontal = |e: usize| points[e].y == points[self.next[e]].y;
+		let mut order: Vec<usize> = (0..n).collect();
+		order.sort_by(|&a, &b| sweep_order(&points[a], &points[b]));
+
+		//vertices in one place, and the edges with one of them inside
+		let mut groups: Vec<Vec<usize>> = Vec::new();
+		let mut splits = Vec::new();
+		let mut status: BTreeSet<Place<T>> = BTreeSet::new();
+		//horizontal edges are kept out of the status, at most one runs through
+		//the sweep point as they never overlap
+		let mut flat = None;
+		for group in order.chunk_by(|&a, &b| points[a].equals(&points[b])) {
+			let v = group[0];
+			for &u in group {
+				for e in [self.prev[u], u] {
+					if self.ends(e).1 == u {
+						if horizontal(e) {flat = None} else {status.remove(&Place { rings: &*self, at: Spot::Edge(e) });}
+					}
+				}
+			}
+			let through = flat.or_else(|| {
+				let e = status.range(Place { rings: &*self, at: Spot::Vertex(v) }..).next()?.index();
+				let (a, b) = self.ends(e);
+				(points[a].orientation(&points[b], &points[v]) == 0).then_some(e)
+			});
+			if let Some(e) = through {
+				splits.push((e, v, groups.len()));
+			}
+			if through.is_some() || group.len() > 1 {
+				groups.push(group.to_vec());
+			}
+			for &u in group {
+				for e in [self.prev[u], u] {
+					if self.ends(e).0 == u {
+						if horizontal(e) {flat = Some(e)} else {status.insert(Place { rings: &*self, at: Spot::Edge(e) });}
+					}
+				}
+			}
+		}
+		drop(status);
+
+		//several vertices can touch one edge, so they go in from its start
+		splits.sort_by(|&(e, v, _), &(f, w, _)| e.cmp(&f).then_with(|| {
+			let start = &self.points[e];
+			start.distance_squared(&self.points[v]).partial_cmp(&start.distance_squared(&self.points[w])).unwrap()
+		}));
+		let mut last = None;
+		for (e, v, g) in splits {
+			let tail = match last {
+				Some((f, x)) if f == e => x,
+				_ => e,
+			};
+			let (x, after) = (self.points.len(), self.next[tail]);
+			self.points.push(self.points[v].clone());
+			self.ids.push(self.ids[v]);
+			self.next.push(after);
+			self.prev.push(tail);
+			self.next[tail] = x;
+			self.prev[after] = x;
+			groups[g].push(x);
+			last = Some((e, x));
+		}
+
+		//corners at one point never overlap once each vertex is swapped with
+		//the one whose corner its ring lies in
+		for group in groups {
+			for (k, &c) in group.iter().enumerate() {
+				let toward = &self.points[self.next[c]];
+				if let Some(u) = group[..k].iter().copied().find(|&u| self.inside_corner(u, toward)) {
+					let (a, b) = (self.next[u], self.next[c]);
+					(self.next[u], self.prev[b]) = (b, u);
+					(self.next[c], self.prev[a]) = (a, c);
+				}
+			}
+		}
+	}
+
+	//upper and lower end of the edge from e to the next vertex
+	fn ends(&self, e: usize) -> (usize, usize) {
+		let f = self.next[e];
+		if sweep_order(&self.points[e], &self.points[f]) == Ordering::Less {(e, f)} else {(f, e)}
+	}
+
+	fn inside_corner(&self, p: usize, q: &Point<T>) -> bool {
+		inside_corner(&self.points[self.prev[p]], &self.points[p], &self.points[self.next[p]], q)
+	}
+}
+
+// q is strictly inside the corner at p, coming from a and going on to b with
+// the inside on the left, seen from p
+fn inside_corner<T: Scalar>(a: &Point<T>, p: &Point<T>, b: &Point<T>, q: &Point<T>) -> bool {
+	if a.orientation(p, b) == 1 {
+		a.orientation(p, q) == 2 || p.orientation(b, q) == 2
+	} else {
+		a.orientation(p, q) == 2 && p.orientation(b, q) == 2
+	}
+}
+
+// triangle a b c, counterclockwise, if it has any area
+fn push_triangle<T: Scalar>(triangles: &mut Vec<[usize; 3]>, points: &[Point<T>], a: usize, b: usize, c: usize) {
+	match points[a].orientation(&points[b], &points[c]) {
+		2 => triangles.push([a, b, c]),
+		1 => triangles.push([a, c, b]),
+		_ => {}
+	}
+}
+
+//EAR CLIPPING
+
+fn ear_clipping<T: Scalar>(rings: &Rings<T>) -> Vec<[usize; 3]> {
+	let mut triangles = Vec::new();
+	for ring in bridge_holes(rings) {
+		clip_ears(&rings.points, &ring, &mut triangles);
+	}
+	triangles
+}
+
+// Cuts ears off a ring of vertex indices, counterclockwise, until only one
+// triangle is left
+fn clip_ears<T: Scalar>(points: &[Point<T>], ring: &[usize], triangles: &mut Vec<[usize; 3]>) {
+	let n = ring.len();
+	let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+	let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+	let point = |i: usize| &points[ring[i]];
+
+	//i is a convex corner and no other vertex is in the triangle it cuts off
+	let is_ear = |i: usize, next: &[usize], prev: &[usize]| {
+		let (a, b, c) = (point(prev[i]), point(i), point(next[i]));
+		if a.orientation(b, c) != 2 {
+			return false;
+		}
+		let mut k = next[next[i]];
+		while k != prev[i] {
+			let p = point(k);
+			let shared = p.equals(a) || p.equals(b) || p.equals(c);
+			if !shared && a.orientation(b, p) != 1 && b.orientation(c, p) != 1 && c.orientation(a, p) != 1 {
+				return false;
+			}
+			k = next[k];
+		}
+		true
+	};
+
+	let mut ear: Vec<bool> = (0..n).map(|i| n >= 3 && is_ear(i, &next, &prev)).collect();
+	let (mut left, mut i, mut tried) = (n, 0, 0);
+	while left > 3 {
+		if ear[i] {
+			let (a, c) = (prev[i], next[i]);
+			triangles.push([ring[a], ring[i], ring[c]]);
+			next[a] = c;
+			prev[c] = a;
+			left -= 1;
+			ear[a] = is_ear(a, &next, &prev);
+			ear[c] = is_ear(c, &next, &prev);
+			i = c;
+			tried = 0;
+			continue;
+		}
+		i = next[i];
+		tried += 1;
+		if tried <= left {
+			continue;
+		}
+
+		//no ear left, which only happens when every remaining corner is straight
+		//or reflex. Drop a straight vertex, or give up on input that is not simple
+		let mut k = i;
+		let straight = loop {
+			if point(prev[k]).orientation(point(k), point(next[k])) == 0 {
+				break Some(k);
+			}
+			k = next[k];
+			if k == i {
+				break None;
+			}
+		};
+		let Some(k) = straight else {
+			break;
+		};
+		let (a, c) = (prev[k], next[k]);
+		next[a] = c;
+		prev[c] = a;
+		left -= 1;
+		ear[a] = is_ear(a, &next, &prev);
+		ear[c] = is_ear(c, &next, &prev);
+		i = c;
+		tried = 0;
+	}
+	if left == 3 {
+		push_triangle(triangles, points, ring[prev[i]], ring[i], ring[next[i]]);
+	}
+}
+
+// Joins every hole to the ring around it, giving a ring of vertex indices for
+// each counterclockwise ring that runs out along a bridge, round the hole and
+// back. Holes are taken from the one reaching furthest right, so a ray cast
+// right from the rightmost vertex of each only meets rings already joined.
+// The bridge goes to the far end of the first edge the ray meets, unless
+// vertices in the triangle between them hide it. O(n) for each hole.
+// Holes touching the exterior were already joined to it by Rings::new
+fn bridge_holes<T: Scalar>(rings: &Rings<T>) -> Vec<Vec<usize>> {
+	let points = &rings.points;
+	let walk = |start: usize| {
+		let mut ring = vec![start];
+		let mut k = rings.next[start];
+		while k != start {
+			ring.push(k);
+			k = rings.next[k];
+		}
+		ring
+	};
+
+	//rightmost vertex of each hole, then the top one
+	let rightmost = |a: &usize, b: &usize| points[*a].x.partial_cmp(&points[*b].x).unwrap().then(points[*a].y.partial_cmp(&points[*b].y).unwrap());
+	let (mut outer, mut holes) = (Vec::new(), Vec::new());
+	let mut seen = vec![false; points.len()];
+	for start in 0..points.len() {
+		if seen[start] {
+			continue;
+		}
+		let ring = walk(start);
+		for &v in &ring {
+			seen[v] = true;
+		}
+		let area = Polygon { points: ring.iter().map(|&v| points[v].clone()).collect(), orientation: false, holes: Vec::new() }.signed_double_area();
+		if area > T::zero() {
+			outer.push(ring);
+		} else {
+			holes.push((ring.iter().copied().max_by(rightmost).unwrap(), ring));
+		}
+	}
+	holes.sort_by(|a, b| rightmost(&b.0, &a.0));
+
+	for (m, hole) in holes {
+		let mp = &points[m];
+		//the ray leaves through the right side of a ring, where edges rise
+		let edge = |r: usize, j: usize| {
+			let ring: &Vec<usize> = &outer[r];
+			(&points[ring[j]], &points[ring[(j + 1) % ring.len()]])
+		};
+		let mut hit: Option<(usize, usize)> = None;
+		for (r, ring) in outer.iter().enumerate() {
+			for j in 0..ring.len() {
+				let (a, b) = edge(r, j);
+				if a.y <= mp.y && mp.y <= b.y && a.y < b.y && a.orientation(b, mp) == 2 && hit.is_none_or(|(s, k)| {
+					let (c, d) = edge(s, k);
+					meets_before(a, b, c, d)
+				}) {
+					hit = Some((r, j));
+				}
+			}
+		}
+		debug_assert!(hit.is_some(), "no ring around a hole");
+		let Some((r, j)) = hit else {
+			continue;
+		};
+
+		//the end of the edge on the ray, or else the one further right, sees m
+		//unless vertices inside the triangle between them and the ray are in
+		//the way. Then the one at the smallest angle to the ray does
+		let (a, b) = edge(r, j);
+		let c = if a.y == mp.y || (b.y != mp.y && a.x > b.x) {a} else {b};
+		let side = if c.y > mp.y {2} else {1};
+		let len = outer[r].len();
+		let mut best: Option<usize> = None;
+		for k in 0..len {
+			let ring = &outer[r];
+			let (u, p, w) = (&points[ring[(k + len - 1) % len]], &points[ring[k]], &points[ring[(k + 1) % len]]);
+			let between = if side == 2 {mp.y <= p.y && p.y <= c.y} else {c.y <= p.y && p.y <= mp.y};
+			if !between || p.x < mp.x || a.orientation(b, p) == 1 || mp.orientation(c, p) == side || !inside_corner(u, p, w, mp) {
+				continue;
+			}
+			if best.is_none_or(|best| {
+				let q = &points[ring[best]];
+				match mp.orientation(q, p) {
+					0 => mp.distance_squared(p) < mp.distance_squared(q),
+					turn => turn != side,
+				}
+			}) {
+				best = Some(k);
+			}
+		}
+		debug_assert!(best.is_some(), "no vertex to bridge a hole to");
+		let Some(j) = best else {
+			continue;
+		};
+		let ring = &mut outer[r];
+		//holes joined at their rightmost point have it twice, and the bridge
+		//arrives in the corner of only one
+		let m = hole.iter().copied().find(|&k| points[k].equals(mp) && rings.inside_corner(k, &points[ring[j]])).unwrap_or(m);
+		let mut joined = ring[..=j].to_vec();
+		joined.extend(walk(m));
+		joined.push(m);
+		joined.extend_from_slice(&ring[j..]);
+		*ring = joined;
+	}
+	outer
+}
+
+// Rising edge a b crosses the line through their common heights left of rising
+// edge c d, given they don't cross. Decided by an end of one that lies within
+// the height of the other
+fn meets_before<T: Scalar>(a: &Point<T>, b: &Point<T>, c: &Point<T>, d: &Point<T>) -> bool {
+	if c.y > a.y {
+		return !meets_before(c, d, a, b);
+	}
+	match c.orientation(d, a) {
+		2 => true,
+		1 => false,
+		_ if b.y <= d.y => c.orientation(d, b) == 2,
+		_ => a.orientation(b, d) == 1,
+	}
+}
+
+//MONOTONE
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Kind {
+	Start,
+	End,
+	Split,
+	Merge,
+	Regular,
+}
+
+// p comes before q in the sweep, from the top down and left to right
+fn above<T: Scalar>(p: &Point<T>, q: &Point<T>) -> bool {
+	p.y > q.y || (p.y == q.y && p.x < q.x)
+}
+
+fn sweep_order<T: Scalar>(p: &Point<T>, q: &Point<T>) -> Ordering {
+	if above(p, q) {Ordering::Less}
+	else if above(q, p) {Ordering::Greater}
+	else {Ordering::Equal}
+}
+
+// An edge in the monotone sweep status, or a vertex being looked up among
+// them, ordered left to right along the sweep line by orientation tests. The
+// edges in the status never cross, so they keep the same order all the way
+// down and a BTreeSet can hold them
+struct Place<'a, T> {
+	rings: &'a Rings<T>,
+	at: Spot,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Spot {
+	Edge(usize),
+	Vertex(usize),
+}
+
+impl<T> Place<'_, T> {
+	fn index(&self) -> usize {
+		match self.at {
+			Spot::Edge(i) | Spot::Vertex(i) => i,
+		}
+	}
+}
+
+impl<T: Scalar> Ord for Place<'_, T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		let points = &self.rings.points;
+		let ends = |e: usize| {
+			let (a, b) = self.rings.ends(e);
+			(&points[a], &points[b])
+		};
+		//which side of edge e the point p is on, Less if e passes left of it
+		let side = |e: usize, p: &Point<T>| {
+			let (a, b) = ends(e);
+			match a.orientation(b, p) {
+				2 => Ordering::Less,
+				1 => Ordering::Greater,
+				_ => Ordering::Equal,
+			}
+		};
+		match (self.at, other.at) {
+			(Spot::Edge(e), Spot::Vertex(v)) => if side(e, &points[v]) == Ordering::Less {Ordering::Less} else {Ordering::Greater},
+			(Spot::Vertex(_), Spot::Edge(_)) => other.cmp(self).reverse(),
+			(Spot::Edge(e), Spot::Edge(f)) if e != f => {
+				//judge by the ends of the edge starting lower, which lie within the other's span
+				let (low, high, flip) = if above(ends(e).0, ends(f).0) {(f, e, false)} else {(e, f, true)};
+				let (a, b) = ends(low);
+				let order = side(high, a).then_with(|| side(high, b)).then(high.cmp(&low));
+				if flip {order.reverse()} else {order}
+			}
+			_ => self.index().cmp(&other.index()),
+		}
+	}
+}
+
+impl<T: Scalar> PartialOrd for Place<'_, T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T: Scalar> PartialEq for Place<'_, T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<T: Scalar> Eq for Place<'_, T> {}
+
+fn monotone<T: Scalar>(rings: &Rings<T>) -> Vec<[usize; 3]> {
+	let points = &rings.points;
+	let n = points.len();
+	let kinds: Vec<Kind> = (0..n).map(|v| {
+		let (u, p, w) = (&points[rings.prev[v]], &points[v], &points[rings.next[v]]);
+		let convex = u.orientation(p, w) != 1;
+		match (above(p, u), above(p, w)) {
+			(true, true) => if convex {Kind::Start} else {Kind::Split},
+			(false, false) => if convex {Kind::End} else {Kind::Merge},
+			_ => Kind::Regular,
+		}
+	}).collect();
+	//vertices where rings touch have corners that never overlap. Taking the
+	//ones whose edges all end there first means no edge ending there is still
+	//in the status when one starting there goes in
+	let rank = |v: usize| match kinds[v] {
+		Kind::End | Kind::Merge => 0,
+		Kind::Regular => 1,
+		Kind::Start | Kind::Split => 2,
+	};
+	let mut order: Vec<usize> = (0..n).collect();
+	order.sort_by(|&a, &b| sweep_order(&points[a], &points[b]).then(rank(a).cmp(&rank(b))));
+
+	//edges are named by their first vertex and only those with the inside on
+	//their right are kept, left to right. Each runs down from its first vertex
+	let mut status = BTreeSet::new();
+	let mut helper = vec![0; n];
+	let mut diagonals = Vec::new();
+	for v in order {
+		let p = &points[v];
+		let edge = |e: usize| Place { rings, at: Spot::Edge(e) };
+		let find_left = |status: &BTreeSet<Place<T>>| status.range(..Place { rings, at: Spot::Vertex(v) }).next_back().map(|place| place.index());
+		let e = rings.prev[v];
+		match kinds[v] {
+			Kind::Start => {
+				status.insert(edge(v));
+				helper[v] = v;
+			}
+			Kind::End => {
+				if kinds[helper[e]] == Kind::Merge {
+					diagonals.push((v, helper[e]));
+				}
+				status.remove(&edge(e));
+			}
+			Kind::Split => {
+				if let Some(j) = find_left(&status) {
+					diagonals.push((v, helper[j]));
+					helper[j] = v;
+				}
+				status.insert(edge(v));
+				helper[v] = v;
+			}
+			Kind::Merge => {
+				if kinds[helper[e]] == Kind::Merge {
+					diagonals.push((v, helper[e]));
+				}
+				status.remove(&edge(e));
+				if let Some(j) = find_left(&status) {
+					if kinds[helper[j]] == Kind::Merge {
+						diagonals.push((v, helper[j]));
+					}
+					helper[j] = v;
+				}
+			}
+			//on a left chain the inside is to the right
+			Kind::Regular if above(&points[e], p) => {
+				if kinds[helper[e]] == Kind::Merge {
+					diagonals.push((v, helper[e]));
+				}
+				status.remove(&edge(e));
+				status.insert(edge(v));
+				helper[v] = v;
+			}
+			Kind::Regular => {
+				if let Some(j) = find_left(&status) {
+					if kinds[helper[j]] == Kind::Merge {
+						diagonals.push((v, helper[j]));
+					}
+					helper[j] = v;
+				}
+			}
+		}
+	}
+
+	//walk round each piece taking the sharpest left turn at every vertex
+	let mut outgoing: Vec<Vec<usize>> = (0..n).map(|v| vec![rings.next[v]]).collect();
+	for &(a, b) in &diagonals {
+		outgoing[a].push(b);
+		outgoing[b].push(a);
+	}
+	let mut used: Vec<Vec<bool>> = outgoing.iter().map(|out| vec![false; out.len()]).collect();
+	let mut triangles = Vec::new();
+	for start in 0..n {
+		for first in 0..outgoing[start].len() {
+			if used[start][first] {
+				continue;
+			}
+			let mut piece = Vec::new();
+			let (mut u, mut k) = (start, first);
+			while !used[u][k] {
+				used[u][k] = true;
+				piece.push(u);
+				let v = outgoing[u][k];
+				let turn = (0..outgoing[v].len()).min_by(|&x, &y| {
+					clockwise_from(&points[v], &points[u], &points[outgoing[v][x]], &points[outgoing[v][y]])
+				}).unwrap();
+				(u, k) = (v, turn);
+			}
+			triangulate_monotone(&piece, points, &mut triangles);
+		}
+	}
+	triangles
+}
+
+// Triangulates a y-monotone piece given counterclockwise, keeping a stack of
+// the vertices still waiting for a triangle
+fn triangulate_monotone<T: Scalar>(piece: &[usize], points: &[Point<T>], triangles: &mut Vec<[usize; 3]>) {
+	let k = piece.len();
+	if k < 3 {
+		return;
+	}
+	let mut order: Vec<usize> = (0..k).collect();
+	order.sort_by(|&a, &b| sweep_order(&points[piece[a]], &points[piece[b]]));
+	//counterclockwise from the top runs down the left chain
+	let (top, bottom) = (order[0], order[k - 1]);
+	let mut left = vec![false; k];
+	let mut i = top;
+	while i != bottom {
+		left[i] = true;
+		i = (i + 1) % k;
+	}
+
+	let mut stack = vec![order[0], order[1]];
+	for &j in &order[2..k - 1] {
+		let top = *stack.last().unwrap();
+		if left[j] != left[top] {
+			for w in stack.windows(2) {
+				push_triangle(triangles, points, piece[j], piece[w[0]], piece[w[1]]);
+			}
+			stack = vec![top, j];
+		} else {
+			let mut last = stack.pop().unwrap();
+			//cut off corners that bulge into the piece
+			let inward = if left[j] {2} else {1};
+			while let Some(&s) = stack.last() {
+				if points[piece[s]].orientation(&points[piece[last]], &points[piece[j]]) != inward {
+					break;
+				}
+				push_triangle(triangles, points, piece[j], piece[last], piece[s]);
+				last = stack.pop().unwrap();
+			}
+			stack.push(last);
+			stack.push(j);
+		}
+	}
+	let j = order[k - 1];
+	for w in stack.windows(2) {
+		push_triangle(triangles, points, piece[j], piece[w[0]], piece[w[1]]);
+	}
+}
@@ -0,0 +1,751 @@
+use crate::delaunay::circumcenter;
+use crate::line::{Line, Linear};
+use crate::point::Point;
+use crate::polygon::Polygon;
+use crate::random::{Lcg, SEED};
+use crate::scalar::{Scalar, Float};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+//VORONOI
+// One side of an edge of the diagram, running counterclockwise round the cell
+// of site. The twin runs the other way round the neighbouring cell, and is
+// None along the box
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HalfEdge {
+	pub origin: usize,
+	pub twin: Option<usize>,
+	pub next: usize,
+	pub prev: usize,
+	pub site: usize,
+}
+
+// Voronoi diagram clipped to a box, each cell both as a polygon and as a cycle
+// of half-edges. Vertices are stored once and shared by every cell round them.
+// A cell is None for a site whose cell misses the box, or one repeating an
+// earlier site
+#[derive(Clone, Debug)]
+pub struct Voronoi<T = f64> {
+	pub(crate) sites: Vec<Point<T>>,
+	pub(crate) cells: Vec<Option<Polygon<T>>>,
+	pub(crate) vertices: Vec<Point<T>>,
+	pub(crate) half_edges: Vec<HalfEdge>,
+	//a half-edge of each cell
+	pub(crate) faces: Vec<Option<usize>>,
+}
+
+//Methods
+impl<T: Scalar> Voronoi<T> {
+	pub fn sites(&self) -> &[Point<T>] {
+		&self.sites
+	}
+
+	pub fn cells(&self) -> &[Option<Polygon<T>>] {
+		&self.cells
+	}
+
+	pub fn cell(&self, site: usize) -> Option<&Polygon<T>> {
+		self.cells[site].as_ref()
+	}
+
+	pub fn vertices(&self) -> &[Point<T>] {
+		&self.vertices
+	}
+
+	pub fn half_edges(&self) -> &[HalfEdge] {
+		&self.half_edges
+	}
+
+	//the half-edges round the cell of site, counterclockwise
+	pub fn boundary(&self, site: usize) -> Vec<usize> {
+		let mut edges = Vec::new();
+		if let Some(first) = self.faces[site] {
+			let mut e = first;
+			loop {
+				edges.push(e);
+				e = self.half_edges[e].next;
+				if e == first {
+					break;
+				}
+			}
+		}
+		edges
+	}
+}
+
+// Voronoi diagram of the sites inside the box with corners c1 and c2, by
+// Fortune's sweep, O(n log n). Breakpoints closer than a small tolerance
+// relative to the size of the input are taken as one, so cocircular sites
+// give one vertex where all their cells meet, and collinear sites give cells
+// between parallel edges. Cells are counterclockwise
+pub fn voronoi<T: Float>(sites: &[Point<T>], c1: Point<T>, c2: Point<T>) -> Voronoi<T> {
+	let (min, max) = (Point::new(c1.x.min(c2.x), c1.y.min(c2.y)), Point::new(c1.x.max(c2.x), c1.y.max(c2.y)));
+	let scale = sites.iter().chain([&min, &max]).fold(T::zero(), |m, p| m.max(p.x.abs()).max(p.y.abs()));
+	let mut fortune = Fortune::new(sites, scale*tolerance::<T>());
+	fortune.run();
+	let mut diagram = Voronoi {
+		sites: sites.to_vec(),
+		cells: vec![None; sites.len()],
+		vertices: Vec::new(),
+		half_edges: Vec::new(),
+		faces: vec![None; sites.len()],
+	};
+	if min.x < max.x && min.y < max.y {
+		Clipper::new(fortune, min, max).build(&mut diagram);
+	}
+	diagram
+}
+
+// Machine epsilon to the power 3/4, about 2e-12 for f64 and 6e-6 for f32
+fn tolerance<T: Float>() -> T {
+	let two = T::from_i32(2);
+	let mut e = T::one();
+	while T::one() + e/two != T::one() {
+		e = e/two;
+	}
+	e.sqrt()*e.sqrt().sqrt()
+}
+
+const NIL: usize = usize::MAX;
+
+// A piece of the bisector of two sites, with left on its left. Each end is a
+// vertex, or None where it runs on forever
+struct Edge {
+	left: usize,
+	right: usize,
+	start: Option<usize>,
+	end: Option<usize>,
+}
+
+// An arc of the beach line, kept both in a treap ordered left to right and
+// in a list through prev and next
+struct Arc<T> {
+	site: usize,
+	prev: usize,
+	next: usize,
+	left: usize,
+	right: usize,
+	parent: usize,
+	priority: u64,
+	//the edge traced by the breakpoint with prev
+	edge: usize,
+	//where the arc is squeezed out, and the sweep position then
+	event: Option<(Point<T>, T)>,
+}
+
+// Circle events, ordered by sweep position then x
+#[derive(Copy, Clone)]
+struct Time<T> {
+	y: T,
+	x: T,
+	arc: usize,
+}
+
+impl<T: Scalar> PartialEq for Time<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<T: Scalar> Eq for Time<T> {}
+
+impl<T: Scalar> PartialOrd for Time<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T: Scalar> Ord for Time<T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		let by = |a: T, b: T| a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+		by(self.y.clone(), other.y.clone()).then(by(self.x.clone(), other.x.clone())).then(self.arc.cmp(&other.arc))
+	}
+}
+
+// The sweep runs upwards. Sites below it are in the beach line, the lower
+// envelope of parabolas equally far from their site and the sweep line
+struct Fortune<'a, T> {
+	sites: &'a [Point<T>],
+	arcs: Vec<Arc<T>>,
+	root: usize,
+	events: BTreeSet<Time<T>>,
+	vertices: Vec<Point<T>>,
+	edges: Vec<Edge>,
+	epsilon: T,
+	random: Lcg,
+}
+
+impl<'a, T: Float> Fortune<'a, T> {
+	fn new(sites: &'a [Point<T>], epsilon: T) -> Self {
+		Fortune {
+			sites,
+			arcs: Vec::new(),
+			root: NIL,
+			events: BTreeSet::new(),
+			vertices: Vec::new(),
+			edges: Vec::new(),
+			epsilon,
+			random: Lcg::new(SEED),
+		}
+	}
+
+	// Sites bottom to top, each repeat left out, with circle events between
+	fn run(&mut self) {
+		let by = |a: T, b: T| a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+		let mut order: Vec<usize> = (0..self.sites.len()).collect();
+		order.sort_by(|&a, &b| by(self.sites[a].y, self.sites[b].y).then(by(self.sites[a].x, self.sites[b].x)).then(a.cmp(&b)));
+		order.dedup_by(|b, a| self.sites[*a].equals(&self.sites[*b]));
+
+		let mut sites = order.into_iter().peekable();
+		loop {
+			let circle = self.events.first().copied();
+			match (sites.peek(), circle) {
+				(Some(&s), None) => {
+					self.add_site(s);
+					sites.next();
+				}
+				(Some(&s), Some(c)) if self.sites[s].y < c.y || (self.sites[s].y == c.y && self.sites[s].x < c.x) => {
+					self.add_site(s);
+					sites.next();
+				}
+				(_, Some(c)) => self.remove_arc(c.arc),
+				(None, None) => break,
+			}
+		}
+	}
+
+	// Splits the arc above the site, or where it lands on a breakpoint, starts
+	// a vertex there
+	fn add_site(&mut self, s: usize) {
+		let (x, directrix) = (self.sites[s].x, self.sites[s].y);
+		let eps = self.epsilon;
+		let (mut left, mut right) = (NIL, NIL);
+		let mut node = self.root;
+		while node != NIL {
+			let dxl = self.left_break(node, directrix).map(|b| b - x);
+			if dxl.is_some_and(|d| d > eps) {
+				node = self.arcs[node].left;
+				continue;
+			}
+			let dxr = self.right_break(node, directrix).map(|b| x - b);
+			if dxr.is_some_and(|d| d > eps) {
+				if self.arcs[node].right == NIL {
+					left = node;
+					break;
+				}
+				node = self.arcs[node].right;
+				continue;
+			}
+			if dxl.is_some_and(|d| d > -eps) && self.arcs[node].prev != NIL {
+				(left, right) = (self.arcs[node].prev, node);
+			} else if dxr.is_some_and(|d| d > -eps) {
+				(left, right) = (node, self.arcs[node].next);
+			} else {
+				(left, right) = (node, node);
+			}
+			break;
+		}
+
+		let arc = self.new_arc(s);
+		self.insert_after(left, arc);
+		if left == NIL && right == NIL {
+			return;
+		}
+		let between = if left != right && right != NIL {
+			circumcenter(&self.sites[self.arcs[left].site], &self.sites[s], &self.sites[self.arcs[right].site])
+		} else {
+			None
+		};
+		if let Some(center) = between {
+			self.detach_event(left);
+			self.detach_event(right);
+			let v = self.vertices.len();
+			self.vertices.push(center);
+			let (l, r) = (self.arcs[left].site, self.arcs[right].site);
+			self.set_end(self.arcs[right].edge, l, r, v);
+			self.arcs[arc].edge = self.new_edge(l, s, Some(v));
+			self.arcs[right].edge = self.new_edge(s, r, Some(v));
+			self.attach_event(left);
+			self.attach_event(right);
+		} else if right == NIL {
+			self.arcs[arc].edge = self.new_edge(self.arcs[left].site, s, None);
+		} else {
+			//splits the arc on the left, also when the breakpoint is in line with the site
+			self.detach_event(left);
+			let copy = self.new_arc(self.arcs[left].site);
+			self.insert_after(arc, copy);
+			let e = self.new_edge(self.arcs[left].site, s, None);
+			self.arcs[arc].edge = e;
+			self.arcs[copy].edge = e;
+			self.attach_event(left);
+			self.attach_event(copy);
+		}
+	}
+
+	// Circle event. Arcs next to it squeezed out at the same point go too, so
+	// cocircular sites meet at one vertex
+	fn remove_arc(&mut self, arc: usize) {
+		let (center, _) = self.arcs[arc].event.unwrap();
+		let v = self.vertices.len();
+		self.vertices.push(center);
+		let close = |event: Option<(Point<T>, T)>, eps: T| event.is_some_and(|(c, _)| (c.x - center.x).abs() < eps && (c.y - center.y).abs() < eps);
+
+		let (mut l, mut r) = (self.arcs[arc].prev, self.arcs[arc].next);
+		let mut gone = VecDeque::from([arc]);
+		self.detach_arc(arc);
+		while close(self.arcs[l].event, self.epsilon) {
+			let prev = self.arcs[l].prev;
+			gone.push_front(l);
+			self.detach_arc(l);
+			l = prev;
+		}
+		gone.push_front(l);
+		self.detach_event(l);
+		while close(self.arcs[r].event, self.epsilon) {
+			let next = self.arcs[r].next;
+			gone.push_back(r);
+			self.detach_arc(r);
+			r = next;
+		}
+		gone.push_back(r);
+		self.detach_event(r);
+
+		for i in 1..gone.len() {
+			let (a, b) = (gone[i - 1], gone[i]);
+			self.set_end(self.arcs[b].edge, self.arcs[a].site, self.arcs[b].site, v);
+		}
+		self.arcs[r].edge = self.new_edge(self.arcs[l].site, self.arcs[r].site, Some(v));
+		self.attach_event(l);
+		self.attach_event(r);
+	}
+
+	// Queues the point where arc is squeezed out, if its breakpoints close in
+	fn attach_event(&mut self, arc: usize) {
+		let (prev, next) = (self.arcs[arc].prev, self.arcs[arc].next);
+		if prev == NIL || next == NIL {
+			return;
+		}
+		let [l, c, r] = [prev, arc, next].map(|a| &self.sites[self.arcs[a].site]);
+		if self.arcs[prev].site == self.arcs[next].site || l.orientation(c, r) != 2 {
+			return;
+		}
+		let Some(center) = circumcenter(l, c, r) else {
+			return;
+		};
+		let y = center.y + center.distance(c);
+		self.arcs[arc].event = Some((center, y));
+		self.events.insert(Time { y, x: center.x, arc });
+	}
+
+	fn detach_event(&mut self, arc: usize) {
+		if let Some((center, y)) = self.arcs[arc].event.take() {
+			self.events.remove(&Time { y, x: center.x, arc });
+		}
+	}
+
+	//where the breakpoint traced by edge from left to right stops
+	fn set_end(&mut self, edge: usize, left: usize, right: usize, v: usize) {
+		let edge = &mut self.edges[edge];
+		if edge.left == left && edge.right == right {
+			edge.end = Some(v);
+		} else {
+			edge.start = Some(v);
+		}
+	}
+
+	fn new_edge(&mut self, left: usize, right: usize, start: Option<usize>) -> usize {
+		self.edges.push(Edge { left, right, start, end: None });
+		self.edges.len() - 1
+	}
+
+	// x of the breakpoint between arc and the one before it, with the sweep
+	// at directrix. None if there is no arc before it
+	fn left_break(&self, arc: usize, directrix: T) -> Option<T> {
+		let two = T::from_i32(2);
+		let focus = self.sites[self.arcs[arc].site];
+		let p = focus.y - directrix;
+		if p == T::zero() {
+			return Some(focus.x);
+		}
+		let prev = self.arcs[arc].prev;
+		if prev == NIL {
+			return None;
+		}
+		let other = self.sites[self.arcs[prev].site];
+		let q = other.y - directrix;
+		if q == T::zero() {
+			return Some(other.x);
+		}
+		let h = other.x - focus.x;
+		let a = T::one()/p - T::one()/q;
+		let b = h/q;
+		if a == T::zero() {
+			return Some((focus.x + other.x)/two);
+		}
+		let discriminant = b*b - two*a*(h*h/(-two*q) - other.y + q/two + focus.y - p/two);
+		Some((-b + discriminant.max(T::zero()).sqrt())/a + focus.x)
+	}
+
+	//None if the arc runs on forever to the right
+	fn right_break(&self, arc: usize, directrix: T) -> Option<T> {
+		let next = self.arcs[arc].next;
+		if next != NIL {
+			return self.left_break(next, directrix);
+		}
+		let focus = self.sites[self.arcs[arc].site];
+		if focus.y == directrix {Some(focus.x)} else {None}
+	}
+
+	//Treap
+	fn new_arc(&mut self, site: usize) -> usize {
+		let priority = self.random.next();
+		self.arcs.push(Arc { site, prev: NIL, next: NIL, left: NIL, right: NIL, parent: NIL, priority, edge: NIL, event: None });
+		self.arcs.len() - 1
+	}
+
+	//puts arc just after a, or first if a is NIL
+	fn insert_after(&mut self, a: usize, arc: usize) {
+		let next = if a == NIL {self.first()} else {self.arcs[a].next};
+		if a != NIL && self.arcs[a].right == NIL {
+			self.arcs[a].right = arc;
+			self.arcs[arc].parent = a;
+		} else if self.root == NIL {
+			self.root = arc;
+		} else {
+			let mut m = if a == NIL {self.root} else {self.arcs[a].right};
+			while self.arcs[m].left != NIL {
+				m = self.arcs[m].left;
+			}
+			self.arcs[m].left = arc;
+			self.arcs[arc].parent = m;
+		}
+		self.arcs[arc].prev = a;
+		self.arcs[arc].next = next;
+		if a != NIL {
+			self.arcs[a].next = arc;
+		}
+		if next != NIL {
+			self.arcs[next].prev = arc;
+		}
+		while self.arcs[arc].parent != NIL && self.arcs[arc].priority > self.arcs[self.arcs[arc].parent].priority {
+			self.rotate_up(arc);
+		}
+	}
+
+	fn first(&self) -> usize {
+		let mut m = self.root;
+		while m != NIL && self.arcs[m].left != NIL {
+			m = self.arcs[m].left;
+		}
+		m
+	}
+
+	//takes arc out of the beach line
+	fn detach_arc(&mut self, arc: usize) {
+		self.detach_event(arc);
+		loop {
+			let (l, r) = (self.arcs[arc].left, self.arcs[arc].right);
+			let child = match (l, r) {
+				(NIL, NIL) => break,
+				(NIL, _) => r,
+				(_, NIL) => l,
+				_ => if self.arcs[l].priority > self.arcs[r].priority {l} else {r},
+			};
+			self.rotate_up(child);
+		}
+		let parent = self.arcs[arc].parent;
+		if parent == NIL {
+			self.root = NIL;
+		} else if self.arcs[parent].left == arc {
+			self.arcs[parent].left = NIL;
+		} else {
+			self.arcs[parent].right = NIL;
+		}
+		let (prev, next) = (self.arcs[arc].prev, self.arcs[arc].next);
+		if prev != NIL {
+			self.arcs[prev].next = next;
+		}
+		if next != NIL {
+			self.arcs[next].prev = prev;
+		}
+	}
+
+	//moves x above its parent
+	fn rotate_up(&mut self, x: usize) {
+		let p = self.arcs[x].parent;
+		let g = self.arcs[p].parent;
+		if self.arcs[p].left == x {
+			let moved = self.arcs[x].right;
+			self.arcs[p].left = moved;
+			if moved != NIL {
+				self.arcs[moved].parent = p;
+			}
+			self.arcs[x].right = p;
+		} else {
+			let moved = self.arcs[x].left;
+			self.arcs[p].right = moved;
+			if moved != NIL {
+				self.arcs[moved].parent = p;
+			}
+			self.arcs[x].left = p;
+		}
+		self.arcs[p].parent = x;
+		self.arcs[x].parent = g;
+		if g == NIL {
+			self.root = x;
+		} else if self.arcs[g].left == p {
+			self.arcs[g].left = x;
+		} else {
+			self.arcs[g].right = x;
+		}
+	}
+}
+
+// Cuts the edges of the sweep down to the box and closes each cell along it.
+// Points on the box are kept once by position, and ends of pieces too short
+// to keep are merged
+struct Clipper<'a, T: Scalar> {
+	fortune: Fortune<'a, T>,
+	min: Point<T>,
+	max: Point<T>,
+	vertices: Vec<Point<T>>,
+	on_box: HashMap<(<T as Scalar>::Key, <T as Scalar>::Key), usize>,
+	merged: Vec<usize>,
+}
+
+impl<'a, T: Float> Clipper<'a, T> {
+	fn new(fortune: Fortune<'a, T>, min: Point<T>, max: Point<T>) -> Self {
+		let vertices = fortune.vertices.clone();
+		let merged = (0..vertices.len()).collect();
+		let mut clipper = Clipper { fortune, min, max, vertices, on_box: HashMap::new(), merged };
+		for corner in [min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)] {
+			clipper.box_vertex(corner);
+		}
+		clipper
+	}
+
+	fn build(mut self, diagram: &mut Voronoi<T>) {
+		let sites = self.fortune.sites;
+		let eps = self.fortune.epsilon;
+
+		//the part of each edge in the box, merging the ends of those too short to keep
+		let mut pieces = Vec::new();
+		for i in 0..self.fortune.edges.len() {
+			if let Some((a, b)) = self.clip(i) {
+				if self.vertices[a].distance(&self.vertices[b]) <= eps {
+					self.merge(a, b);
+				} else {
+					pieces.push((a, b, self.fortune.edges[i].left, self.fortune.edges[i].right));
+				}
+			}
+		}
+		//each piece on both its cells, running with the cell on its left, ordered
+		//counterclockwise by the direction to the neighbour
+		let mut around: Vec<Vec<(usize, usize, usize)>> = vec![Vec::new(); sites.len()];
+		for (a, b, left, right) in pieces {
+			around[left].push((a, b, right));
+			around[right].push((b, a, left));
+		}
+		for (s, edges) in around.iter_mut().enumerate() {
+			let angle = |n: usize| (sites[n].y - sites[s].y).atan2(sites[n].x - sites[s].x);
+			edges.sort_by(|a, b| angle(a.2).partial_cmp(&angle(b.2)).unwrap_or(Ordering::Equal));
+		}
+
+		//an edge cut by the box just beside where the next one starts is joined to it
+		for edges in &around {
+			for i in 0..edges.len() {
+				let (b, next) = (edges[i].1, edges[(i + 1) % edges.len()].0);
+				if self.vertices[b].distance(&self.vertices[next]) <= eps {
+					self.merge(b, next);
+				}
+			}
+		}
+		for edges in around.iter_mut() {
+			for edge in edges.iter_mut() {
+				(edge.0, edge.1) = (self.find(edge.0), self.find(edge.1));
+			}
+			edges.retain(|edge| edge.0 != edge.1);
+		}
+
+		//the site nearest the middle of the box owns it if no edge crosses it
+		let middle = Point::new((self.min.x + self.max.x)/T::from_i32(2), (self.min.y + self.max.y)/T::from_i32(2));
+		let nearest = (0..sites.len()).min_by(|&a, &b| sites[a].distance_squared(&middle).partial_cmp(&sites[b].distance_squared(&middle)).unwrap_or(Ordering::Equal));
+
+		//each cell as a ring of (origin, neighbour) with None along the box
+		let mut rings = vec![Vec::new(); sites.len()];
+		for (s, edges) in around.iter().enumerate() {
+			if edges.is_empty() {
+				if nearest == Some(s) {
+					rings[s] = (0..4).map(|k| (self.on_box[&self.corner_key(k)], None)).collect();
+				}
+				continue;
+			}
+			let ring = &mut rings[s];
+			for i in 0..edges.len() {
+				let (a, b, neighbour) = edges[i];
+				ring.push((a, Some(neighbour)));
+				let next = edges[(i + 1) % edges.len()].0;
+				if b != next {
+					ring.push((b, None));
+					ring.extend(self.corners_between(b, next).into_iter().map(|c| (c, None)));
+				}
+			}
+		}
+		self.assemble(diagram, rings);
+	}
+
+	// Keeps rings enclosing some area, with their vertices renumbered
+	fn assemble(&self, diagram: &mut Voronoi<T>, rings: Vec<Vec<(usize, Option<usize>)>>) {
+		let mut index = HashMap::new();
+		let mut at = HashMap::new();
+		for (s, ring) in rings.into_iter().enumerate() {
+			if ring.len() < 3 {
+				continue;
+			}
+			let points: Vec<Point<T>> = ring.iter().map(|&(v, _)| self.vertices[v]).collect();
+			let Ok(poly) = Polygon::from_vec(points) else {
+				continue;
+			};
+			if poly.signed_double_area() <= T::zero() {
+				continue;
+			}
+			diagram.cells[s] = Some(poly);
+			let first = diagram.half_edges.len();
+			diagram.faces[s] = Some(first);
+			let n = ring.len();
+			for (i, &(v, _)) in ring.iter().enumerate() {
+				let origin = *index.entry(v).or_insert_with(|| {
+					diagram.vertices.push(self.vertices[v]);
+					diagram.vertices.len() - 1
+				});
+				let next = first + (i + 1) % n;
+				let prev = first + (i + n - 1) % n;
+				diagram.half_edges.push(HalfEdge { origin, twin: None, next, prev, site: s });
+			}
+			for (i, &(v, neighbour)) in ring.iter().enumerate() {
+				if neighbour.is_some() {
+					at.insert((v, ring[(i + 1) % n].0), first + i);
+				}
+			}
+		}
+		for (&(a, b), &e) in &at {
+			diagram.half_edges[e].twin = at.get(&(b, a)).copied();
+		}
+	}
+
+	// The part of edge i inside the box as two vertices, the one the edge
+	// starts from first
+	fn clip(&mut self, i: usize) -> Option<(usize, usize)> {
+		let &Edge { left, right, start, end } = &self.fortune.edges[i];
+		let sites = self.fortune.sites;
+		let line = Line::bisector(&sites[left], &sites[right]).ok()?;
+		let (m, (dx, dy)) = (line.p1(), line.direction());
+		let along = |p: &Point<T>| ((p.x - m.x)*dx + (p.y - m.y)*dy)/(dx*dx + dy*dy);
+
+		//each end as a parameter, and the side of the box that cut it there
+		let mut low = start.map(|v| (along(&self.vertices[v]), None));
+		let mut high = end.map(|v| (along(&self.vertices[v]), None));
+		for (d, o, lo, hi, axis) in [(dx, m.x, self.min.x, self.max.x, 0), (dy, m.y, self.min.y, self.max.y, 1)] {
+			if d == T::zero() {
+				if o < lo || o > hi {
+					return None;
+				}
+				continue;
+			}
+			let (enter, exit) = if d > T::zero() {((lo - o)/d, (hi - o)/d)} else {((hi - o)/d, (lo - o)/d)};
+			let (enter_at, exit_at) = if d > T::zero() {(lo, hi)} else {(hi, lo)};
+			if low.is_none_or(|(t, _)| t < enter) {
+				low = Some((enter, Some((axis, enter_at))));
+			}
+			if high.is_none_or(|(t, _)| t > exit) {
+				high = Some((exit, Some((axis, exit_at))));
+			}
+		}
+		let ((t0, cut0), (t1, cut1)) = (low?, high?);
+		if t0 >= t1 {
+			return None;
+		}
+		//a vertex only just outside, by rounding, is moved onto the box
+		let mut cut_end = |t: T, cut: Option<(usize, T)>, vertex: Option<usize>| {
+			let (p, v) = match (cut, vertex) {
+				(Some((axis, at)), _) => {
+					let (x, y) = (m.x + dx*t, m.y + dy*t);
+					(if axis == 0 {Point::new(at, y)} else {Point::new(x, at)}, None)
+				}
+				(None, v) => (self.vertices[v.unwrap()], v),
+			};
+			let inside = Point::new(p.x.max(self.min.x).min(self.max.x), p.y.max(self.min.y).min(self.max.y));
+			match v {
+				Some(v) if inside.equals(&p) => v,
+				_ => self.box_vertex(inside),
+			}
+		};
+		Some((cut_end(t0, cut0, start), cut_end(t1, cut1, end)))
+	}
+
+	fn box_vertex(&mut self, p: Point<T>) -> usize {
+		let key = (p.x.key(), p.y.key());
+		if let Some(&v) = self.on_box.get(&key) {
+			return v;
+		}
+		let v = self.vertices.len();
+		self.vertices.push(p);
+		self.merged.push(v);
+		self.on_box.insert(key, v);
+		v
+	}
+
+	//joins two vertices, keeping one on the box if there is one
+	fn merge(&mut self, a: usize, b: usize) {
+		let (a, b) = (self.find(a), self.find(b));
+		if a == b {
+			return;
+		}
+		if self.position(a).is_some() {
+			self.merged[b] = a;
+		} else {
+			self.merged[a] = b;
+		}
+	}
+
+	fn find(&mut self, mut v: usize) -> usize {
+		while self.merged[v] != v {
+			self.merged[v] = self.merged[self.merged[v]];
+			v = self.merged[v];
+		}
+		v
+	}
+
+	// How far counterclockwise round the box from the corner at min a point on
+	// it is, one per side. Points within the tolerance of a side count as on it.
+	// None for points inside
+	fn position(&self, v: usize) -> Option<T> {
+		let (p, min, max) = (self.vertices[v], self.min, self.max);
+		let (w, h) = (max.x - min.x, max.y - min.y);
+		let gaps = [p.y - min.y, max.x - p.x, max.y - p.y, p.x - min.x];
+		let side = (0..4).min_by(|&a, &b| gaps[a].partial_cmp(&gaps[b]).unwrap_or(Ordering::Equal)).unwrap();
+		if gaps[side] > self.fortune.epsilon {
+			return None;
+		}
+		let along = [(p.x - min.x)/w, (p.y - min.y)/h, (max.x - p.x)/w, (max.y - p.y)/h][side];
+		let s = T::from_i32(side as i32) + along.max(T::zero()).min(T::one());
+		Some(if s >= T::from_i32(4) {T::zero()} else {s})
+	}
+
+	//corners passed going counterclockwise round the box from a to b
+	fn corners_between(&self, a: usize, b: usize) -> Vec<usize> {
+		let (Some(from), Some(mut to)) = (self.position(a), self.position(b)) else {
+			return Vec::new();
+		};
+		if to <= from {
+			to = to + T::from_i32(4);
+		}
+		(1..8).filter(|&k| from < T::from_i32(k) && T::from_i32(k) < to).map(|k| self.on_box[&self.corner_key(k % 4)]).collect()
+	}
+
+	fn corner_key(&self, k: i32) -> (<T as Scalar>::Key, <T as Scalar>::Key) {
+		let (min, max) = (self.min, self.max);
+		let corner = [min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)][k as usize];
+		(corner.x.key(), corner.y.key())
+	}
+}